@@ -14,11 +14,21 @@ pub const COLOR_DONE_OK: u32 = 0xFF00_AA22;
 pub const COLOR_DONE_ERROR: u32 = 0xFFAA_0022;
 pub const COLOR_READ: u32 = 0xFF00_9999;
 pub const COLOR_WRITE: u32 = 0xFF99_0099;
+pub const COLOR_PIVOT: u32 = 0xFFDD_AA00;
+pub const COLOR_EQUAL: u32 = 0xFF44_66CC;
+pub const COLOR_FALLBACK: u32 = 0xFF88_5533;
+pub const COLOR_BLOCK: u32 = 0xFF55_8855;
+pub const COLOR_RUN: u32 = 0xFF66_77BB;
+pub const COLOR_MERGE: u32 = 0xFFCC_7744;
+pub const COLOR_CACHE_HIT: u32 = 0xFF33_7755;
+pub const COLOR_CACHE_OUTER_HIT: u32 = 0xFF99_8833;
+pub const COLOR_CACHE_MISS: u32 = 0xFF99_3344;
 
 pub struct ListVisualization<T>
 where
     T: Copy + Ord + Into<f64> + std::fmt::Display,
 {
+    pub top: usize,
     pub height: usize,
     pub width: usize,
     pub margin: usize,
@@ -36,16 +46,33 @@ where
         height: usize,
         margin: usize,
     ) -> ListVisualization<T> {
-        let largest = data.iter().fold(0.0 as f64, |acc, &x| x.into().max(acc));
+        let largest = data.iter().fold(0.0f64, |acc, &x| x.into().max(acc));
+        ListVisualization::with_layout(data.len(), largest, 0, width, height, margin)
+    }
+    pub fn with_layout(
+        len: usize,
+        largest: f64,
+        top: usize,
+        width: usize,
+        height: usize,
+        margin: usize,
+    ) -> ListVisualization<T> {
+        // Drop the margins if there are too many elements to fit them
+        let margin = if (len + 1) * margin < width {
+            margin
+        } else {
+            0
+        };
         let unit_height = (height - 2 * margin) as f64 / largest;
-        let element_width = (width - (data.len() + 1) * margin) as f64 / data.len() as f64;
-        let mut element_positions = Vec::with_capacity(data.len());
+        let element_width = (width - (len + 1) * margin) as f64 / len as f64;
+        let mut element_positions = Vec::with_capacity(len);
         let mut position = margin as f64;
-        for _ in 0..data.len() {
+        for _ in 0..len {
             element_positions.push((position as usize, (position + element_width) as usize));
             position += element_width + margin as f64;
         }
         ListVisualization {
+            top,
             height,
             width,
             margin,
@@ -63,21 +90,21 @@ where
             let y_bound =
                 self.height - (((element.value.into()) * self.unit_height) as usize + self.margin);
             // Draw background above element
-            for y in 0..y_bound {
+            for y in self.top..self.top + y_bound {
                 let y_offset = y * self.width;
                 for e in framebuffer[y_offset + x_bounds.0..y_offset + x_bounds.1].iter_mut() {
                     *e = COLOR_BACKGROUND;
                 }
             }
             // Draw background below element (margin)
-            for y in self.height - self.margin..self.height {
+            for y in self.top + self.height - self.margin..self.top + self.height {
                 let y_offset = y * self.width;
                 for e in framebuffer[y_offset + x_bounds.0..y_offset + x_bounds.1].iter_mut() {
                     *e = COLOR_BACKGROUND;
                 }
             }
             // Draw element bar
-            for y in y_bound..self.height - self.margin {
+            for y in self.top + y_bound..self.top + self.height - self.margin {
                 let y_offset = y * self.width;
                 for e in framebuffer[y_offset + x_bounds.0..y_offset + x_bounds.1].iter_mut() {
                     *e = element.color;
@@ -87,44 +114,111 @@ where
     }
 }
 
+/// Which part of the window a `ListUpdate` refers to
+#[derive(Copy, Clone, PartialEq)]
+pub enum Band {
+    Main,
+    Buffer(&'static str),
+}
+
 pub struct ListUpdate<T>
 where
     T: Copy + Ord + Into<f64> + std::fmt::Display,
 {
+    pub band: Band,
     pub index: usize,
     pub value: T,
     pub color: u32,
 }
 
+pub enum WindowUpdate<T>
+where
+    T: Copy + Ord + Into<f64> + std::fmt::Display,
+{
+    Draw(Vec<ListUpdate<T>>),
+    AllocBuffer(&'static str, usize),
+    FreeBuffer(&'static str),
+    /// Histogram, index, new count if it changed and color
+    DrawHistogram(&'static str, usize, Option<usize>, u32),
+    AllocHistogram(&'static str, usize),
+    FreeHistogram(&'static str),
+    /// Keep the current frame on screen for this many more refresh periods
+    Hold(usize),
+}
+
+struct BufferBand<T>
+where
+    T: Copy + Ord + Into<f64> + std::fmt::Display,
+{
+    name: &'static str,
+    values: Vec<Option<T>>,
+    visualization: ListVisualization<T>,
+}
+
+struct HistogramBand {
+    name: &'static str,
+    counts: Vec<usize>,
+    // Count drawn at full height, grows as needed
+    largest: usize,
+    visualization: ListVisualization<u32>,
+}
+impl HistogramBand {
+    fn draw(&self, index: usize, color: u32, framebuffer: &mut [u32]) {
+        let update = ListUpdate {
+            band: Band::Main,
+            index,
+            value: self.counts[index] as u32,
+            color,
+        };
+        self.visualization
+            .draw(std::iter::once(update), framebuffer);
+    }
+}
+
 pub struct ListVisualizationWindow<T>
 where
     T: Copy + Ord + Into<f64> + std::fmt::Display,
 {
     window: Window,
-    channel: (SyncSender<Vec<ListUpdate<T>>>, Receiver<Vec<ListUpdate<T>>>),
+    channel: (SyncSender<WindowUpdate<T>>, Receiver<WindowUpdate<T>>),
     visualization: ListVisualization<T>,
+    buffer_height: usize,
+    buffers: Vec<BufferBand<T>>,
+    histograms: Vec<HistogramBand>,
     framebuffer: Vec<u32>,
     revert_changes: Vec<ListUpdate<T>>,
+    // Histogram bars drawn since the last update, and to redraw with the fill color
+    histogram_changes: Vec<(&'static str, usize)>,
+    revert_histograms: Vec<(&'static str, usize)>,
+    // Color of each main element when not being read or written
+    base_colors: Vec<u32>,
 }
 impl<T> ListVisualizationWindow<T>
 where
     T: Copy + Ord + Into<f64> + std::fmt::Display,
 {
-    pub fn new(title: &str, visualization: ListVisualization<T>) -> ListVisualizationWindow<T> {
-        let window = Window::new(
-            title,
-            visualization.width,
-            visualization.height,
-            WindowOptions::default(),
-        )
-        .unwrap();
+    /// `buffer_height` pixels are reserved below `visualization` for auxiliary buffers
+    pub fn new(
+        title: &str,
+        visualization: ListVisualization<T>,
+        buffer_height: usize,
+    ) -> ListVisualizationWindow<T> {
+        let height = visualization.height + buffer_height;
+        let window =
+            Window::new(title, visualization.width, height, WindowOptions::default()).unwrap();
         let channel = sync_channel(8); // Arbitrary buffer size
         let mut visualization_window = ListVisualizationWindow {
             window,
             channel,
-            framebuffer: vec![0; visualization.width * visualization.height],
+            framebuffer: vec![0; visualization.width * height],
+            base_colors: vec![COLOR_FILL; visualization.element_positions.len()],
             visualization,
+            buffer_height,
+            buffers: Vec::new(),
+            histograms: Vec::new(),
             revert_changes: Vec::with_capacity(4),
+            histogram_changes: Vec::with_capacity(1),
+            revert_histograms: Vec::with_capacity(1),
         };
         visualization_window
             .window
@@ -133,6 +227,16 @@ where
         visualization_window
     }
     pub fn update(&mut self, changes: Vec<ListUpdate<T>>) -> minifb::Result<()> {
+        // Revert previous histogram changes unless they were drawn again
+        let revert_histograms = std::mem::take(&mut self.revert_histograms);
+        for (name, index) in revert_histograms {
+            if !self.histogram_changes.contains(&(name, index)) {
+                if let Some(band) = self.histograms.iter().find(|h| h.name == name) {
+                    band.draw(index, COLOR_FILL, &mut self.framebuffer);
+                }
+            }
+        }
+        self.revert_histograms = std::mem::take(&mut self.histogram_changes);
         // If there are no changes, just run self.window.update()
         if changes.is_empty() && self.revert_changes.is_empty() {
             // Just doing self.window.update() does not keep refreshing the window on Linux X11 :(
@@ -142,55 +246,205 @@ where
         let mut revert_changes_previous = Vec::with_capacity(4);
         std::mem::swap(&mut revert_changes_previous, &mut self.revert_changes);
         for change in changes.iter() {
-            // Ignore previous changes to revert if same index is in current changes
+            // Ignore previous changes to revert if same element is in current changes
             if let Some(to_remove) = revert_changes_previous
                 .iter()
-                .position(|x| x.index == change.index)
+                .position(|x| x.band == change.band && x.index == change.index)
             {
                 revert_changes_previous.remove(to_remove);
             }
-            // Store next changes to revert
+            // Store next changes to revert, other colors become the new base color
             if [COLOR_READ, COLOR_WRITE].contains(&change.color) {
+                let color = match change.band {
+                    Band::Main => self.base_colors[change.index],
+                    Band::Buffer(_) => COLOR_FILL,
+                };
                 self.revert_changes.push(ListUpdate {
+                    band: change.band,
                     index: change.index,
                     value: change.value,
-                    color: COLOR_FILL,
+                    color,
                 });
+            } else if change.band == Band::Main {
+                self.base_colors[change.index] = change.color;
             }
         }
         // Merge previous changes to revert and new changes
-        let all_changes = revert_changes_previous
-            .into_iter()
-            .chain(changes.into_iter());
+        let all_changes = revert_changes_previous.into_iter().chain(changes);
         // Redraw changed elements on the framebuffer and update window
-        self.visualization.draw(all_changes, &mut self.framebuffer);
+        for change in all_changes {
+            match change.band {
+                Band::Main => self
+                    .visualization
+                    .draw(std::iter::once(change), &mut self.framebuffer),
+                Band::Buffer(name) => {
+                    if let Some(band) = self.buffers.iter_mut().find(|b| b.name == name) {
+                        band.values[change.index] = Some(change.value);
+                        band.visualization
+                            .draw(std::iter::once(change), &mut self.framebuffer);
+                    }
+                }
+            }
+        }
         self.window.update_with_buffer(&self.framebuffer)
     }
+    /// Draw a histogram bar, which is shown on the next `update`
+    pub fn draw_histogram(
+        &mut self,
+        name: &'static str,
+        index: usize,
+        count: Option<usize>,
+        color: u32,
+    ) {
+        if let Some(position) = self.histograms.iter().position(|h| h.name == name) {
+            if let Some(count) = count {
+                let band = &mut self.histograms[position];
+                band.counts[index] = count;
+                if count > band.largest {
+                    band.largest = count.next_power_of_two();
+                    self.layout_buffers();
+                }
+            }
+            self.histograms[position].draw(index, color, &mut self.framebuffer);
+            self.histogram_changes.push((name, index));
+        }
+    }
+    pub fn alloc_buffer(&mut self, name: &'static str, len: usize) {
+        self.buffers.push(BufferBand {
+            name,
+            values: vec![None; len],
+            visualization: ListVisualization::with_layout(0, 1.0, 0, 0, 0, 0),
+        });
+        self.layout_buffers();
+    }
+    pub fn free_buffer(&mut self, name: &'static str) {
+        self.buffers.retain(|b| b.name != name);
+        self.revert_changes.retain(|x| x.band != Band::Buffer(name));
+        self.layout_buffers();
+    }
+    pub fn alloc_histogram(&mut self, name: &'static str, len: usize) {
+        self.histograms.push(HistogramBand {
+            name,
+            counts: vec![0; len],
+            largest: 1,
+            visualization: ListVisualization::with_layout(0, 1.0, 0, 0, 0, 0),
+        });
+        self.layout_buffers();
+    }
+    pub fn free_histogram(&mut self, name: &'static str) {
+        self.histograms.retain(|h| h.name != name);
+        self.histogram_changes.retain(|&(n, _)| n != name);
+        self.revert_histograms.retain(|&(n, _)| n != name);
+        self.layout_buffers();
+    }
+    fn layout_buffers(&mut self) {
+        // Clear the whole buffer area
+        let top = self.visualization.top + self.visualization.height;
+        for e in self.framebuffer[top * self.visualization.width..].iter_mut() {
+            *e = COLOR_BACKGROUND;
+        }
+        let bands = self.buffers.len() + self.histograms.len();
+        if bands == 0 {
+            return;
+        }
+        // Stack live buffers vertically, scaled like the main list, followed by histograms
+        let largest = (self.visualization.height - 2 * self.visualization.margin) as f64
+            / self.visualization.unit_height;
+        let band_height = self.buffer_height / bands;
+        for (i, band) in self.buffers.iter_mut().enumerate() {
+            band.visualization = ListVisualization::with_layout(
+                band.values.len(),
+                largest,
+                top + i * band_height,
+                self.visualization.width,
+                band_height,
+                self.visualization.margin,
+            );
+            let name = band.name;
+            let changes = band.values.iter().enumerate().filter_map(|(index, value)| {
+                value.map(|value| ListUpdate {
+                    band: Band::Buffer(name),
+                    index,
+                    value,
+                    color: COLOR_FILL,
+                })
+            });
+            band.visualization.draw(changes, &mut self.framebuffer);
+        }
+        for (i, band) in self.histograms.iter_mut().enumerate() {
+            band.visualization = ListVisualization::with_layout(
+                band.counts.len(),
+                band.largest as f64,
+                top + (self.buffers.len() + i) * band_height,
+                self.visualization.width,
+                band_height,
+                self.visualization.margin,
+            );
+            for index in 0..band.counts.len() {
+                band.draw(index, COLOR_FILL, &mut self.framebuffer);
+            }
+        }
+    }
     pub fn update_loop(mut self, refresh_period: Duration) {
+        // Refresh periods left to keep the current frame
+        let mut hold = 0;
         // Loop until Window is closed
         while self.is_open() {
             // Store instant when cycle begins
             let before = Instant::now();
+            // Keep the current frame while holding, without reverting its highlights
+            if hold > 0 {
+                hold -= 1;
+                self.window.update_with_buffer(&self.framebuffer).unwrap();
+                sleep_remaining(before, refresh_period);
+                continue;
+            }
             // Receive list updates to visualize via self.channel
-            let next_update = if let Ok(next_update) = self.channel.1.try_recv() {
-                next_update
-            } else {
-                vec![]
+            let next_update = match self.channel.1.try_recv() {
+                Ok(WindowUpdate::Draw(changes)) => changes,
+                Ok(WindowUpdate::AllocBuffer(name, len)) => {
+                    self.alloc_buffer(name, len);
+                    vec![]
+                }
+                Ok(WindowUpdate::FreeBuffer(name)) => {
+                    self.free_buffer(name);
+                    vec![]
+                }
+                Ok(WindowUpdate::DrawHistogram(name, index, count, color)) => {
+                    self.draw_histogram(name, index, count, color);
+                    vec![]
+                }
+                Ok(WindowUpdate::AllocHistogram(name, len)) => {
+                    self.alloc_histogram(name, len);
+                    vec![]
+                }
+                Ok(WindowUpdate::FreeHistogram(name)) => {
+                    self.free_histogram(name);
+                    vec![]
+                }
+                Ok(WindowUpdate::Hold(periods)) => {
+                    hold = periods;
+                    continue;
+                }
+                Err(_) => vec![],
             };
             // Need to call self.update periodically for the Window to remain responsive
             self.update(next_update).unwrap();
-            // Sleep for the remaining time of the cycle
-            let elapsed = Instant::now() - before;
-            if elapsed < refresh_period {
-                sleep(refresh_period - elapsed);
-            }
-            //println!("{}", elapsed.subsec_millis());
+            sleep_remaining(before, refresh_period);
         }
     }
-    pub fn make_update_channel(&self) -> SyncSender<Vec<ListUpdate<T>>> {
+    pub fn make_update_channel(&self) -> SyncSender<WindowUpdate<T>> {
         self.channel.0.clone()
     }
     pub fn is_open(&self) -> bool {
         self.window.is_open()
     }
 }
+
+/// Sleep for the remaining time of the cycle that began at `before`
+fn sleep_remaining(before: Instant, refresh_period: Duration) {
+    let elapsed = Instant::now() - before;
+    if elapsed < refresh_period {
+        sleep(refresh_period - elapsed);
+    }
+}
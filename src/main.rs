@@ -1,8 +1,8 @@
-use clap::{App, Arg, ArgGroup};
+use clap::{App, Arg, ArgGroup, ArgMatches};
 use rand::prelude::*;
 
 mod player;
-use player::play;
+use player::{play, PlayOptions};
 
 mod graphics;
 use graphics::{ListVisualization, ListVisualizationWindow};
@@ -12,6 +12,9 @@ use sort::*;
 const WIDTH: usize = 800;
 const HEIGHT: usize = 600;
 const MARGIN: usize = 2;
+const BUFFER_HEIGHT: usize = 150;
+/// Largest list whose shuffle bias is measured, as it takes a count per value and position
+const MAX_BIAS_LENGTH: usize = 1000;
 
 macro_rules! error {
     ($($arg:tt)*) => ({
@@ -21,16 +24,75 @@ macro_rules! error {
     })
 }
 
-fn get_sort_fn(name: &str) -> Option<fn(&mut dyn List<u32>)> {
-    match name {
-        "bubblesort" => Some(bubblesort),
-        "insertionsort" => Some(insertionsort),
-        "selectionsort" => Some(selectionsort),
-        "shellsort" => Some(shellsort),
-        "quicksort" => Some(quicksort),
-        "quicksort2" => Some(quicksort2),
-        _ => None,
+fn print_algorithms() {
+    println!(
+        "{:<20} {:<24} {:<7} {:<9} {:<11} {:<11} {:<11} RANDOMIZED",
+        "NAME", "ALIASES", "STABLE", "IN-PLACE", "BEST", "AVERAGE", "WORST"
+    );
+    let yes_no = |b| if b { "yes" } else { "no" };
+    for algorithm in algorithms::<u32>() {
+        println!(
+            "{:<20} {:<24} {:<7} {:<9} {:<11} {:<11} {:<11} {}",
+            algorithm.name,
+            algorithm.aliases.join(","),
+            yes_no(algorithm.stable),
+            yes_no(algorithm.in_place),
+            algorithm.best,
+            algorithm.average,
+            algorithm.worst,
+            yes_no(algorithm.randomized)
+        );
     }
+    println!();
+    println!("Pivot strategies: {}", PivotStrategy::NAMES.join(", "));
+    println!("Gap sequences: {}", GapSequence::NAMES.join(", "));
+    println!("Shuffles: {}", Shuffle::NAMES.join(", "));
+    println!("Distributions: {}", sort::Distribution::NAMES.join(", "));
+    println!(
+        "Cost models: {} (operation kinds: {})",
+        CostModel::NAMES.join(", "),
+        CostModel::KINDS.join(", ")
+    );
+    println!(
+        "Shrink factors: greater than 1 (default {})",
+        Shrink::default()
+    );
+    println!(
+        "Radixes: powers of two from {} to {} (default {})",
+        Radix::MIN,
+        Radix::MAX,
+        Radix::default()
+    );
+    let limits: Vec<String> = algorithms::<u32>()
+        .iter()
+        .filter_map(|algorithm| {
+            algorithm
+                .max_key_range
+                .map(|range| format!("{} {}", algorithm.name, range))
+        })
+        .collect();
+    println!("Value range limits: {}", limits.join(", "));
+    let search_names: Vec<&str> = searches::<u32>().iter().map(|search| search.name).collect();
+    println!("Searches: {}", search_names.join(", "));
+}
+
+fn print_shuffle_bias(shuffle: Shuffle, length: usize, trials: usize, rng: &mut dyn RngCore) {
+    let bias = ShuffleBias::measure(shuffle, length, trials, rng);
+    println!(
+        "Shuffle {} of {} values, {} trials (percentage of trials in which each value, by row, lands at each position, by column):",
+        shuffle, length, trials
+    );
+    print!("{}", bias);
+    println!("Expected: {:.1}%", 100.0 / length as f64);
+    println!(
+        "Largest deviation from expected: {:.1}%",
+        100.0 * bias.max_deviation()
+    );
+    println!(
+        "Chi-squared: {:.1} ({} degrees of freedom)",
+        bias.chi_squared(),
+        (length - 1) * (length - 1)
+    );
 }
 
 fn get_data_from_file(filename: &str) -> Vec<u32> {
@@ -69,16 +131,96 @@ fn get_data_from_file(filename: &str) -> Vec<u32> {
     data
 }
 
-fn get_random_data(count: usize) -> Vec<u32> {
+fn get_random_data(count: usize, rng: &mut dyn RngCore) -> Vec<u32> {
     let mut data = Vec::with_capacity(count);
     for _ in 0..count {
-        data.push(random::<u32>());
+        data.push(rng.next_u32());
     }
     data.shrink_to_fit();
     data
 }
 
-fn parse_args() -> (String, fn(&mut dyn List<u32>), Vec<u32>) {
+fn get_trace_from_file(filename: &str) -> Trace<u32> {
+    use std::fs::File;
+    use std::io::prelude::*;
+    use std::io::stdin;
+    let mut file: Box<dyn Read> = if filename == "." {
+        Box::new(stdin())
+    } else {
+        Box::new(
+            File::open(filename)
+                .unwrap_or_else(|_| error!("Cannot open trace file \"{}\"", filename)),
+        )
+    };
+    Trace::read(&mut file)
+        .unwrap_or_else(|e| error!("Cannot read trace file \"{}\": {}", filename, e))
+}
+
+fn get_network_from_file(filename: &str) -> Network {
+    let text = std::fs::read_to_string(filename)
+        .unwrap_or_else(|_| error!("Cannot read network file \"{}\" as UTF-8 text", filename));
+    text.parse::<Network>()
+        .unwrap_or_else(|e| error!("Cannot read network file \"{}\": {}", filename, e))
+}
+
+fn write_trace_to_file(trace: &Trace<u32>, filename: &str, binary: bool) {
+    use std::fs::File;
+    use std::io::BufWriter;
+    let mut file = BufWriter::new(
+        File::create(filename)
+            .unwrap_or_else(|_| error!("Cannot create trace file \"{}\"", filename)),
+    );
+    let result = if binary {
+        trace.write_binary(&mut file)
+    } else {
+        trace.write_text(&mut file)
+    };
+    result.unwrap_or_else(|e| error!("Cannot write trace file \"{}\": {}", filename, e));
+}
+
+fn write_data_to_file(data: &[u32], filename: &str) {
+    use std::fs::File;
+    use std::io::prelude::*;
+    use std::io::BufWriter;
+    let mut file = BufWriter::new(
+        File::create(filename)
+            .unwrap_or_else(|_| error!("Cannot create data file \"{}\"", filename)),
+    );
+    data.iter()
+        .try_for_each(|value| writeln!(file, "{}", value))
+        .and_then(|_| file.flush())
+        .unwrap_or_else(|e| error!("Cannot write data file \"{}\": {}", filename, e));
+}
+
+fn get_algorithm<T>(name: &str) -> Algorithm<T>
+where
+    T: RadixKey + std::fmt::Display,
+{
+    match find_algorithm(name) {
+        Some(algorithm) => algorithm,
+        None => match suggest_algorithm(name) {
+            Some(suggestion) => error!(
+                "Sorting function \"{}\" not found, did you mean \"{}\"?",
+                name, suggestion
+            ),
+            None => error!("Sorting function \"{}\" not found", name),
+        },
+    }
+}
+
+/// Sorting function of a run, along with the generator for its random choices
+type SortFn = Box<dyn FnOnce(&mut dyn List<u32>, &mut dyn RngCore) + Send>;
+
+/// Everything needed to visualize a run
+struct Run {
+    title: String,
+    uses_buffers: bool,
+    sort_fn: SortFn,
+    data: Vec<u32>,
+    options: PlayOptions<u32>,
+}
+
+fn parse_args() -> Run {
     let matches = App::new("Sort GUI")
         .version("0.1.0")
         .author("Jon Ayerdi")
@@ -88,32 +230,448 @@ fn parse_args() -> (String, fn(&mut dyn List<u32>), Vec<u32>) {
             .long("sort")
             .value_name("ALGORITHM")
             .help("Sorting algorithm to use")
-            .required(true))
+            .required_unless_one(&["list", "replay", "network", "select", "bias"]))
+        .arg(Arg::with_name("network")
+            .long("network")
+            .value_name("FILE")
+            .help("File containing a sorting network to run instead of a sorting algorithm")
+            .conflicts_with_all(&["sort", "pivot", "gaps", "shrink", "radix", "inner"]))
+        .arg(Arg::with_name("select")
+            .long("select")
+            .value_name("K")
+            .help("Select the K-th smallest element (counting from 0) instead of sorting")
+            .conflicts_with_all(&["sort", "network", "gaps", "shrink", "radix", "inner"]))
+        .arg(Arg::with_name("search")
+            .long("search")
+            .value_name("VALUE")
+            .help("Value to look up with each search algorithm once the data is sorted")
+            .conflicts_with("select"))
+        .arg(Arg::with_name("pivot")
+            .short("p")
+            .long("pivot")
+            .value_name("STRATEGY")
+            .help("Pivot strategy for quicksort-like algorithms"))
+        .arg(Arg::with_name("gaps")
+            .short("g")
+            .long("gaps")
+            .value_name("SEQUENCE")
+            .help("Gap sequence for shellsort-like algorithms, by name or as a comma separated list")
+            .conflicts_with("pivot"))
+        .arg(Arg::with_name("shrink")
+            .long("shrink")
+            .value_name("FACTOR")
+            .help("Shrink factor of the gap for comb sort, greater than 1")
+            .conflicts_with_all(&["pivot", "gaps"]))
+        .arg(Arg::with_name("radix")
+            .long("radix")
+            .value_name("RADIX")
+            .help("Radix for radix sorts, a power of two")
+            .conflicts_with_all(&["pivot", "gaps", "shrink"]))
+        .arg(Arg::with_name("inner")
+            .long("inner")
+            .value_name("ALGORITHM")
+            .help("Sorting algorithm used on each bucket by bucket sorts")
+            .conflicts_with_all(&["pivot", "gaps", "shrink", "radix"]))
+        .arg(Arg::with_name("list")
+            .short("l")
+            .long("list")
+            .help("List the available sorting algorithms and exit"))
         .arg(Arg::with_name("file")
             .short("f")
             .long("file")
             .value_name("FILE")
             .help("File containing the data to be sorted. Use \".\" as a filename to read from stdin")
-            .required(false))
+            .required_unless_one(&["list", "rand", "replay"]))
         .arg(Arg::with_name("rand")
             .short("r")
             .long("rand")
             .value_name("COUNT")
             .help("Count of random elements to sort")
-            .required(false))
+            .required_unless_one(&["list", "file", "replay"]))
+        .arg(Arg::with_name("dist")
+            .long("dist")
+            .value_name("DISTRIBUTION")
+            .help("Shape of the random data, with values from 1 to COUNT unless --range is given")
+            .requires("rand"))
+        .arg(Arg::with_name("range")
+            .long("range")
+            .value_name("MIN:MAX")
+            .help("Range of the random values, both inclusive")
+            .requires("rand"))
+        .arg(Arg::with_name("shuffle")
+            .long("shuffle")
+            .value_name("SHUFFLE")
+            .help("Show the data being shuffled before sorting, starting from sorted data unless --dist is given")
+            .requires("rand")
+            .conflicts_with_all(&["file", "replay"]))
+        .arg(Arg::with_name("bias")
+            .long("bias")
+            .value_name("TRIALS")
+            .help("Estimate how often the shuffle moves each value to each position over TRIALS shuffles and exit")
+            .requires("shuffle")
+            .conflicts_with_all(&["sort", "network", "select", "search", "record", "dist", "range"]))
+        .arg(Arg::with_name("antiqsort")
+            .long("antiqsort")
+            .value_name("FILE")
+            .help("Build an input of COUNT elements that drives the algorithm to its worst case, write it to FILE and exit")
+            .requires_all(&["sort", "rand"])
+            .conflicts_with_all(&["network", "select", "search", "gaps", "shrink", "radix", "inner", "dist", "range", "shuffle", "bias", "record", "replay"]))
+        .arg(Arg::with_name("cost")
+            .long("cost")
+            .value_name("MODEL")
+            .help("Cost model, optionally followed by comma separated KIND=COST overrides, to measure simulated time and pace the playback by cost"))
+        .arg(Arg::with_name("cache")
+            .long("cache")
+            .value_name("LEVELS")
+            .help("Simulate a cache over the accessed elements and report its hits and misses, given as comma separated LINE:CAPACITY:WAYS levels counted in elements, e.g. 8:64:2,8:512:4"))
+        .arg(Arg::with_name("cache-tint")
+            .long("cache-tint")
+            .help("Tint each element by whether its last access hit or missed the cache")
+            .requires("cache"))
+        .arg(Arg::with_name("seed")
+            .long("seed")
+            .value_name("SEED")
+            .help("Seed for the random data, shuffle and pivots, so that runs can be reproduced"))
+        .arg(Arg::with_name("record")
+            .long("record")
+            .value_name("TRACE")
+            .help("Record the operations performed by the sort into a trace file")
+            .conflicts_with("replay"))
+        .arg(Arg::with_name("trace-format")
+            .long("trace-format")
+            .value_name("FORMAT")
+            .help("Format of the recorded trace file")
+            .possible_values(&["text", "binary"])
+            .default_value("text"))
+        .arg(Arg::with_name("replay")
+            .long("replay")
+            .value_name("TRACE")
+            .help("Trace file to replay instead of running a sort. Use \".\" as a filename to read from stdin")
+            .conflicts_with_all(&["sort", "network", "select", "file", "rand"]))
         .group(ArgGroup::with_name("data")
             .arg("file")
-            .arg("rand")
-            .required(true))
+            .arg("rand"))
         .get_matches();
-    // Get sort_fn
-    let sort_fn_name = matches.value_of("sort").unwrap();
-    let sort_fn = match get_sort_fn(sort_fn_name) {
-        Some(fptr) => fptr,
-        None => error!("Sorting function \"{}\" not found", sort_fn_name),
+    // List algorithms
+    if matches.is_present("list") {
+        print_algorithms();
+        std::process::exit(0);
+    }
+    // Single generator for the data, shuffle and algorithm, seeded if requested
+    let mut rng = match matches.value_of("seed") {
+        Some(seed_str) => match seed_str.parse::<u64>() {
+            Ok(seed) => StdRng::seed_from_u64(seed),
+            Err(_) => error!("Cannot parse \"{}\" as unsigned integer", seed_str),
+        },
+        None => StdRng::from_entropy(),
+    };
+    // Shuffle to show before sorting
+    let shuffle = matches
+        .value_of("shuffle")
+        .map(|name| name.parse::<Shuffle>().unwrap_or_else(|e| error!("{}", e)));
+    // Analyze shuffle bias
+    if let Some(trials_str) = matches.value_of("bias") {
+        let trials = match trials_str.parse::<usize>() {
+            Ok(n) if n > 0 => n,
+            _ => error!("Cannot parse \"{}\" as positive integer", trials_str),
+        };
+        // unwrap() should be safe because --shuffle requires --rand
+        let length_str = matches.value_of("rand").unwrap();
+        let length = match length_str.parse::<usize>() {
+            Ok(n) => n,
+            Err(_) => error!("Cannot parse \"{}\" as unsigned integer", length_str),
+        };
+        if !(2..=MAX_BIAS_LENGTH).contains(&length) {
+            error!(
+                "Shuffle bias needs from 2 to {} values, but there are {}",
+                MAX_BIAS_LENGTH, length
+            );
+        }
+        // unwrap() should be safe because --bias requires --shuffle
+        print_shuffle_bias(shuffle.unwrap(), length, trials, &mut rng);
+        std::process::exit(0);
+    }
+    // Build a killer input against the algorithm
+    if let Some(filename) = matches.value_of("antiqsort") {
+        write_antiqsort_input(&matches, filename, &mut rng);
+        std::process::exit(0);
+    }
+    // Cost model for simulated time and playback pacing
+    let cost_model = matches.value_of("cost").map(|name| {
+        name.parse::<CostModel>()
+            .unwrap_or_else(|e| error!("{}", e))
+    });
+    // Cache simulated over the accesses of the run
+    let cache = matches.value_of("cache").map(|levels| {
+        levels
+            .parse::<CacheHierarchy>()
+            .unwrap_or_else(|e| error!("{}", e))
+    });
+    let cache_tint = matches.is_present("cache-tint");
+    // Value to search for after sorting
+    let search = matches
+        .value_of("search")
+        .map(|value| match value.parse::<u32>() {
+            Ok(value) => value,
+            Err(_) => error!("Cannot parse \"{}\" as unsigned integer", value),
+        });
+    // Replay trace
+    if let Some(filename) = matches.value_of("replay") {
+        let trace = get_trace_from_file(filename);
+        return Run {
+            title: trace.algorithm.clone(),
+            uses_buffers: trace.uses_buffers(),
+            data: trace.data.clone(),
+            sort_fn: Box::new(move |list, _| trace.replay(list)),
+            options: PlayOptions {
+                rng: Box::new(rng),
+                shuffle,
+                selected: None,
+                search,
+                cost_model,
+                cache,
+                cache_tint,
+            },
+        };
+    }
+    // Run a sorting network
+    if let Some(filename) = matches.value_of("network") {
+        let network = get_network_from_file(filename);
+        match network.verify() {
+            Ok(()) => println!(
+                "Network verified: sorts every input of {} elements",
+                network.size()
+            ),
+            Err(e) => eprintln!("warning: {}", e),
+        }
+        let data = get_data(&matches, &mut rng);
+        if data.len() != network.size() {
+            error!(
+                "Network \"{}\" sorts {} elements, but the data has {}",
+                filename,
+                network.size(),
+                data.len()
+            );
+        }
+        let sort_fn: SortFn = Box::new(move |list, _| run_network(list, &network));
+        return Run {
+            title: String::from(filename),
+            uses_buffers: false,
+            sort_fn: record_if_requested(&matches, filename, sort_fn),
+            data,
+            options: PlayOptions {
+                rng: Box::new(rng),
+                shuffle,
+                selected: None,
+                search,
+                cost_model,
+                cache,
+                cache_tint,
+            },
+        };
+    }
+    // Select the k-th element, with quickselect if a pivot strategy is given
+    if let Some(k_str) = matches.value_of("select") {
+        let k = match k_str.parse::<usize>() {
+            Ok(k) => k,
+            Err(_) => error!("Cannot parse \"{}\" as unsigned integer", k_str),
+        };
+        let data = get_data(&matches, &mut rng);
+        if k >= data.len() {
+            error!("Cannot select element {} of {} elements", k, data.len());
+        }
+        let pivot = matches.value_of("pivot");
+        let (name, select_fn): (&str, SortFn) = if let Some(pivot_name) = pivot {
+            let pivot_strategy = pivot_name
+                .parse::<PivotStrategy>()
+                .unwrap_or_else(|e| error!("{}", e));
+            (
+                "quickselect",
+                Box::new(move |list, rng| {
+                    quickselect_with_rng(
+                        list,
+                        k,
+                        pivot_strategy,
+                        &mut pivot_rng(pivot_strategy, rng),
+                    )
+                }),
+            )
+        } else {
+            ("nth_element", Box::new(move |list, _| nth_element(list, k)))
+        };
+        let sort_fn: SortFn = Box::new(move |list, rng| {
+            select_fn(list, rng);
+            list.highlight(k, k + 1, Highlight::Pivot);
+        });
+        return Run {
+            title: format!("{} {}", name, k),
+            uses_buffers: false,
+            sort_fn: record_if_requested(&matches, name, sort_fn),
+            data,
+            options: PlayOptions {
+                rng: Box::new(rng),
+                shuffle,
+                selected: Some(k),
+                search,
+                cost_model,
+                cache,
+                cache_tint,
+            },
+        };
+    }
+    // Get algorithm
+    let algorithm = get_algorithm(matches.value_of("sort").unwrap());
+    let mut inner_algorithm = None;
+    // Get pivot strategy, gap sequence, shrink factor, radix or inner sort
+    let sort_fn: SortFn = if let Some(pivot_name) = matches.value_of("pivot") {
+        let pivot_strategy = pivot_name
+            .parse::<PivotStrategy>()
+            .unwrap_or_else(|e| error!("{}", e));
+        let pivot_fn = algorithm.pivot_fn.unwrap_or_else(|| {
+            error!(
+                "Sorting function \"{}\" does not support pivot strategies",
+                algorithm.name
+            )
+        });
+        Box::new(move |list, rng| {
+            pivot_fn(list, pivot_strategy, &mut pivot_rng(pivot_strategy, rng))
+        })
+    } else if let Some(gaps_name) = matches.value_of("gaps") {
+        let gap_sequence = gaps_name
+            .parse::<GapSequence>()
+            .unwrap_or_else(|e| error!("{}", e));
+        let gaps_fn = algorithm.gaps_fn.unwrap_or_else(|| {
+            error!(
+                "Sorting function \"{}\" does not support gap sequences",
+                algorithm.name
+            )
+        });
+        Box::new(move |list, _| gaps_fn(list, &gap_sequence))
+    } else if let Some(shrink_name) = matches.value_of("shrink") {
+        let shrink = shrink_name
+            .parse::<Shrink>()
+            .unwrap_or_else(|e| error!("{}", e));
+        let shrink_fn = algorithm.shrink_fn.unwrap_or_else(|| {
+            error!(
+                "Sorting function \"{}\" does not support shrink factors",
+                algorithm.name
+            )
+        });
+        Box::new(move |list, _| shrink_fn(list, shrink))
+    } else if let Some(radix_name) = matches.value_of("radix") {
+        let radix = radix_name
+            .parse::<Radix>()
+            .unwrap_or_else(|e| error!("{}", e));
+        let radix_fn = algorithm.radix_fn.unwrap_or_else(|| {
+            error!(
+                "Sorting function \"{}\" does not support radixes",
+                algorithm.name
+            )
+        });
+        Box::new(move |list, _| radix_fn(list, radix))
+    } else if let Some(inner_name) = matches.value_of("inner") {
+        let inner = get_algorithm(inner_name);
+        let inner_fn = algorithm.inner_fn.unwrap_or_else(|| {
+            error!(
+                "Sorting function \"{}\" does not support inner sorts",
+                algorithm.name
+            )
+        });
+        inner_algorithm = Some(inner);
+        // Randomized inner sorts draw their pivots from the run's generator too
+        let inner_pivot_fn = inner.pivot_fn.filter(|_| inner.randomized);
+        let inner_sort_fn = inner.sort_fn;
+        Box::new(move |list, rng| {
+            inner_fn(list, &mut |sublist| match inner_pivot_fn {
+                Some(pivot_fn) => pivot_fn(sublist, PivotStrategy::Random, rng),
+                None => inner_sort_fn(sublist),
+            })
+        })
+    } else {
+        // Randomized algorithms choose random pivots by default
+        match algorithm.pivot_fn {
+            Some(pivot_fn) if algorithm.randomized => {
+                Box::new(move |list, rng| pivot_fn(list, PivotStrategy::Random, rng))
+            }
+            _ => {
+                let sort_fn = algorithm.sort_fn;
+                Box::new(move |list, _| sort_fn(list))
+            }
+        }
     };
     // Get data
-    let data = if let Some(filename) = matches.value_of("file") {
+    let data = get_data(&matches, &mut rng);
+    // Check value range requirements
+    for algorithm in std::iter::once(&algorithm).chain(inner_algorithm.iter()) {
+        algorithm
+            .check_data(&data)
+            .unwrap_or_else(|e| error!("{}", e));
+    }
+    Run {
+        title: String::from(algorithm.name),
+        uses_buffers: algorithm.uses_buffers,
+        sort_fn: record_if_requested(&matches, algorithm.name, sort_fn),
+        data,
+        options: PlayOptions {
+            rng: Box::new(rng),
+            shuffle,
+            selected: None,
+            search,
+            cost_model,
+            cache,
+            cache_tint,
+        },
+    }
+}
+
+/// Run the algorithm against the antiqsort adversary and write the input it builds to `filename`
+fn write_antiqsort_input(matches: &ArgMatches, filename: &str, rng: &mut dyn RngCore) {
+    // unwrap() should be safe because --antiqsort requires --sort and --rand
+    let name = matches.value_of("sort").unwrap();
+    let length_str = matches.value_of("rand").unwrap();
+    let length = match length_str.parse::<usize>() {
+        Ok(n) => n,
+        Err(_) => error!("Cannot parse \"{}\" as unsigned integer", length_str),
+    };
+    let pivot_strategy = matches.value_of("pivot").map(|pivot_name| {
+        pivot_name
+            .parse::<PivotStrategy>()
+            .unwrap_or_else(|e| error!("{}", e))
+    });
+    let algorithm = get_algorithm::<u32>(name);
+    if pivot_strategy.is_some() && algorithm.pivot_fn.is_none() {
+        error!(
+            "Sorting function \"{}\" does not support pivot strategies",
+            algorithm.name
+        );
+    }
+    // The algorithm is looked up again for the adversary's element type
+    let (input, compares) = antiqsort(length, |list| {
+        let algorithm = get_algorithm(name);
+        match (pivot_strategy, algorithm.pivot_fn) {
+            (Some(pivot_strategy), Some(pivot_fn)) => {
+                pivot_fn(list, pivot_strategy, &mut pivot_rng(pivot_strategy, rng))
+            }
+            (None, Some(pivot_fn)) if algorithm.randomized => {
+                pivot_fn(list, PivotStrategy::Random, rng)
+            }
+            _ => (algorithm.sort_fn)(list),
+        }
+    });
+    write_data_to_file(&input, filename);
+    let n_log_n = length as f64 * (length as f64).log2();
+    println!(
+        "Antiqsort: {} compares by {} on {} elements ({:.1} n log2 n), input written to \"{}\"",
+        compares,
+        algorithm.name,
+        length,
+        compares as f64 / n_log_n.max(1.0),
+        filename
+    );
+}
+
+fn get_data(matches: &ArgMatches, rng: &mut dyn RngCore) -> Vec<u32> {
+    if let Some(filename) = matches.value_of("file") {
         get_data_from_file(filename)
     } else {
         // unwrap() should be safe because <file|rand> is mandatory
@@ -122,17 +680,72 @@ fn parse_args() -> (String, fn(&mut dyn List<u32>), Vec<u32>) {
             Ok(n) => n,
             Err(_) => error!("Cannot parse \"{}\" as unsigned integer", rand_count_str),
         };
-        get_random_data(rand_count)
-    };
-    (String::from(sort_fn_name), sort_fn, data)
+        let distribution = matches.value_of("dist").map(|name| {
+            name.parse::<sort::Distribution>()
+                .unwrap_or_else(|e| error!("{}", e))
+        });
+        let range = matches
+            .value_of("range")
+            .map(|text| parse_range(text).unwrap_or_else(|e| error!("{}", e)));
+        // Shuffles start from sorted data, and generated values default to 1..=COUNT
+        let distribution = if matches.is_present("shuffle") {
+            Some(distribution.unwrap_or(sort::Distribution::Sorted))
+        } else {
+            distribution
+        };
+        if distribution.is_none() && range.is_none() {
+            return get_random_data(rand_count, rng);
+        }
+        let distribution = distribution.unwrap_or(sort::Distribution::Uniform);
+        let range = range.unwrap_or(1..=std::cmp::max(rand_count, 1) as u32);
+        distribution.generate(rand_count, range, rng)
+    }
+}
+
+/// Generator for random pivots: explicitly seeded strategies keep their own one, others use the run's
+fn pivot_rng(pivot_strategy: PivotStrategy, rng: &mut dyn RngCore) -> Box<dyn RngCore + '_> {
+    match pivot_strategy {
+        PivotStrategy::SeededRandom(_) => pivot_strategy.make_rng(),
+        _ => Box::new(rng),
+    }
+}
+
+/// Wrap `sort_fn` to record a trace of the run if requested. The trace starts from the data
+/// as the sort receives it, after any shuffle.
+fn record_if_requested(matches: &ArgMatches, name: &str, sort_fn: SortFn) -> SortFn {
+    if let Some(filename) = matches.value_of("record") {
+        let filename = String::from(filename);
+        let binary = matches.value_of("trace-format") == Some("binary");
+        let name = String::from(name);
+        Box::new(move |list, rng| {
+            let initial_data = list.as_slice().to_vec();
+            let mut list = TracingList::new(list);
+            sort_fn(&mut list, rng);
+            let trace = Trace {
+                algorithm: name,
+                data: initial_data,
+                steps: list.into_steps(),
+            };
+            write_trace_to_file(&trace, &filename, binary);
+        })
+    } else {
+        sort_fn
+    }
 }
 
 fn main() {
     // Parse args
-    let (sort_fn_name, sort_fn, data) = parse_args();
-    // Init
-    let visualization = ListVisualization::autogenerate(&data, WIDTH, HEIGHT, MARGIN);
-    let window = ListVisualizationWindow::new(&sort_fn_name, visualization);
+    let run = parse_args();
+    // Init (reserve space for auxiliary buffers only if the algorithm or shuffle needs them)
+    let shuffle_buffers = run.options.shuffle.is_some_and(|s| s.uses_buffer());
+    let buffer_height = if run.uses_buffers || shuffle_buffers {
+        BUFFER_HEIGHT
+    } else {
+        0
+    };
+    let visualization =
+        ListVisualization::autogenerate(&run.data, WIDTH, HEIGHT - buffer_height, MARGIN);
+    let window = ListVisualizationWindow::new(&run.title, visualization, buffer_height);
     // Run
-    play(sort_fn, data, window);
+    play(run.sort_fn, run.data, run.options, window);
 }
@@ -1,120 +1,372 @@
-use sort::{CallbackList, List, Operation};
+use sort::{
+    searches, CacheHierarchy, CacheList, Callback, CallbackList, CostModel, CountingList,
+    Highlight, List, Operation, RadixKey, Shuffle,
+};
 
 use crate::graphics::*;
 
+use rand::RngCore;
+use std::cell::{Cell, RefCell};
+use std::cmp::Ordering;
 use std::marker::Send;
 use std::sync::mpsc::SyncSender;
 use std::thread;
 use std::time::Duration;
 
-pub fn play<T, F>(sort_fn: F, data: Vec<T>, window: ListVisualizationWindow<T>)
-where
-    T: 'static + Copy + Ord + Into<f64> + Send + std::fmt::Display,
-    F: 'static + FnOnce(&mut dyn List<T>) + Send,
+/// Steps of a run besides the sort itself
+pub struct PlayOptions<T> {
+    /// Generator for the random choices of the shuffle and the sort
+    pub rng: Box<dyn RngCore + Send>,
+    /// Shuffle shown before the sort, which is not counted in the statistics
+    pub shuffle: Option<Shuffle>,
+    /// Index picked by a selection, checked for partitioning instead of sortedness
+    pub selected: Option<usize>,
+    /// Value looked up by the search algorithms once the data is sorted
+    pub search: Option<T>,
+    /// Cost model measuring the simulated time of the sort and pacing its playback,
+    /// with the cheapest operation taking one frame. Otherwise every operation takes one frame.
+    pub cost_model: Option<CostModel>,
+    /// Cache simulated over the accesses of the sort, whose hits and misses are reported
+    pub cache: Option<CacheHierarchy>,
+    /// Whether to tint each element by the cache level that served its last access
+    pub cache_tint: bool,
+}
+
+/// Run `sort_fn` on `data` and show its operations in `window`. If `options.selected` is given,
+/// the result is checked to be partitioned around that index instead of fully sorted.
+/// If `options.search` is given, every search algorithm looks for it in the sorted data.
+pub fn play<T, F>(
+    sort_fn: F,
+    data: Vec<T>,
+    options: PlayOptions<T>,
+    window: ListVisualizationWindow<T>,
+) where
+    T: 'static + RadixKey + Into<f64> + Send + std::fmt::Display,
+    F: 'static + FnOnce(&mut dyn List<T>, &mut dyn RngCore) + Send,
 {
     // Make update channel for Window
     let channel = window.make_update_channel();
     // Display initial slice
     channel
-        .send(
+        .send(WindowUpdate::Draw(
             data.iter()
                 .enumerate()
                 .map(move |(i, &e)| ListUpdate {
+                    band: Band::Main,
                     index: i,
                     value: e,
                     color: COLOR_FILL,
                 })
                 .collect(),
-        )
+        ))
         .unwrap();
     // Call the sorting function
     thread::spawn(move || {
-        // Capture variables + create CallbackList
+        // Capture variables + create CallbackList wrapped in a CountingList
         let channel = &channel;
         let mut data = data;
-        let mut list = CallbackList::new(&mut data, make_callback(channel));
+        let mut rng = options.rng;
+        // Cache tints waiting to be drawn along with the access that caused them
+        let tints = RefCell::new(Vec::new());
+        let tints = if options.cache_tint {
+            Some(&tints)
+        } else {
+            None
+        };
+        if let Some(shuffle) = options.shuffle {
+            let callback = make_callback(channel, options.cost_model, tints);
+            shuffle.shuffle(&mut CallbackList::new(&mut data, callback), &mut rng);
+        }
+        // Input of the sort, to find the fewest writes that sort it
+        let input = data.clone();
+        let callback = make_callback(channel, options.cost_model, tints);
+        let mut list = CallbackList::new(&mut data, callback);
+        // Simulate the cache between the sort and the visualization, so that tints come first
+        let mut cache_list = None;
+        let list: &mut dyn List<T> = match options.cache {
+            Some(cache) => cache_list.insert(match tints {
+                Some(tints) => CacheList::with_observer(
+                    &mut list,
+                    cache,
+                    Box::new(move |index, level| {
+                        tints.borrow_mut().push((index, cache_color(level)));
+                    }),
+                ),
+                None => CacheList::new(&mut list, cache),
+            }),
+            None => &mut list,
+        };
+        let cost_model = options.cost_model.unwrap_or_default();
+        let mut list = CountingList::with_cost_model(list, cost_model);
         // Call sort function
-        sort_fn(&mut list);
+        sort_fn(&mut list, &mut rng);
+        let statistics = list.statistics();
+        let simulated_time = list.simulated_time();
+        let cache = cache_list.map(CacheList::into_cache);
         // Display ending animation
-        ending_animation(&data, &channel);
+        let correct = ending_animation(&data, options.selected, channel);
+        let yes_no = if correct { "yes" } else { "no" };
+        match options.selected {
+            Some(k) => {
+                println!("Selected: {} at index {}", data[k], k);
+                println!("Partitioned: {}", yes_no);
+            }
+            None => println!("Sorted: {}", yes_no),
+        }
+        println!("{}", statistics);
+        if options.selected.is_none() {
+            // Sorting must write at least every position holding the wrong value
+            let mut sorted = input.clone();
+            sorted.sort();
+            let misplaced = input.iter().zip(&sorted).filter(|(a, b)| a != b).count();
+            println!(
+                "Writes: {} (sorting needs at least {})",
+                statistics.writes, misplaced
+            );
+        }
+        if options.cost_model.is_some() {
+            println!("Simulated time: {}", simulated_time);
+        }
+        if let Some(cache) = cache {
+            for (level, (config, statistics)) in
+                cache.configs().iter().zip(cache.statistics()).enumerate()
+            {
+                println!("Cache L{} {}: {}", level + 1, config, statistics);
+            }
+        }
+        // Search the sorted data
+        if let Some(value) = options.search {
+            if correct && options.selected.is_none() {
+                run_searches(&mut data, value, channel);
+            } else {
+                println!("Skipping searches: the data is not sorted");
+            }
+        }
     });
     // Execute window loop
     window.update_loop(Duration::from_millis(10));
 }
 
 fn make_callback<'a, T>(
-    channel: &'a SyncSender<Vec<ListUpdate<T>>>,
-) -> Box<dyn 'a + Fn(Operation, &[T]) + Send>
+    channel: &'a SyncSender<WindowUpdate<T>>,
+    cost_model: Option<CostModel>,
+    tints: Option<&'a RefCell<Vec<(usize, u32)>>>,
+) -> Callback<'a, T>
 where
     T: Copy + Ord + Into<f64> + Send + std::fmt::Display,
 {
-    Box::new(move |operation, slice| {
-        channel
-            .send(match operation {
-                Operation::Get(i) => vec![ListUpdate {
-                    index: i,
-                    value: slice[i],
-                    color: COLOR_READ,
-                }],
-                Operation::Set(i) => vec![ListUpdate {
-                    index: i,
-                    value: slice[i],
-                    color: COLOR_WRITE,
-                }],
-                Operation::Compare(i1, i2) => vec![
-                    ListUpdate {
-                        index: i1,
-                        value: slice[i1],
-                        color: COLOR_READ,
-                    },
-                    ListUpdate {
-                        index: i2,
-                        value: slice[i2],
-                        color: COLOR_READ,
-                    },
-                ],
-                Operation::Swap(i1, i2) => vec![
-                    ListUpdate {
-                        index: i1,
-                        value: slice[i1],
-                        color: COLOR_WRITE,
-                    },
-                    ListUpdate {
-                        index: i2,
-                        value: slice[i2],
-                        color: COLOR_WRITE,
-                    },
-                ],
-            })
-            .unwrap();
+    let update = |band, index, value, color| ListUpdate {
+        band,
+        index,
+        value,
+        color,
+    };
+    // Operations left in the current parallel group and their updates so far
+    let parallel = Cell::new(0);
+    let parallel_changes = RefCell::new(Vec::new());
+    let parallel_cost = Cell::new(0.0);
+    // Hold the frame of an update for as many frames as its cost beyond the first one,
+    // carrying fractions of a frame over to the next update
+    let carried_frames = Cell::new(0.0);
+    let pace = move |cost: f64| {
+        if let Some(model) = cost_model {
+            let frames = (carried_frames.get() + cost / model.cheapest() - 1.0).max(0.0);
+            carried_frames.set(frames.fract());
+            if frames >= 1.0 {
+                channel.send(WindowUpdate::Hold(frames as usize)).unwrap();
+            }
+        }
+    };
+    let cost = move |operation| cost_model.map_or(0.0, |model| model.cost(operation));
+    Box::new(move |operation, slice, buffer| {
+        // Buffer operations always come with the buffer they touched
+        let buffer_value = |index| buffer.unwrap().get(index);
+        let window_update = match operation {
+            Operation::Get(i) => {
+                WindowUpdate::Draw(vec![update(Band::Main, i, slice[i], COLOR_READ)])
+            }
+            Operation::Set(i) => {
+                WindowUpdate::Draw(vec![update(Band::Main, i, slice[i], COLOR_WRITE)])
+            }
+            Operation::Compare(i1, i2) => WindowUpdate::Draw(vec![
+                update(Band::Main, i1, slice[i1], COLOR_READ),
+                update(Band::Main, i2, slice[i2], COLOR_READ),
+            ]),
+            Operation::Swap(i1, i2) => WindowUpdate::Draw(vec![
+                update(Band::Main, i1, slice[i1], COLOR_WRITE),
+                update(Band::Main, i2, slice[i2], COLOR_WRITE),
+            ]),
+            Operation::Alloc(name, len) => WindowUpdate::AllocBuffer(name, len),
+            Operation::Free(name) => WindowUpdate::FreeBuffer(name),
+            Operation::BufferGet(name, i) => WindowUpdate::Draw(vec![update(
+                Band::Buffer(name),
+                i,
+                buffer_value(i),
+                COLOR_READ,
+            )]),
+            Operation::BufferSet(name, i) => WindowUpdate::Draw(vec![update(
+                Band::Buffer(name),
+                i,
+                buffer_value(i),
+                COLOR_WRITE,
+            )]),
+            Operation::CopyToBuffer(i, name, bi) => WindowUpdate::Draw(vec![
+                update(Band::Main, i, slice[i], COLOR_READ),
+                update(Band::Buffer(name), bi, buffer_value(bi), COLOR_WRITE),
+            ]),
+            Operation::CopyFromBuffer(name, bi, i) => WindowUpdate::Draw(vec![
+                update(Band::Buffer(name), bi, buffer_value(bi), COLOR_READ),
+                update(Band::Main, i, slice[i], COLOR_WRITE),
+            ]),
+            Operation::Highlight(begin, end, highlight) => WindowUpdate::Draw(
+                (begin..end)
+                    .map(|i| update(Band::Main, i, slice[i], highlight_color(highlight)))
+                    .collect(),
+            ),
+            Operation::Key(i) => {
+                WindowUpdate::Draw(vec![update(Band::Main, i, slice[i], COLOR_READ)])
+            }
+            Operation::AllocHistogram(name, len) => WindowUpdate::AllocHistogram(name, len),
+            Operation::FreeHistogram(name) => WindowUpdate::FreeHistogram(name),
+            Operation::HistogramGet(name, i) => {
+                WindowUpdate::DrawHistogram(name, i, None, COLOR_READ)
+            }
+            Operation::HistogramSet(name, i, count) => {
+                WindowUpdate::DrawHistogram(name, i, Some(count), COLOR_WRITE)
+            }
+            // Block operations are drawn at once as a single update
+            Operation::Reverse(begin, end) | Operation::Rotate(begin, end, _) => {
+                WindowUpdate::Draw(
+                    (begin..end)
+                        .map(|i| update(Band::Main, i, slice[i], COLOR_WRITE))
+                        .collect(),
+                )
+            }
+            Operation::BlockSwap(a, b, len) => WindowUpdate::Draw(
+                (a..a + len)
+                    .chain(b..b + len)
+                    .map(|i| update(Band::Main, i, slice[i], COLOR_WRITE))
+                    .collect(),
+            ),
+            Operation::Parallel(count) => {
+                parallel.set(count);
+                return;
+            }
+        };
+        // Pending tints become the base color of their elements, drawn before the access itself
+        let window_update = match (window_update, tints) {
+            (WindowUpdate::Draw(changes), Some(tints)) => WindowUpdate::Draw(
+                tints
+                    .borrow_mut()
+                    .drain(..)
+                    .map(|(i, color)| update(Band::Main, i, slice[i], color))
+                    .chain(changes)
+                    .collect(),
+            ),
+            (window_update, _) => window_update,
+        };
+        // Parallel operations are drawn together once the last one arrives
+        if parallel.get() > 0 {
+            parallel.set(parallel.get() - 1);
+            if let WindowUpdate::Draw(changes) = window_update {
+                parallel_changes.borrow_mut().extend(changes);
+                parallel_cost.set(parallel_cost.get() + cost(operation));
+                if parallel.get() > 0 {
+                    return;
+                }
+                channel
+                    .send(WindowUpdate::Draw(parallel_changes.take()))
+                    .unwrap();
+                pace(parallel_cost.replace(0.0));
+                return;
+            }
+        }
+        channel.send(window_update).unwrap();
+        pace(cost(operation));
     })
 }
 
-fn ending_animation<T>(data: &[T], channel: &SyncSender<Vec<ListUpdate<T>>>)
+/// Run every search algorithm for `value`, probing `data` through a visualized list
+fn run_searches<T>(data: &mut [T], value: T, channel: &SyncSender<WindowUpdate<T>>)
+where
+    T: RadixKey + Into<f64> + Send + std::fmt::Display,
+{
+    let list = CallbackList::new(data, make_callback(channel, None, None));
+    for search in searches::<T>() {
+        let result = (search.search_fn)(&list, value);
+        let index = match result.index {
+            Some(index) => index.to_string(),
+            None => String::from("not found"),
+        };
+        println!(
+            "Search {} {}: {} ({} probes)",
+            search.name, value, index, result.probes
+        );
+    }
+}
+
+/// Tint for an access served by cache `level`, or by memory
+fn cache_color(level: Option<usize>) -> u32 {
+    match level {
+        Some(0) => COLOR_CACHE_HIT,
+        Some(_) => COLOR_CACHE_OUTER_HIT,
+        None => COLOR_CACHE_MISS,
+    }
+}
+
+fn highlight_color(highlight: Highlight) -> u32 {
+    match highlight {
+        Highlight::None => COLOR_FILL,
+        Highlight::Pivot => COLOR_PIVOT,
+        Highlight::Equal => COLOR_EQUAL,
+        Highlight::Fallback => COLOR_FALLBACK,
+        Highlight::Block => COLOR_BLOCK,
+        Highlight::Run => COLOR_RUN,
+        Highlight::Merge => COLOR_MERGE,
+    }
+}
+
+/// Returns whether the data was correctly sorted, or partitioned around index `selected`
+fn ending_animation<T>(
+    data: &[T],
+    selected: Option<usize>,
+    channel: &SyncSender<WindowUpdate<T>>,
+) -> bool
 where
     T: Copy + Ord + Into<f64> + Send + std::fmt::Display,
 {
     let mut previous = None;
-    data.iter().enumerate().for_each(move |(index, &value)| {
-        // Select color: Different color if previous_value > value
-        let color = if let Some(previous_value) = previous {
-            if previous_value > value {
-                COLOR_DONE_ERROR
-            } else {
-                COLOR_DONE_OK
-            }
+    let mut correct = true;
+    data.iter().enumerate().for_each(|(index, &value)| {
+        // Check order: sorted after previous_value, or on the right side of the selected element
+        let in_order = match selected {
+            Some(k) => match index.cmp(&k) {
+                Ordering::Less => value <= data[k],
+                Ordering::Equal => true,
+                Ordering::Greater => value >= data[k],
+            },
+            None => previous.is_none_or(|previous_value| previous_value <= value),
+        };
+        // Select color: Different color if out of order, the selected element keeps its highlight
+        let color = if !in_order {
+            correct = false;
+            COLOR_DONE_ERROR
+        } else if selected == Some(index) {
+            COLOR_PIVOT
         } else {
             COLOR_DONE_OK
         };
         // Send update
         channel
-            .send(vec![ListUpdate {
+            .send(WindowUpdate::Draw(vec![ListUpdate {
+                band: Band::Main,
                 index,
                 value,
                 color,
-            }])
+            }]))
             .unwrap();
         // Store previous value
         previous = Some(value);
     });
+    correct
 }
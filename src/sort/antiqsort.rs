@@ -0,0 +1,183 @@
+//! McIlroy's adversary for comparison sorts ("A Killer Adversary for Quicksort", 1999).
+//!
+//! Every element starts as "gas", larger than any other value. When two gas elements are
+//! compared, one of them freezes into "solid", taking the next smallest value: the pivot
+//! candidate if it is one of them, otherwise the second one. The candidate is the element
+//! left gas by the latest comparison that involved a gas element. A pivot is compared
+//! against every other element, so it is soon frozen as the smallest value still available,
+//! and partitioning around it splits off almost nothing. After the sort, the values given to
+//! the elements form an input that makes the algorithm perform the very same comparisons.
+//!
+//! The adversary lives in the element type rather than in `List::compare`, so comparisons that
+//! algorithms make on copied values are decided lazily as well.
+
+use super::{List, RadixKey};
+use std::cell::Cell;
+use std::cmp::Ordering;
+
+/// Adversary deciding the comparisons between the items of a list of `length` elements
+pub struct AntiQsort {
+    values: Vec<Cell<usize>>,
+    solid: Cell<usize>,
+    candidate: Cell<usize>,
+    compares: Cell<usize>,
+}
+impl AntiQsort {
+    pub fn new(length: usize) -> AntiQsort {
+        AntiQsort {
+            values: (0..length).map(|_| Cell::new(length)).collect(),
+            solid: Cell::new(0),
+            candidate: Cell::new(0),
+            compares: Cell::new(0),
+        }
+    }
+    /// Items to be sorted, one for each position of the input
+    pub fn items(&self) -> Vec<AntiQsortItem<'_>> {
+        (0..self.values.len())
+            .map(|id| AntiQsortItem {
+                id,
+                adversary: self,
+            })
+            .collect()
+    }
+    /// Comparisons decided so far
+    pub fn compares(&self) -> usize {
+        self.compares.get()
+    }
+    /// Input reproducing the comparisons made so far, a permutation of `1..=length`.
+    /// Elements never frozen get the largest values in order.
+    pub fn input(&self) -> Vec<u32> {
+        for id in 0..self.values.len() {
+            if self.is_gas(id) {
+                self.freeze(id);
+            }
+        }
+        self.values
+            .iter()
+            .map(|value| value.get() as u32 + 1)
+            .collect()
+    }
+    fn is_gas(&self, id: usize) -> bool {
+        self.values[id].get() == self.values.len()
+    }
+    fn freeze(&self, id: usize) -> usize {
+        self.values[id].set(self.solid.get());
+        self.solid.set(self.solid.get() + 1);
+        self.values[id].get()
+    }
+    fn compare(&self, a: usize, b: usize) -> Ordering {
+        self.compares.set(self.compares.get() + 1);
+        if self.is_gas(a) && self.is_gas(b) {
+            if a == self.candidate.get() {
+                self.freeze(a);
+            } else {
+                self.freeze(b);
+            }
+        }
+        if self.is_gas(a) {
+            self.candidate.set(a);
+        } else if self.is_gas(b) {
+            self.candidate.set(b);
+        }
+        self.values[a].get().cmp(&self.values[b].get())
+    }
+}
+
+/// Element of a list whose comparisons are decided by an `AntiQsort` adversary
+#[derive(Copy, Clone)]
+pub struct AntiQsortItem<'a> {
+    id: usize,
+    adversary: &'a AntiQsort,
+}
+impl<'a> AntiQsortItem<'a> {
+    /// Position of the item in the input
+    pub fn id(&self) -> usize {
+        self.id
+    }
+}
+impl<'a> Ord for AntiQsortItem<'a> {
+    fn cmp(&self, other: &AntiQsortItem<'a>) -> Ordering {
+        self.adversary.compare(self.id, other.id)
+    }
+}
+impl<'a> PartialOrd for AntiQsortItem<'a> {
+    fn partial_cmp(&self, other: &AntiQsortItem<'a>) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+impl<'a> PartialEq for AntiQsortItem<'a> {
+    fn eq(&self, other: &AntiQsortItem<'a>) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+impl<'a> Eq for AntiQsortItem<'a> {}
+impl<'a> RadixKey for AntiQsortItem<'a> {
+    const BITS: u32 = 32;
+    /// Reading a key freezes the item, since non-comparison sorts cannot be fooled lazily
+    fn radix_key(&self) -> u64 {
+        if self.adversary.is_gas(self.id) {
+            self.adversary.freeze(self.id);
+        }
+        self.adversary.values[self.id].get() as u64
+    }
+}
+impl<'a> std::fmt::Display for AntiQsortItem<'a> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
+        write!(f, "#{}", self.id)
+    }
+}
+
+/// Run `sort_fn` against the adversary on `length` elements, returning the killer input
+/// and the number of comparisons it causes
+pub fn antiqsort<F>(length: usize, sort_fn: F) -> (Vec<u32>, usize)
+where
+    F: for<'a> FnOnce(&mut dyn List<AntiQsortItem<'a>>),
+{
+    let adversary = AntiQsort::new(length);
+    let mut items = adversary.items();
+    sort_fn(&mut items);
+    (adversary.input(), adversary.compares())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{heapsort, introsort, quicksort_with_pivot, CountingList, PivotStrategy};
+    #[test]
+    fn test_antiqsort_input() {
+        let (input, _) = antiqsort(100, |list| {
+            quicksort_with_pivot(list, PivotStrategy::Middle)
+        });
+        let mut sorted = input.clone();
+        sorted.sort_unstable();
+        assert_eq!(sorted, (1..=100).collect::<Vec<_>>());
+    }
+    #[test]
+    fn test_antiqsort_quadratic() {
+        let strategies = [
+            PivotStrategy::First,
+            PivotStrategy::Middle,
+            PivotStrategy::MedianOfThree,
+            PivotStrategy::Ninther,
+        ];
+        for &pivot_strategy in strategies.iter() {
+            let (input, compares) =
+                antiqsort(500, |list| quicksort_with_pivot(list, pivot_strategy));
+            assert!(compares > 500 * 500 / 10, "{} {}", pivot_strategy, compares);
+            // The input reproduces the comparisons made against the adversary
+            let mut test_slice = input;
+            let mut list = CountingList::new(&mut test_slice);
+            quicksort_with_pivot(&mut list, pivot_strategy);
+            assert_eq!(list.statistics().compares, compares);
+            assert_eq!(test_slice, (1..=500).collect::<Vec<_>>());
+        }
+    }
+    #[test]
+    fn test_antiqsort_guarded() {
+        // Heapsort and introsort stay O(n log n) whatever the comparisons answer
+        let (_, compares) = antiqsort(500, |list| heapsort(list));
+        assert!(compares < 4 * 500 * 9);
+        let (_, compares) = antiqsort(500, |list| introsort(list));
+        assert!(compares < 4 * 500 * 9);
+    }
+}
@@ -0,0 +1,234 @@
+use super::{insertionsort_range, lower_bound, upper_bound, Highlight, List};
+use std::cmp::Ordering;
+
+/// Chunks of this size are insertion sorted before merging
+const INSERTION_SORT_LENGTH: usize = 16;
+
+/// Merge the sorted ranges `begin..middle` and `middle..end` by repeatedly moving the
+/// elements of the second range that go before the first one with a rotation
+fn merge_in_place<T>(list: &mut dyn List<T>, begin: usize, middle: usize, end: usize)
+where
+    T: Copy + Ord + std::fmt::Display,
+{
+    let (mut begin, mut middle) = (begin, middle);
+    while begin < middle && middle < end {
+        // Skip the elements of the first range which are already in place
+        begin = upper_bound(list, begin, middle, middle);
+        if begin == middle {
+            break;
+        }
+        let cut = lower_bound(list, middle, end, begin);
+        list.rotate_range(begin, cut, middle - begin);
+        begin += cut - middle;
+        middle = cut;
+    }
+}
+
+/// Smallest integer square root, at least 1
+fn block_length(length: usize) -> usize {
+    let mut root = 1;
+    while (root + 1) * (root + 1) <= length {
+        root += 1;
+    }
+    root
+}
+
+/// Move up to `wanted` distinct values of the sorted range `begin..end`, the first one
+/// of each, to its start in increasing order, keeping the rest sorted after them.
+/// Returns how many were found.
+fn extract_keys<T>(list: &mut dyn List<T>, begin: usize, end: usize, wanted: usize) -> usize
+where
+    T: Copy + Ord + std::fmt::Display,
+{
+    // The keys found so far are kept together at `first..first + count`, and rolled
+    // forward past the repeated values whenever a new one turns up
+    let (mut first, mut count) = (begin, 0);
+    for index in begin..end {
+        if count == wanted {
+            break;
+        }
+        if count == 0 || list.compare(first + count - 1, index) == Ordering::Less {
+            if first + count < index {
+                list.rotate_range(first, index, count);
+            }
+            first = index - count;
+            count += 1;
+        }
+    }
+    if first > begin {
+        list.rotate_range(begin, first + count, first - begin);
+    }
+    count
+}
+
+/// Merge the sorted ranges `begin..middle` (A) and `middle..end` (B) as in WikiSort.
+/// A is split into blocks which are rolled through B by block swaps. Whenever the smallest
+/// A block goes before the end of the last B block, it is dropped at its place and the
+/// previously dropped A block is merged locally with the B elements between both.
+/// The smallest A block is the one with the smallest head. Heads may be equal, so the
+/// second element of each A block is swapped with one of the `key_count` distinct values
+/// at `keys`, in increasing order, to break ties, and swapped back when it is dropped.
+fn block_merge<T>(
+    list: &mut dyn List<T>,
+    begin: usize,
+    middle: usize,
+    end: usize,
+    keys: usize,
+    key_count: usize,
+) where
+    T: Copy + Ord + std::fmt::Display,
+{
+    if list.compare(middle - 1, middle) != Ordering::Greater {
+        return;
+    }
+    // Everything in B goes before A
+    if list.compare(end - 1, begin) == Ordering::Less {
+        list.rotate_range(begin, end, middle - begin);
+        return;
+    }
+    list.highlight(begin, end, Highlight::Merge);
+    // Use longer blocks if there are not enough keys to tag them all
+    let length_a = middle - begin;
+    let block = block_length(length_a).max((length_a + key_count - 1) / key_count.max(1));
+    // The first A block is uneven and stays in place
+    let mut last_a = (begin, begin + length_a % block);
+    let mut last_b = (last_a.1, last_a.1);
+    let mut blocks_a = (last_a.1, middle);
+    let mut block_b = (middle, end.min(middle + block));
+    let count = (middle - last_a.1) / block;
+    debug_assert!(count <= key_count && (count < 2 || block >= 2));
+    if count > 1 {
+        for tag in 0..count {
+            list.swap(blocks_a.0 + tag * block + 1, keys + tag);
+        }
+    }
+    // A blocks are dropped in their original order, so this is also the key of the next one
+    let mut dropped = 0;
+    while blocks_a.0 < blocks_a.1 {
+        let mut min_a = blocks_a.0;
+        for a in (blocks_a.0 + block..blocks_a.1).step_by(block) {
+            let order = match list.compare(a, min_a) {
+                Ordering::Equal => list.compare(a + 1, min_a + 1),
+                order => order,
+            };
+            if order == Ordering::Less {
+                min_a = a;
+            }
+        }
+        if block_b.0 == block_b.1
+            || (last_b.0 < last_b.1 && list.compare(last_b.1 - 1, min_a) != Ordering::Less)
+        {
+            // Drop the smallest A block before the B elements not smaller than its first one
+            let split = lower_bound(list, last_b.0, last_b.1, min_a);
+            let remaining = last_b.1 - split;
+            if min_a > blocks_a.0 {
+                list.swap_blocks(blocks_a.0, min_a, block);
+            }
+            if count > 1 {
+                list.swap(blocks_a.0 + 1, keys + dropped);
+            }
+            dropped += 1;
+            list.rotate_range(split, blocks_a.0 + block, blocks_a.0 - split);
+            merge_in_place(list, last_a.0, last_a.1, split);
+            last_a = (blocks_a.0 - remaining, blocks_a.0 - remaining + block);
+            list.highlight(last_a.0, last_a.1, Highlight::Block);
+            last_b = (last_a.1, last_a.1 + remaining);
+            blocks_a.0 += block;
+        } else if block_b.1 - block_b.0 < block {
+            // Move the uneven last B block before the A blocks
+            list.rotate_range(blocks_a.0, block_b.1, blocks_a.1 - blocks_a.0);
+            let length = block_b.1 - block_b.0;
+            last_b = (blocks_a.0, blocks_a.0 + length);
+            blocks_a = (blocks_a.0 + length, blocks_a.1 + length);
+            block_b.0 = block_b.1;
+        } else {
+            // Roll the first A block to the end by swapping it with the next B block
+            list.swap_blocks(blocks_a.0, block_b.0, block);
+            last_b = (blocks_a.0, blocks_a.0 + block);
+            blocks_a = (blocks_a.0 + block, blocks_a.1 + block);
+            block_b = (block_b.0 + block, end.min(block_b.1 + block));
+        }
+    }
+    merge_in_place(list, last_a.0, last_a.1, end);
+    list.highlight(begin, end, Highlight::None);
+}
+
+/// Stable in-place block merge sort (WikiSort). Chunks are insertion sorted and then
+/// merged bottom up without any buffer. On each level, the distinct values used as keys
+/// by `block_merge` are taken from the first range and merged back at the end.
+pub fn blocksort<T>(list: &mut dyn List<T>)
+where
+    T: Copy + Ord + std::fmt::Display,
+{
+    let length = list.len();
+    for begin in (0..length).step_by(INSERTION_SORT_LENGTH) {
+        insertionsort_range(list, begin, length.min(begin + INSERTION_SORT_LENGTH));
+    }
+    let mut width = INSERTION_SORT_LENGTH;
+    while width < length {
+        // One key for each A block of the widest ranges
+        let wanted = width / block_length(width);
+        let key_count = extract_keys(list, 0, width, wanted);
+        list.highlight(0, key_count, Highlight::Block);
+        block_merge(list, key_count, width, length.min(2 * width), 0, key_count);
+        for begin in (2 * width..length - width).step_by(2 * width) {
+            block_merge(
+                list,
+                begin,
+                begin + width,
+                length.min(begin + 2 * width),
+                0,
+                key_count,
+            );
+        }
+        // The keys were the first of their values, so they go before the equal ones
+        list.highlight(0, key_count, Highlight::None);
+        merge_in_place(list, 0, key_count, length.min(2 * width));
+        width *= 2;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tagged::{is_sorted_stably, tagged};
+    use crate::CountingList;
+    #[test]
+    fn test_blocksort() {
+        let mut test_slice = vec![1, 4, 123, 7, 8, 4, 8, 8, 2, 4, 57, 8, 324, 213];
+        let mut test_slice2 = test_slice.clone();
+        assert_eq!(test_slice, test_slice2);
+        test_slice.sort_unstable();
+        blocksort(&mut test_slice2);
+        assert_eq!(test_slice, test_slice2);
+        println!("{:?}", test_slice2);
+    }
+    #[test]
+    fn test_blocksort_stable() {
+        for &length in [17, 100, 1000, 3000].iter() {
+            for &distinct in [1, 2, 13, 1000].iter() {
+                let keys: Vec<u32> = (0..length).map(|i| (i * 7919) % distinct).collect();
+                let test_slice = tagged(&keys);
+                let mut test_slice2 = test_slice.clone();
+                let mut list = CountingList::new(&mut test_slice2);
+                blocksort(&mut list);
+                assert_eq!(list.statistics().allocs, 0);
+                assert!(is_sorted_stably(&test_slice, &test_slice2));
+            }
+        }
+    }
+    #[test]
+    fn test_block_merge() {
+        // Uneven first A block and last B block
+        let mut test_slice: Vec<u32> = (0..30).map(|i| i * 2).collect();
+        test_slice.extend((0..23).map(|i| i * 3));
+        let mut expected = test_slice.clone();
+        expected.sort_unstable();
+        let keys = [100, 101, 102, 103, 104];
+        let mut with_keys = keys.to_vec();
+        with_keys.extend(&test_slice);
+        block_merge(&mut with_keys, 5, 35, 58, 0, keys.len());
+        assert_eq!(with_keys[..5], keys);
+        assert_eq!(with_keys[5..], expected[..]);
+    }
+}
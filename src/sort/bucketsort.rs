@@ -0,0 +1,98 @@
+use super::countingsort::key_bounds;
+use super::{insertionsort, List, RadixKey, SubList};
+
+/// Average number of elements per bucket
+const BUCKET_SIZE: usize = 8;
+
+pub fn bucketsort<T>(list: &mut dyn List<T>)
+where
+    T: RadixKey + std::fmt::Display,
+{
+    bucketsort_with_inner(list, &mut insertionsort);
+}
+
+/// Distribute the elements into buckets covering equal parts of the key range,
+/// then sort each bucket with `inner_sort`
+pub fn bucketsort_with_inner<T>(
+    list: &mut dyn List<T>,
+    inner_sort: &mut dyn FnMut(&mut dyn List<T>),
+) where
+    T: RadixKey + std::fmt::Display,
+{
+    let length = list.len();
+    if length < 2 {
+        return;
+    }
+    let (min, max) = key_bounds(list);
+    if min == max {
+        return;
+    }
+    let buckets = length.div_ceil(BUCKET_SIZE);
+    let bucket_of =
+        |key: u64| (u128::from(key - min) * buckets as u128 / (u128::from(max - min) + 1)) as usize;
+    let mut sizes = list.alloc_histogram("sizes", buckets);
+    for index in 0..length {
+        let bucket = bucket_of(list.key(index));
+        let size = list.histogram_get(&sizes, bucket);
+        list.histogram_set(&mut sizes, bucket, size + 1);
+    }
+    // Each size becomes the position of the bucket, and ends up as the position of the next one
+    let mut position = 0;
+    for bucket in 0..buckets {
+        let size = list.histogram_get(&sizes, bucket);
+        list.histogram_set(&mut sizes, bucket, position);
+        position += size;
+    }
+    let mut output = list.alloc_buffer("buckets", length);
+    for index in 0..length {
+        let bucket = bucket_of(list.key(index));
+        let position = list.histogram_get(&sizes, bucket);
+        list.copy_to_buffer(index, &mut output, position);
+        list.histogram_set(&mut sizes, bucket, position + 1);
+    }
+    for index in 0..length {
+        list.copy_from_buffer(&output, index, index);
+    }
+    list.free_buffer(output);
+    let ends: Vec<usize> = (0..buckets)
+        .map(|bucket| list.histogram_get(&sizes, bucket))
+        .collect();
+    list.free_histogram(sizes);
+    // The inner sort may allocate buffers and histograms of its own
+    let mut begin = 0;
+    for end in ends {
+        if end - begin > 1 {
+            inner_sort(&mut SubList::new(list, begin, end));
+        }
+        begin = end;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{countingsort, mergesort};
+    #[test]
+    fn test_bucketsort() {
+        let mut test_slice = vec![1, 4, 123, 7, 8, 4, 8, 8, 2, 4, 57, 8, 324, 213];
+        let mut test_slice2 = test_slice.clone();
+        assert_eq!(test_slice, test_slice2);
+        test_slice.sort_unstable();
+        bucketsort(&mut test_slice2);
+        assert_eq!(test_slice, test_slice2);
+        println!("{:?}", test_slice2);
+    }
+    #[test]
+    fn test_bucketsort_with_inner() {
+        let test_slice: Vec<u32> = (0..500)
+            .map(|i| (i * 7919) % 1000 + 4_000_000_000)
+            .collect();
+        let mut expected = test_slice.clone();
+        expected.sort_unstable();
+        for inner_sort in [mergesort, countingsort, bucketsort].iter_mut() {
+            let mut test_slice2 = test_slice.clone();
+            bucketsort_with_inner(&mut test_slice2, inner_sort);
+            assert_eq!(test_slice2, expected);
+        }
+    }
+}
@@ -0,0 +1,419 @@
+use super::{Buffer, Highlight, Histogram, List, RadixKey};
+
+use std::cell::RefCell;
+
+/// Shape of one cache level, with sizes counted in list elements
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct CacheConfig {
+    /// Elements in each cache line
+    pub line_size: usize,
+    /// Elements held by the whole level
+    pub capacity: usize,
+    /// Lines in each set
+    pub associativity: usize,
+}
+impl CacheConfig {
+    pub fn sets(&self) -> usize {
+        self.capacity / (self.line_size * self.associativity)
+    }
+}
+impl std::fmt::Display for CacheConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
+        write!(
+            f,
+            "{}:{}:{}",
+            self.line_size, self.capacity, self.associativity
+        )
+    }
+}
+impl std::str::FromStr for CacheConfig {
+    type Err = String;
+    /// `LINE:CAPACITY:WAYS`, where the capacity is a multiple of the line size times the ways
+    fn from_str(text: &str) -> Result<CacheConfig, String> {
+        let error = || format!("Cannot parse \"{}\" as a LINE:CAPACITY:WAYS cache", text);
+        let sizes = text
+            .split(':')
+            .map(|size| match size.parse::<usize>() {
+                Ok(size) if size > 0 => Ok(size),
+                _ => Err(error()),
+            })
+            .collect::<Result<Vec<usize>, String>>()?;
+        let config = match sizes[..] {
+            [line_size, capacity, associativity] => CacheConfig {
+                line_size,
+                capacity,
+                associativity,
+            },
+            _ => return Err(error()),
+        };
+        if config.capacity % (config.line_size * config.associativity) != 0 {
+            return Err(format!(
+                "Cache capacity {} is not a multiple of the line size {} times the associativity {}",
+                config.capacity, config.line_size, config.associativity
+            ));
+        }
+        Ok(config)
+    }
+}
+
+/// Hits and misses of a cache level
+#[derive(Copy, Clone, Default, PartialEq, Debug)]
+pub struct CacheStatistics {
+    pub hits: usize,
+    pub misses: usize,
+}
+impl CacheStatistics {
+    pub fn hit_rate(&self) -> f64 {
+        let accesses = self.hits + self.misses;
+        if accesses == 0 {
+            0.0
+        } else {
+            self.hits as f64 / accesses as f64
+        }
+    }
+}
+impl std::fmt::Display for CacheStatistics {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
+        write!(
+            f,
+            "Hits: {}, Misses: {}, Hit rate: {:.1}%",
+            self.hits,
+            self.misses,
+            100.0 * self.hit_rate()
+        )
+    }
+}
+
+/// Set-associative cache level with least recently used replacement
+struct CacheLevel {
+    config: CacheConfig,
+    /// Lines held by each set, from least to most recently used
+    sets: Vec<Vec<usize>>,
+    statistics: CacheStatistics,
+}
+impl CacheLevel {
+    fn new(config: CacheConfig) -> CacheLevel {
+        CacheLevel {
+            config,
+            sets: vec![Vec::with_capacity(config.associativity); config.sets()],
+            statistics: CacheStatistics::default(),
+        }
+    }
+    /// Look up the line holding element `index`, loading it on a miss. Returns whether it hit.
+    fn access(&mut self, index: usize) -> bool {
+        let line = index / self.config.line_size;
+        let set_count = self.sets.len();
+        let set = &mut self.sets[line % set_count];
+        let hit = match set.iter().position(|&cached| cached == line) {
+            Some(position) => {
+                set.remove(position);
+                true
+            }
+            None => {
+                if set.len() == self.config.associativity {
+                    set.remove(0);
+                }
+                false
+            }
+        };
+        set.push(line);
+        if hit {
+            self.statistics.hits += 1;
+        } else {
+            self.statistics.misses += 1;
+        }
+        hit
+    }
+}
+
+/// Cache levels looked up in order, each one only on a miss in the previous ones
+pub struct CacheHierarchy {
+    levels: Vec<CacheLevel>,
+}
+impl CacheHierarchy {
+    pub fn new(configs: &[CacheConfig]) -> CacheHierarchy {
+        assert!(!configs.is_empty(), "A cache needs at least one level");
+        CacheHierarchy {
+            levels: configs
+                .iter()
+                .map(|&config| CacheLevel::new(config))
+                .collect(),
+        }
+    }
+    /// Access element `index`, returning the first level that held it or `None` if it came
+    /// from memory. Every level that missed loads its line.
+    pub fn access(&mut self, index: usize) -> Option<usize> {
+        self.levels.iter_mut().position(|level| level.access(index))
+    }
+    pub fn configs(&self) -> Vec<CacheConfig> {
+        self.levels.iter().map(|level| level.config).collect()
+    }
+    /// Statistics of each level, counting only the accesses that reached it
+    pub fn statistics(&self) -> Vec<CacheStatistics> {
+        self.levels.iter().map(|level| level.statistics).collect()
+    }
+}
+impl std::str::FromStr for CacheHierarchy {
+    type Err = String;
+    /// Comma separated `LINE:CAPACITY:WAYS` levels, from the first one looked up
+    fn from_str(text: &str) -> Result<CacheHierarchy, String> {
+        let configs = text
+            .split(',')
+            .map(|level| level.parse::<CacheConfig>())
+            .collect::<Result<Vec<CacheConfig>, String>>()?;
+        Ok(CacheHierarchy::new(&configs))
+    }
+}
+
+/// Called with each element index accessed and the cache level that held it,
+/// `None` if it missed in every level
+pub type CacheObserver<'b> = Box<dyn 'b + Fn(usize, Option<usize>)>;
+
+/// Wraps another `List` and simulates a cache hierarchy over the elements of the main list
+/// accessed through it. Buffers and histograms are not simulated.
+pub struct CacheList<'a, 'b, T>
+where
+    T: Copy + Ord,
+{
+    list: &'a mut dyn List<T>,
+    cache: RefCell<CacheHierarchy>,
+    observer: Option<CacheObserver<'b>>,
+}
+impl<'a, 'b, T> CacheList<'a, 'b, T>
+where
+    T: Copy + Ord,
+{
+    pub fn new(list: &'a mut dyn List<T>, cache: CacheHierarchy) -> CacheList<'a, 'b, T> {
+        CacheList {
+            list,
+            cache: RefCell::new(cache),
+            observer: None,
+        }
+    }
+    /// Like `new`, calling `observer` on every access before it reaches the wrapped list
+    pub fn with_observer(
+        list: &'a mut dyn List<T>,
+        cache: CacheHierarchy,
+        observer: CacheObserver<'b>,
+    ) -> CacheList<'a, 'b, T> {
+        CacheList {
+            list,
+            cache: RefCell::new(cache),
+            observer: Some(observer),
+        }
+    }
+    pub fn statistics(&self) -> Vec<CacheStatistics> {
+        self.cache.borrow().statistics()
+    }
+    pub fn into_cache(self) -> CacheHierarchy {
+        self.cache.into_inner()
+    }
+    fn access(&self, index: usize) {
+        let level = self.cache.borrow_mut().access(index);
+        if let Some(observer) = &self.observer {
+            observer(index, level);
+        }
+    }
+    fn access_range(&self, begin: usize, end: usize) {
+        for index in begin..end {
+            self.access(index);
+        }
+    }
+}
+impl<'a, 'b, T> List<T> for CacheList<'a, 'b, T>
+where
+    T: Copy + Ord,
+{
+    fn as_slice(&self) -> &[T] {
+        self.list.as_slice()
+    }
+    fn as_mut_slice(&mut self) -> &mut [T] {
+        self.list.as_mut_slice()
+    }
+    fn get(&self, index: usize) -> T {
+        self.access(index);
+        self.list.get(index)
+    }
+    fn set(&mut self, index: usize, value: T) {
+        self.access(index);
+        self.list.set(index, value);
+    }
+    fn compare(&self, a: usize, b: usize) -> std::cmp::Ordering {
+        self.access(a);
+        self.access(b);
+        self.list.compare(a, b)
+    }
+    fn swap(&mut self, a: usize, b: usize) {
+        self.access(a);
+        self.access(b);
+        self.list.swap(a, b);
+    }
+    fn reverse_range(&mut self, begin: usize, end: usize) {
+        self.access_range(begin, end);
+        self.list.reverse_range(begin, end);
+    }
+    fn rotate_range(&mut self, begin: usize, end: usize, k: usize) {
+        self.access_range(begin, end);
+        self.list.rotate_range(begin, end, k);
+    }
+    fn swap_blocks(&mut self, a: usize, b: usize, len: usize) {
+        self.access_range(a, a + len);
+        self.access_range(b, b + len);
+        self.list.swap_blocks(a, b, len);
+    }
+    fn alloc_buffer(&mut self, name: &'static str, len: usize) -> Buffer<T> {
+        self.list.alloc_buffer(name, len)
+    }
+    fn free_buffer(&mut self, buffer: Buffer<T>) {
+        self.list.free_buffer(buffer);
+    }
+    fn buffer_get(&self, buffer: &Buffer<T>, index: usize) -> T {
+        self.list.buffer_get(buffer, index)
+    }
+    fn buffer_set(&mut self, buffer: &mut Buffer<T>, index: usize, value: T) {
+        self.list.buffer_set(buffer, index, value);
+    }
+    fn copy_to_buffer(&mut self, index: usize, buffer: &mut Buffer<T>, buffer_index: usize) {
+        self.access(index);
+        self.list.copy_to_buffer(index, buffer, buffer_index);
+    }
+    fn copy_from_buffer(&mut self, buffer: &Buffer<T>, buffer_index: usize, index: usize) {
+        self.access(index);
+        self.list.copy_from_buffer(buffer, buffer_index, index);
+    }
+    fn highlight(&self, begin: usize, end: usize, highlight: Highlight) {
+        self.list.highlight(begin, end, highlight);
+    }
+    fn parallel(&self, count: usize) {
+        self.list.parallel(count);
+    }
+    fn key(&self, index: usize) -> u64
+    where
+        T: RadixKey,
+    {
+        self.access(index);
+        self.list.key(index)
+    }
+    fn alloc_histogram(&mut self, name: &'static str, len: usize) -> Histogram {
+        self.list.alloc_histogram(name, len)
+    }
+    fn free_histogram(&mut self, histogram: Histogram) {
+        self.list.free_histogram(histogram);
+    }
+    fn histogram_get(&self, histogram: &Histogram, index: usize) -> usize {
+        self.list.histogram_get(histogram, index)
+    }
+    fn histogram_set(&mut self, histogram: &mut Histogram, index: usize, count: usize) {
+        self.list.histogram_set(histogram, index, count);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{heapsort, mergesort, quicksort2_with_pivot, PivotStrategy};
+    fn config(text: &str) -> CacheConfig {
+        text.parse::<CacheConfig>().unwrap()
+    }
+    #[test]
+    fn test_cache_lines() {
+        // Sequential accesses only miss on the first element of each line
+        let mut cache = CacheHierarchy::new(&[config("4:8:1")]);
+        for index in 0..16 {
+            assert_eq!(
+                cache.access(index),
+                if index % 4 == 0 { None } else { Some(0) }
+            );
+        }
+        assert_eq!(
+            cache.statistics(),
+            vec![CacheStatistics {
+                hits: 12,
+                misses: 4
+            }]
+        );
+    }
+    #[test]
+    fn test_cache_lru() {
+        // A single set of two lines evicts the least recently used one
+        let mut cache = CacheHierarchy::new(&[config("1:2:2")]);
+        let levels: Vec<_> = [0, 1, 0, 2, 1, 0]
+            .iter()
+            .map(|&index| cache.access(index))
+            .collect();
+        assert_eq!(levels, vec![None, None, Some(0), None, None, None]);
+        // Direct-mapped lines conflict when their indices share a set
+        let mut cache = CacheHierarchy::new(&[config("1:2:1")]);
+        let levels: Vec<_> = [0, 2, 0, 1, 0]
+            .iter()
+            .map(|&index| cache.access(index))
+            .collect();
+        assert_eq!(levels, vec![None, None, None, None, Some(0)]);
+    }
+    #[test]
+    fn test_cache_levels() {
+        let mut cache = "1:1:1,1:4:4".parse::<CacheHierarchy>().unwrap();
+        let levels: Vec<_> = [0, 1, 0, 1, 5]
+            .iter()
+            .map(|&index| cache.access(index))
+            .collect();
+        assert_eq!(levels, vec![None, None, Some(1), Some(1), None]);
+        let statistics = cache.statistics();
+        assert_eq!(statistics[0].misses, 5);
+        assert_eq!(statistics[1].hits + statistics[1].misses, 5);
+        assert_eq!(cache.configs(), vec![config("1:1:1"), config("1:4:4")]);
+    }
+    #[test]
+    fn test_cache_list() {
+        let mut test_slice = vec![1, 4, 123, 7, 8, 4, 8, 8, 2, 4, 57, 8, 324, 213];
+        let accesses = RefCell::new(Vec::new());
+        let cache = CacheHierarchy::new(&[config("4:8:2")]);
+        let observer = Box::new(|index, level| accesses.borrow_mut().push((index, level)));
+        let mut list = CacheList::with_observer(&mut test_slice, cache, observer);
+        mergesort(&mut list);
+        let statistics = list.statistics();
+        assert_eq!(
+            statistics[0].hits + statistics[0].misses,
+            accesses.borrow().len()
+        );
+        assert_eq!(
+            statistics[0].misses,
+            accesses
+                .borrow()
+                .iter()
+                .filter(|(_, level)| level.is_none())
+                .count()
+        );
+        assert_eq!(
+            test_slice,
+            vec![1, 2, 4, 4, 4, 7, 8, 8, 8, 8, 57, 123, 213, 324]
+        );
+    }
+    #[test]
+    fn test_cache_locality() {
+        // Similar access counts, but quicksort scans ranges sequentially
+        // while heapsort jumps between parents and children
+        let input: Vec<u32> = (0..4096).map(|i| (i * 7919) % 4096).collect();
+        let run = |sort_fn: &dyn Fn(&mut dyn List<u32>)| {
+            let mut test_slice = input.clone();
+            let mut list =
+                CacheList::new(&mut test_slice, CacheHierarchy::new(&[config("8:256:4")]));
+            sort_fn(&mut list);
+            list.statistics()[0]
+        };
+        let quicksort = run(&|list| quicksort2_with_pivot(list, PivotStrategy::MedianOfThree));
+        let heapsort = run(&|list| heapsort(list));
+        let accesses = |statistics: CacheStatistics| statistics.hits + statistics.misses;
+        assert!(accesses(quicksort) < 2 * accesses(heapsort));
+        assert!(2 * quicksort.misses < heapsort.misses);
+    }
+    #[test]
+    fn test_cache_names() {
+        assert_eq!(config("8:64:2").to_string(), "8:64:2");
+        assert_eq!(config("8:64:2").sets(), 4);
+        assert!("8:60:2".parse::<CacheConfig>().is_err());
+        assert!("8:64".parse::<CacheConfig>().is_err());
+        assert!("8:0:2".parse::<CacheConfig>().is_err());
+        assert!("8:64:2,x".parse::<CacheHierarchy>().is_err());
+    }
+}
@@ -0,0 +1,48 @@
+use super::List;
+use std::cmp::Ordering;
+
+/// Bubble sort passing alternately forwards and backwards, so that small elements
+/// near the end move to the front as quickly as large ones move to the back
+pub fn cocktailshakersort<T>(list: &mut dyn List<T>)
+where
+    T: Copy + Ord + std::fmt::Display,
+{
+    if list.len() < 2 {
+        return;
+    }
+    // Elements outside begin..=end are already in place
+    let mut begin = 0;
+    let mut end = list.len() - 1;
+    while begin < end {
+        let mut last_swap = begin;
+        for index in begin..end {
+            if list.compare(index, index + 1) == Ordering::Greater {
+                list.swap(index, index + 1);
+                last_swap = index;
+            }
+        }
+        end = last_swap;
+        for index in (begin..end).rev() {
+            if list.compare(index, index + 1) == Ordering::Greater {
+                list.swap(index, index + 1);
+                last_swap = index + 1;
+            }
+        }
+        begin = last_swap;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn test_cocktailshakersort() {
+        let mut test_slice = vec![1, 4, 123, 7, 8, 4, 8, 8, 2, 4, 57, 8, 324, 213];
+        let mut test_slice2 = test_slice.clone();
+        assert_eq!(test_slice, test_slice2);
+        test_slice.sort_unstable();
+        cocktailshakersort(&mut test_slice2);
+        assert_eq!(test_slice, test_slice2);
+        println!("{:?}", test_slice2);
+    }
+}
@@ -0,0 +1,109 @@
+use super::List;
+use std::cmp::Ordering;
+
+/// Factor by which comb sort divides the gap after each pass, which is greater than 1
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct Shrink {
+    factor: f64,
+}
+impl Shrink {
+    pub fn new(factor: f64) -> Result<Shrink, String> {
+        if factor > 1.0 && factor.is_finite() {
+            Ok(Shrink { factor })
+        } else {
+            Err(format!("Shrink factor {} is not greater than 1", factor))
+        }
+    }
+    pub fn factor(&self) -> f64 {
+        self.factor
+    }
+    /// Gap of the pass after one with `gap`
+    fn next_gap(&self, gap: usize) -> usize {
+        ((gap as f64 / self.factor) as usize).max(1)
+    }
+}
+impl Default for Shrink {
+    /// Factor suggested by Lacey and Box
+    fn default() -> Shrink {
+        Shrink { factor: 1.3 }
+    }
+}
+impl std::fmt::Display for Shrink {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
+        write!(f, "{}", self.factor)
+    }
+}
+impl std::str::FromStr for Shrink {
+    type Err = String;
+    fn from_str(text: &str) -> Result<Shrink, String> {
+        match text.parse::<f64>() {
+            Ok(factor) => Shrink::new(factor),
+            Err(_) => Err(format!("Cannot parse \"{}\" as number", text)),
+        }
+    }
+}
+
+pub fn combsort<T>(list: &mut dyn List<T>)
+where
+    T: Copy + Ord + std::fmt::Display,
+{
+    combsort_with_shrink(list, Shrink::default());
+}
+
+/// Bubble sort comparing elements `gap` apart, with the gap divided by `shrink`
+/// after each pass until it reaches 1
+pub fn combsort_with_shrink<T>(list: &mut dyn List<T>, shrink: Shrink)
+where
+    T: Copy + Ord + std::fmt::Display,
+{
+    let length = list.len();
+    let mut gap = length;
+    let mut swapped = true;
+    while gap > 1 || swapped {
+        gap = shrink.next_gap(gap);
+        swapped = false;
+        for index in gap..length {
+            if list.compare(index - gap, index) == Ordering::Greater {
+                list.swap(index - gap, index);
+                swapped = true;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn test_combsort() {
+        let mut test_slice = vec![1, 4, 123, 7, 8, 4, 8, 8, 2, 4, 57, 8, 324, 213];
+        let mut test_slice2 = test_slice.clone();
+        assert_eq!(test_slice, test_slice2);
+        test_slice.sort_unstable();
+        combsort(&mut test_slice2);
+        assert_eq!(test_slice, test_slice2);
+        println!("{:?}", test_slice2);
+    }
+    #[test]
+    fn test_combsort_with_shrink() {
+        let mut test_slice = vec![1, 4, 123, 7, 8, 4, 8, 8, 2, 4, 57, 8, 324, 213];
+        test_slice.sort_unstable();
+        for &shrink in [1.1, 1.3, 2.0, 100.0].iter() {
+            let shrink = Shrink::new(shrink).unwrap();
+            let mut test_slice2 = vec![1, 4, 123, 7, 8, 4, 8, 8, 2, 4, 57, 8, 324, 213];
+            combsort_with_shrink(&mut test_slice2, shrink);
+            assert_eq!(test_slice, test_slice2);
+        }
+    }
+    #[test]
+    fn test_shrink() {
+        assert_eq!("1.3".parse::<Shrink>(), Ok(Shrink::default()));
+        assert_eq!(Shrink::new(2.0).unwrap().next_gap(9), 4);
+        assert_eq!(Shrink::new(2.0).unwrap().next_gap(1), 1);
+        assert!("1".parse::<Shrink>().is_err());
+        assert!("0.5".parse::<Shrink>().is_err());
+        assert!("inf".parse::<Shrink>().is_err());
+        assert!("NaN".parse::<Shrink>().is_err());
+        assert!("fast".parse::<Shrink>().is_err());
+    }
+}
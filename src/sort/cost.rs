@@ -0,0 +1,247 @@
+use super::Operation;
+
+/// Cost of a comparison in the preset where comparisons dominate
+const EXPENSIVE_COMPARE: f64 = 10.0;
+/// Cost of writing an element in the flash memory preset, where reads cost 1
+const EXPENSIVE_WRITE: f64 = 10.0;
+
+/// Simulated cost of each kind of `Operation`. Block operations cost their value
+/// for every element they move, highlights and parallel markers are free.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct CostModel {
+    pub get: f64,
+    pub set: f64,
+    pub compare: f64,
+    pub swap: f64,
+    pub alloc: f64,
+    pub free: f64,
+    pub buffer_get: f64,
+    pub buffer_set: f64,
+    pub copy_to_buffer: f64,
+    pub copy_from_buffer: f64,
+    pub key: f64,
+    pub histogram_get: f64,
+    pub histogram_set: f64,
+    pub reverse: f64,
+    pub rotate: f64,
+    pub block_swap: f64,
+}
+impl CostModel {
+    pub const NAMES: &'static [&'static str] = &["uniform", "comparisons", "flash"];
+    /// Operation kinds whose cost can be given as `KIND=COST`
+    pub const KINDS: &'static [&'static str] = &[
+        "get",
+        "set",
+        "compare",
+        "swap",
+        "alloc",
+        "free",
+        "buffer_get",
+        "buffer_set",
+        "copy_to_buffer",
+        "copy_from_buffer",
+        "key",
+        "histogram_get",
+        "histogram_set",
+        "reverse",
+        "rotate",
+        "block_swap",
+    ];
+    /// Every operation costs 1, so the simulated time of a run without block
+    /// operations is its operation count
+    pub fn uniform() -> CostModel {
+        CostModel {
+            get: 1.0,
+            set: 1.0,
+            compare: 1.0,
+            swap: 1.0,
+            alloc: 1.0,
+            free: 1.0,
+            buffer_get: 1.0,
+            buffer_set: 1.0,
+            copy_to_buffer: 1.0,
+            copy_from_buffer: 1.0,
+            key: 1.0,
+            histogram_get: 1.0,
+            histogram_set: 1.0,
+            reverse: 1.0,
+            rotate: 1.0,
+            block_swap: 1.0,
+        }
+    }
+    /// Comparisons are much more expensive than moving elements, as with long string keys
+    pub fn comparisons() -> CostModel {
+        CostModel {
+            compare: EXPENSIVE_COMPARE,
+            ..CostModel::uniform()
+        }
+    }
+    /// Writes are much more expensive than reads, as on flash memory
+    pub fn flash() -> CostModel {
+        CostModel {
+            set: EXPENSIVE_WRITE,
+            swap: 2.0 * EXPENSIVE_WRITE,
+            buffer_set: EXPENSIVE_WRITE,
+            copy_to_buffer: EXPENSIVE_WRITE,
+            copy_from_buffer: EXPENSIVE_WRITE,
+            histogram_set: EXPENSIVE_WRITE,
+            reverse: EXPENSIVE_WRITE,
+            rotate: EXPENSIVE_WRITE,
+            block_swap: EXPENSIVE_WRITE,
+            ..CostModel::uniform()
+        }
+    }
+    pub fn cost(&self, operation: Operation) -> f64 {
+        match operation {
+            Operation::Get(_) => self.get,
+            Operation::Set(_) => self.set,
+            Operation::Compare(_, _) => self.compare,
+            Operation::Swap(_, _) => self.swap,
+            Operation::Alloc(_, _) | Operation::AllocHistogram(_, _) => self.alloc,
+            Operation::Free(_) | Operation::FreeHistogram(_) => self.free,
+            Operation::BufferGet(_, _) => self.buffer_get,
+            Operation::BufferSet(_, _) => self.buffer_set,
+            Operation::CopyToBuffer(_, _, _) => self.copy_to_buffer,
+            Operation::CopyFromBuffer(_, _, _) => self.copy_from_buffer,
+            Operation::Key(_) => self.key,
+            Operation::HistogramGet(_, _) => self.histogram_get,
+            Operation::HistogramSet(_, _, _) => self.histogram_set,
+            Operation::Reverse(begin, end) => self.reverse * (end - begin) as f64,
+            Operation::Rotate(begin, end, _) => self.rotate * (end - begin) as f64,
+            Operation::BlockSwap(_, _, len) => self.block_swap * (2 * len) as f64,
+            Operation::Highlight(_, _, _) | Operation::Parallel(_) => 0.0,
+        }
+    }
+    /// Smallest positive cost of any operation kind, or 1 if they are all free
+    pub fn cheapest(&self) -> f64 {
+        let mut model = *self;
+        let cheapest = Self::KINDS
+            .iter()
+            .filter_map(|kind| model.kind_mut(kind).map(|cost| *cost))
+            .filter(|&cost| cost > 0.0)
+            .fold(f64::INFINITY, f64::min);
+        if cheapest.is_finite() {
+            cheapest
+        } else {
+            1.0
+        }
+    }
+    fn kind_mut(&mut self, kind: &str) -> Option<&mut f64> {
+        match kind {
+            "get" => Some(&mut self.get),
+            "set" => Some(&mut self.set),
+            "compare" => Some(&mut self.compare),
+            "swap" => Some(&mut self.swap),
+            "alloc" => Some(&mut self.alloc),
+            "free" => Some(&mut self.free),
+            "buffer_get" => Some(&mut self.buffer_get),
+            "buffer_set" => Some(&mut self.buffer_set),
+            "copy_to_buffer" => Some(&mut self.copy_to_buffer),
+            "copy_from_buffer" => Some(&mut self.copy_from_buffer),
+            "key" => Some(&mut self.key),
+            "histogram_get" => Some(&mut self.histogram_get),
+            "histogram_set" => Some(&mut self.histogram_set),
+            "reverse" => Some(&mut self.reverse),
+            "rotate" => Some(&mut self.rotate),
+            "block_swap" => Some(&mut self.block_swap),
+            _ => None,
+        }
+    }
+}
+impl Default for CostModel {
+    fn default() -> CostModel {
+        CostModel::uniform()
+    }
+}
+impl std::fmt::Display for CostModel {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
+        let mut model = *self;
+        for (i, kind) in Self::KINDS.iter().enumerate() {
+            if i > 0 {
+                write!(f, ",")?;
+            }
+            // unwrap() should be safe because KINDS only lists known kinds
+            write!(f, "{}={}", kind, model.kind_mut(kind).unwrap())?;
+        }
+        Ok(())
+    }
+}
+impl std::str::FromStr for CostModel {
+    type Err = String;
+    /// A preset name, a comma separated list of `KIND=COST` overrides, or a preset followed by them
+    fn from_str(name: &str) -> Result<CostModel, String> {
+        let mut parts = name.split(',').peekable();
+        let mut model = match parts.peek() {
+            Some(&"uniform") => CostModel::uniform(),
+            Some(&"comparisons") => CostModel::comparisons(),
+            Some(&"flash") => CostModel::flash(),
+            Some(part) if !part.contains('=') => {
+                return Err(format!("Cost model \"{}\" not found", part))
+            }
+            _ => CostModel::uniform(),
+        };
+        if parts.peek().is_some_and(|part| !part.contains('=')) {
+            parts.next();
+        }
+        for part in parts {
+            let position = part
+                .find('=')
+                .ok_or_else(|| format!("Cannot parse \"{}\" as KIND=COST", part))?;
+            let (kind, cost) = (&part[..position], &part[position + 1..]);
+            let cost = match cost.parse::<f64>() {
+                Ok(cost) if cost >= 0.0 && cost.is_finite() => cost,
+                _ => return Err(format!("Cannot parse \"{}\" as a cost", cost)),
+            };
+            *model
+                .kind_mut(kind)
+                .ok_or_else(|| format!("Operation kind \"{}\" not found", kind))? = cost;
+        }
+        Ok(model)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn test_cost_presets() {
+        let uniform = CostModel::uniform();
+        assert_eq!(uniform.cost(Operation::Swap(0, 1)), 1.0);
+        assert_eq!(uniform.cost(Operation::Reverse(2, 7)), 5.0);
+        assert_eq!(uniform.cost(Operation::BlockSwap(0, 4, 3)), 6.0);
+        assert_eq!(uniform.cost(Operation::Parallel(4)), 0.0);
+        let comparisons = CostModel::comparisons();
+        assert_eq!(comparisons.cost(Operation::Compare(0, 1)), 10.0);
+        assert_eq!(comparisons.cost(Operation::Set(0)), 1.0);
+        let flash = CostModel::flash();
+        assert_eq!(flash.cost(Operation::Get(0)), 1.0);
+        assert_eq!(flash.cost(Operation::Set(0)), 10.0);
+        assert_eq!(flash.cost(Operation::Swap(0, 1)), 20.0);
+        assert_eq!(flash.cheapest(), 1.0);
+        let free = "uniform,get=0,compare=0.5".parse::<CostModel>().unwrap();
+        assert_eq!(free.cheapest(), 0.5);
+    }
+    #[test]
+    fn test_cost_names() {
+        for name in CostModel::NAMES.iter() {
+            let model = name.parse::<CostModel>().unwrap();
+            assert_eq!(model.to_string().parse::<CostModel>(), Ok(model));
+        }
+        let model = "flash,get=2,swap=0".parse::<CostModel>().unwrap();
+        assert_eq!(model.get, 2.0);
+        assert_eq!(model.swap, 0.0);
+        assert_eq!(model.set, 10.0);
+        let model = "compare=4".parse::<CostModel>().unwrap();
+        assert_eq!(
+            model,
+            CostModel {
+                compare: 4.0,
+                ..CostModel::uniform()
+            }
+        );
+        assert!("disk".parse::<CostModel>().is_err());
+        assert!("flash,write=3".parse::<CostModel>().is_err());
+        assert!("flash,get=-1".parse::<CostModel>().is_err());
+        assert!("flash,get".parse::<CostModel>().is_err());
+    }
+}
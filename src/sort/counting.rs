@@ -0,0 +1,322 @@
+use super::{Buffer, CostModel, Highlight, Histogram, List, Operation, RadixKey};
+
+use std::cell::Cell;
+
+/// Number of operations of each kind performed on a list
+#[derive(Copy, Clone, Default, PartialEq, Debug)]
+pub struct Statistics {
+    pub gets: usize,
+    pub sets: usize,
+    pub compares: usize,
+    pub swaps: usize,
+    pub allocs: usize,
+    pub frees: usize,
+    pub buffer_gets: usize,
+    pub buffer_sets: usize,
+    pub copies_to_buffer: usize,
+    pub copies_from_buffer: usize,
+    pub keys: usize,
+    pub histogram_gets: usize,
+    pub histogram_sets: usize,
+    pub reverses: usize,
+    pub rotates: usize,
+    pub block_swaps: usize,
+    /// Elements written to the list by the operations above, not part of the total
+    pub writes: usize,
+}
+impl Statistics {
+    pub fn record(&mut self, operation: Operation) {
+        self.writes += match operation {
+            Operation::Set(_) | Operation::CopyFromBuffer(_, _, _) => 1,
+            Operation::Swap(a, b) if a != b => 2,
+            Operation::Reverse(begin, end) => (end - begin) / 2 * 2,
+            Operation::Rotate(begin, end, k) if k > 0 && begin + k < end => end - begin,
+            Operation::BlockSwap(a, b, len) if a != b => 2 * len,
+            _ => 0,
+        };
+        match operation {
+            Operation::Get(_) => self.gets += 1,
+            Operation::Set(_) => self.sets += 1,
+            Operation::Compare(_, _) => self.compares += 1,
+            Operation::Swap(_, _) => self.swaps += 1,
+            Operation::Alloc(_, _) => self.allocs += 1,
+            Operation::Free(_) => self.frees += 1,
+            Operation::BufferGet(_, _) => self.buffer_gets += 1,
+            Operation::BufferSet(_, _) => self.buffer_sets += 1,
+            Operation::CopyToBuffer(_, _, _) => self.copies_to_buffer += 1,
+            Operation::CopyFromBuffer(_, _, _) => self.copies_from_buffer += 1,
+            // Highlights do not access the list
+            Operation::Highlight(_, _, _) => {}
+            Operation::Key(_) => self.keys += 1,
+            Operation::AllocHistogram(_, _) => self.allocs += 1,
+            Operation::FreeHistogram(_) => self.frees += 1,
+            Operation::HistogramGet(_, _) => self.histogram_gets += 1,
+            Operation::HistogramSet(_, _, _) => self.histogram_sets += 1,
+            Operation::Reverse(_, _) => self.reverses += 1,
+            Operation::Rotate(_, _, _) => self.rotates += 1,
+            Operation::BlockSwap(_, _, _) => self.block_swaps += 1,
+            // Parallel only groups the operations after it
+            Operation::Parallel(_) => {}
+        }
+    }
+    pub fn total(&self) -> usize {
+        self.gets
+            + self.sets
+            + self.compares
+            + self.swaps
+            + self.allocs
+            + self.frees
+            + self.buffer_gets
+            + self.buffer_sets
+            + self.copies_to_buffer
+            + self.copies_from_buffer
+            + self.keys
+            + self.histogram_gets
+            + self.histogram_sets
+            + self.reverses
+            + self.rotates
+            + self.block_swaps
+    }
+}
+impl std::fmt::Display for Statistics {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
+        write!(
+            f,
+            "Get: {}, Set: {}, Compare: {}, Swap: {}",
+            self.gets, self.sets, self.compares, self.swaps
+        )?;
+        if self.keys > 0 {
+            write!(f, ", Key: {}", self.keys)?;
+        }
+        if self.allocs > 0 {
+            write!(
+                f,
+                ", Alloc: {}, Buffer Get: {}, Buffer Set: {}, Copy to buffer: {}, Copy from buffer: {}",
+                self.allocs,
+                self.buffer_gets,
+                self.buffer_sets,
+                self.copies_to_buffer,
+                self.copies_from_buffer
+            )?;
+        }
+        if self.histogram_gets + self.histogram_sets > 0 {
+            write!(
+                f,
+                ", Histogram Get: {}, Histogram Set: {}",
+                self.histogram_gets, self.histogram_sets
+            )?;
+        }
+        if self.reverses + self.rotates + self.block_swaps > 0 {
+            write!(
+                f,
+                ", Reverse: {}, Rotate: {}, Block swap: {}",
+                self.reverses, self.rotates, self.block_swaps
+            )?;
+        }
+        write!(f, ", Total: {}", self.total())
+    }
+}
+
+/// Wraps another `List` and counts every operation performed through it,
+/// along with the simulated time they take under a cost model
+pub struct CountingList<'a, T>
+where
+    T: Copy + Ord,
+{
+    list: &'a mut dyn List<T>,
+    statistics: Cell<Statistics>,
+    cost_model: CostModel,
+    simulated_time: Cell<f64>,
+}
+impl<'a, T> CountingList<'a, T>
+where
+    T: Copy + Ord,
+{
+    pub fn new(list: &'a mut dyn List<T>) -> CountingList<'a, T> {
+        CountingList::with_cost_model(list, CostModel::default())
+    }
+    pub fn with_cost_model(
+        list: &'a mut dyn List<T>,
+        cost_model: CostModel,
+    ) -> CountingList<'a, T> {
+        CountingList {
+            list,
+            statistics: Cell::new(Statistics::default()),
+            cost_model,
+            simulated_time: Cell::new(0.0),
+        }
+    }
+    pub fn statistics(&self) -> Statistics {
+        self.statistics.get()
+    }
+    /// Total cost of the operations performed so far
+    pub fn simulated_time(&self) -> f64 {
+        self.simulated_time.get()
+    }
+    fn record(&self, operation: Operation) {
+        let mut statistics = self.statistics.get();
+        statistics.record(operation);
+        self.statistics.set(statistics);
+        self.simulated_time
+            .set(self.simulated_time.get() + self.cost_model.cost(operation));
+    }
+}
+impl<'a, T> List<T> for CountingList<'a, T>
+where
+    T: Copy + Ord,
+{
+    fn as_slice(&self) -> &[T] {
+        self.list.as_slice()
+    }
+    fn as_mut_slice(&mut self) -> &mut [T] {
+        self.list.as_mut_slice()
+    }
+    fn get(&self, index: usize) -> T {
+        self.record(Operation::Get(index));
+        self.list.get(index)
+    }
+    fn set(&mut self, index: usize, value: T) {
+        self.record(Operation::Set(index));
+        self.list.set(index, value);
+    }
+    fn compare(&self, a: usize, b: usize) -> std::cmp::Ordering {
+        self.record(Operation::Compare(a, b));
+        self.list.compare(a, b)
+    }
+    fn swap(&mut self, a: usize, b: usize) {
+        self.record(Operation::Swap(a, b));
+        self.list.swap(a, b);
+    }
+    fn reverse_range(&mut self, begin: usize, end: usize) {
+        self.record(Operation::Reverse(begin, end));
+        self.list.reverse_range(begin, end);
+    }
+    fn rotate_range(&mut self, begin: usize, end: usize, k: usize) {
+        self.record(Operation::Rotate(begin, end, k));
+        self.list.rotate_range(begin, end, k);
+    }
+    fn swap_blocks(&mut self, a: usize, b: usize, len: usize) {
+        self.record(Operation::BlockSwap(a, b, len));
+        self.list.swap_blocks(a, b, len);
+    }
+    fn alloc_buffer(&mut self, name: &'static str, len: usize) -> Buffer<T> {
+        self.record(Operation::Alloc(name, len));
+        self.list.alloc_buffer(name, len)
+    }
+    fn free_buffer(&mut self, buffer: Buffer<T>) {
+        self.record(Operation::Free(buffer.name));
+        self.list.free_buffer(buffer);
+    }
+    fn buffer_get(&self, buffer: &Buffer<T>, index: usize) -> T {
+        self.record(Operation::BufferGet(buffer.name, index));
+        self.list.buffer_get(buffer, index)
+    }
+    fn buffer_set(&mut self, buffer: &mut Buffer<T>, index: usize, value: T) {
+        self.record(Operation::BufferSet(buffer.name, index));
+        self.list.buffer_set(buffer, index, value);
+    }
+    fn copy_to_buffer(&mut self, index: usize, buffer: &mut Buffer<T>, buffer_index: usize) {
+        self.record(Operation::CopyToBuffer(index, buffer.name, buffer_index));
+        self.list.copy_to_buffer(index, buffer, buffer_index);
+    }
+    fn copy_from_buffer(&mut self, buffer: &Buffer<T>, buffer_index: usize, index: usize) {
+        self.record(Operation::CopyFromBuffer(buffer.name, buffer_index, index));
+        self.list.copy_from_buffer(buffer, buffer_index, index);
+    }
+    fn highlight(&self, begin: usize, end: usize, highlight: Highlight) {
+        self.list.highlight(begin, end, highlight);
+    }
+    fn parallel(&self, count: usize) {
+        self.list.parallel(count);
+    }
+    fn key(&self, index: usize) -> u64
+    where
+        T: RadixKey,
+    {
+        self.record(Operation::Key(index));
+        self.list.key(index)
+    }
+    fn alloc_histogram(&mut self, name: &'static str, len: usize) -> Histogram {
+        self.record(Operation::AllocHistogram(name, len));
+        self.list.alloc_histogram(name, len)
+    }
+    fn free_histogram(&mut self, histogram: Histogram) {
+        self.record(Operation::FreeHistogram(histogram.name));
+        self.list.free_histogram(histogram);
+    }
+    fn histogram_get(&self, histogram: &Histogram, index: usize) -> usize {
+        self.record(Operation::HistogramGet(histogram.name, index));
+        self.list.histogram_get(histogram, index)
+    }
+    fn histogram_set(&mut self, histogram: &mut Histogram, index: usize, count: usize) {
+        self.record(Operation::HistogramSet(histogram.name, index, count));
+        self.list.histogram_set(histogram, index, count);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{bubblesort, mergesort};
+    #[test]
+    fn test_counting_list() {
+        let mut test_slice = vec![3, 2, 1];
+        let mut list = CountingList::new(&mut test_slice);
+        bubblesort(&mut list);
+        let statistics = list.statistics();
+        assert_eq!(statistics.compares, 3);
+        assert_eq!(statistics.swaps, 3);
+        assert_eq!(statistics.total(), 6);
+        assert_eq!(test_slice, vec![1, 2, 3]);
+    }
+    #[test]
+    fn test_counting_list_buffers() {
+        let mut test_slice = vec![2, 1];
+        let mut list = CountingList::new(&mut test_slice);
+        mergesort(&mut list);
+        let statistics = list.statistics();
+        assert_eq!(statistics.allocs, 1);
+        assert_eq!(statistics.frees, 1);
+        assert_eq!(statistics.copies_to_buffer, 1);
+        assert_eq!(statistics.copies_from_buffer, 1);
+        assert_eq!(statistics.gets, 1);
+        assert_eq!(statistics.sets, 1);
+        assert_eq!(test_slice, vec![1, 2]);
+    }
+    #[test]
+    fn test_counting_list_cost() {
+        let mut test_slice = vec![3, 2, 1];
+        let mut list = CountingList::with_cost_model(&mut test_slice, CostModel::flash());
+        bubblesort(&mut list);
+        // 3 compares reading 1 each and 3 swaps writing 2 elements at 10 each
+        assert_eq!(list.simulated_time(), 63.0);
+        let mut test_slice = vec![1, 4, 123, 7, 8, 4, 8, 8, 2, 4, 57, 8, 324, 213];
+        let mut list = CountingList::new(&mut test_slice);
+        mergesort(&mut list);
+        assert_eq!(list.simulated_time(), list.statistics().total() as f64);
+    }
+    #[test]
+    fn test_counting_list_blocks() {
+        let mut test_slice = vec![1, 2, 3, 4, 5, 6, 7];
+        let mut list = CountingList::new(&mut test_slice);
+        list.reverse_range(0, 3);
+        list.rotate_range(1, 6, 2);
+        list.swap_blocks(0, 5, 2);
+        let statistics = list.statistics();
+        assert_eq!(statistics.reverses, 1);
+        assert_eq!(statistics.rotates, 1);
+        assert_eq!(statistics.block_swaps, 1);
+        assert_eq!(statistics.swaps, 0);
+        assert_eq!(statistics.total(), 3);
+        assert_eq!(statistics.writes, 2 + 5 + 4);
+        assert_eq!(test_slice, vec![1, 7, 5, 6, 2, 3, 4]);
+        // The default rotation is built from swaps
+        for k in 0..=7 {
+            let mut test_slice = vec![1, 2, 3, 4, 5, 6, 7];
+            let mut expected = test_slice.clone();
+            expected.rotate_left(k);
+            test_slice.rotate_range(0, 7, k);
+            assert_eq!(test_slice, expected);
+        }
+    }
+}
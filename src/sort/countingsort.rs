@@ -0,0 +1,96 @@
+use super::{List, RadixKey};
+
+/// Largest span of keys supported by counting sort, which needs a count for each of them
+pub const COUNTINGSORT_MAX_RANGE: u64 = 1 << 16;
+
+/// Smallest and largest keys of a non-empty list
+pub(crate) fn key_bounds<T>(list: &dyn List<T>) -> (u64, u64)
+where
+    T: RadixKey,
+{
+    let mut min = list.key(0);
+    let mut max = min;
+    for index in 1..list.len() {
+        let key = list.key(index);
+        min = min.min(key);
+        max = max.max(key);
+    }
+    (min, max)
+}
+
+/// Count the elements with each key, turn the counts into positions and
+/// scatter the elements to their positions through a buffer
+pub fn countingsort<T>(list: &mut dyn List<T>)
+where
+    T: RadixKey + std::fmt::Display,
+{
+    let length = list.len();
+    if length < 2 {
+        return;
+    }
+    let (min, max) = key_bounds(list);
+    assert!(
+        max - min < COUNTINGSORT_MAX_RANGE,
+        "Counting sort supports keys spanning at most {} values",
+        COUNTINGSORT_MAX_RANGE
+    );
+    let mut counts = list.alloc_histogram("counts", (max - min) as usize + 1);
+    for index in 0..length {
+        let bucket = (list.key(index) - min) as usize;
+        let count = list.histogram_get(&counts, bucket);
+        list.histogram_set(&mut counts, bucket, count + 1);
+    }
+    // Each count becomes the position of the first element with that key
+    let mut position = 0;
+    for bucket in 0..counts.len() {
+        let count = list.histogram_get(&counts, bucket);
+        list.histogram_set(&mut counts, bucket, position);
+        position += count;
+    }
+    // Scatter in order, which keeps the sort stable
+    let mut output = list.alloc_buffer("output", length);
+    for index in 0..length {
+        let bucket = (list.key(index) - min) as usize;
+        let position = list.histogram_get(&counts, bucket);
+        list.copy_to_buffer(index, &mut output, position);
+        list.histogram_set(&mut counts, bucket, position + 1);
+    }
+    for index in 0..length {
+        list.copy_from_buffer(&output, index, index);
+    }
+    list.free_buffer(output);
+    list.free_histogram(counts);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::CountingList;
+    #[test]
+    fn test_countingsort() {
+        let mut test_slice = vec![1, 4, 123, 7, 8, 4, 8, 8, 2, 4, 57, 8, 324, 213];
+        let mut test_slice2 = test_slice.clone();
+        assert_eq!(test_slice, test_slice2);
+        test_slice.sort_unstable();
+        countingsort(&mut test_slice2);
+        assert_eq!(test_slice, test_slice2);
+        println!("{:?}", test_slice2);
+    }
+    #[test]
+    fn test_countingsort_histogram() {
+        let mut test_slice: Vec<u32> = vec![1_000_003, 1_000_001, 1_000_002, 1_000_001];
+        let mut list = CountingList::new(&mut test_slice);
+        countingsort(&mut list);
+        let statistics = list.statistics();
+        assert_eq!(statistics.compares, 0);
+        // Counting, prefix sums over 3 keys and scattering
+        assert_eq!(statistics.histogram_sets, 4 + 3 + 4);
+        assert_eq!(test_slice, vec![1_000_001, 1_000_001, 1_000_002, 1_000_003]);
+    }
+    #[test]
+    #[should_panic(expected = "at most")]
+    fn test_countingsort_range() {
+        let mut test_slice: Vec<u32> = vec![0, u32::MAX];
+        countingsort(&mut test_slice);
+    }
+}
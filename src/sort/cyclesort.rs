@@ -0,0 +1,86 @@
+use super::List;
+
+pub fn cyclesort<T>(list: &mut dyn List<T>)
+where
+    T: Copy + Ord + std::fmt::Display,
+{
+    cyclesort_writes(list);
+}
+
+/// Cycle sort, returning the number of writes to the list. Every element is written
+/// at most once, directly to its final position, and elements already in their final
+/// position are never written, which is the minimum possible number of writes.
+pub fn cyclesort_writes<T>(list: &mut dyn List<T>) -> usize
+where
+    T: Copy + Ord + std::fmt::Display,
+{
+    let length = list.len();
+    let mut writes = 0;
+    for cycle_start in 0..length.saturating_sub(1) {
+        let mut item = list.get(cycle_start);
+        // The final position of an element is after every smaller one
+        let position = |list: &dyn List<T>, item: T| {
+            let mut position = cycle_start;
+            for index in cycle_start + 1..length {
+                if list.get(index) < item {
+                    position += 1;
+                }
+            }
+            // Go past equal elements, which are already in place
+            while position != cycle_start && list.get(position) == item {
+                position += 1;
+            }
+            position
+        };
+        let mut target = position(list, item);
+        if target == cycle_start {
+            continue;
+        }
+        // Put the item in place and carry on with the element it displaced,
+        // until the cycle comes back to its start
+        while target != cycle_start {
+            let displaced = list.get(target);
+            list.set(target, item);
+            writes += 1;
+            item = displaced;
+            target = position(list, item);
+        }
+        list.set(cycle_start, item);
+        writes += 1;
+    }
+    writes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::CountingList;
+    #[test]
+    fn test_cyclesort() {
+        let mut test_slice = vec![1, 4, 123, 7, 8, 4, 8, 8, 2, 4, 57, 8, 324, 213];
+        let mut test_slice2 = test_slice.clone();
+        assert_eq!(test_slice, test_slice2);
+        test_slice.sort_unstable();
+        cyclesort(&mut test_slice2);
+        assert_eq!(test_slice, test_slice2);
+        println!("{:?}", test_slice2);
+    }
+    #[test]
+    fn test_cyclesort_writes() {
+        let test_slice = vec![1, 4, 123, 7, 8, 4, 8, 8, 2, 4, 57, 8, 324, 213];
+        let mut sorted = test_slice.clone();
+        sorted.sort_unstable();
+        // Only positions holding the wrong value need to be written
+        let misplaced = (0..test_slice.len())
+            .filter(|&i| test_slice[i] != sorted[i])
+            .count();
+        let mut test_slice2 = test_slice.clone();
+        let mut list = CountingList::new(&mut test_slice2);
+        let writes = cyclesort_writes(&mut list);
+        assert_eq!(writes, misplaced);
+        assert_eq!(list.statistics().sets, misplaced);
+        assert_eq!(list.statistics().writes, misplaced);
+        assert_eq!(list.statistics().swaps, 0);
+        assert_eq!(test_slice2, sorted);
+    }
+}
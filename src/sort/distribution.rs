@@ -0,0 +1,308 @@
+use super::fisher_yates_shuffle;
+use std::ops::RangeInclusive;
+
+use rand::RngCore;
+
+/// Random swaps made by the default nearly sorted distribution
+const DEFAULT_SWAPS: usize = 10;
+/// Distinct values used by the default few unique distribution
+const DEFAULT_UNIQUE: usize = 5;
+/// Ascending runs in the default sawtooth distribution
+const DEFAULT_TEETH: usize = 4;
+
+/// Shape of generated input data
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum Distribution {
+    Uniform,
+    Sorted,
+    Reversed,
+    /// Sorted, then this many random pairs of elements swapped
+    NearlySorted(usize),
+    /// Random choices among this many evenly spaced values
+    FewUnique(usize),
+    /// This many ascending runs one after another
+    Sawtooth(usize),
+    /// Ascending up to the middle, then descending
+    OrganPipe,
+    /// Sorted, except for the largest element moved to the front
+    PushedFront,
+    /// Sorted, except for the smallest element moved to the back
+    PushedBack,
+    /// Normally distributed around the middle of the range, with a standard deviation of 1/6 of it
+    Gaussian,
+    /// Evenly spaced values in random order, which is a permutation of `1..=N` for that range
+    Permutation,
+}
+impl Distribution {
+    pub const NAMES: &'static [&'static str] = &[
+        "uniform",
+        "sorted",
+        "reversed",
+        "nearlysorted",
+        "nearlysorted:SWAPS",
+        "fewunique",
+        "fewunique:COUNT",
+        "sawtooth",
+        "sawtooth:TEETH",
+        "organpipe",
+        "pushedfront",
+        "pushedback",
+        "gaussian",
+        "permutation",
+    ];
+    /// Generate `length` values within `range`
+    pub fn generate(
+        &self,
+        length: usize,
+        range: RangeInclusive<u32>,
+        rng: &mut dyn RngCore,
+    ) -> Vec<u32> {
+        let (min, max) = (*range.start(), *range.end());
+        let sorted = || (0..length).map(|i| spread(min, max, i, length)).collect();
+        match *self {
+            Distribution::Uniform => {
+                let size = u64::from(max - min) + 1;
+                (0..length)
+                    .map(|_| min + (rng.next_u64() % size) as u32)
+                    .collect()
+            }
+            Distribution::Sorted => sorted(),
+            Distribution::Reversed => (0..length)
+                .rev()
+                .map(|i| spread(min, max, i, length))
+                .collect(),
+            Distribution::NearlySorted(swaps) => {
+                let mut data: Vec<u32> = sorted();
+                if length > 1 {
+                    for _ in 0..swaps {
+                        let a = rng.next_u64() as usize % length;
+                        let b = rng.next_u64() as usize % length;
+                        data.swap(a, b);
+                    }
+                }
+                data
+            }
+            Distribution::FewUnique(count) => {
+                let count = std::cmp::max(count, 1);
+                (0..length)
+                    .map(|_| spread(min, max, rng.next_u64() as usize % count, count))
+                    .collect()
+            }
+            Distribution::Sawtooth(teeth) => {
+                let tooth = std::cmp::max(length.div_ceil(std::cmp::max(teeth, 1)), 1);
+                (0..length)
+                    .map(|i| spread(min, max, i % tooth, tooth))
+                    .collect()
+            }
+            Distribution::OrganPipe => {
+                let half = length.div_ceil(2);
+                (0..length)
+                    .map(|i| spread(min, max, std::cmp::min(i, length - 1 - i), half))
+                    .collect()
+            }
+            Distribution::PushedFront => {
+                let mut data: Vec<u32> = sorted();
+                data.rotate_right(std::cmp::min(length, 1));
+                data
+            }
+            Distribution::PushedBack => {
+                let mut data: Vec<u32> = sorted();
+                data.rotate_left(std::cmp::min(length, 1));
+                data
+            }
+            Distribution::Gaussian => {
+                let mean = (f64::from(min) + f64::from(max)) / 2.0;
+                let deviation = (f64::from(max) - f64::from(min)) / 6.0;
+                (0..length)
+                    .map(|_| {
+                        let value = mean + deviation * standard_normal(rng);
+                        value.round().max(f64::from(min)).min(f64::from(max)) as u32
+                    })
+                    .collect()
+            }
+            Distribution::Permutation => {
+                let mut data: Vec<u32> = sorted();
+                fisher_yates_shuffle(&mut data, rng);
+                data
+            }
+        }
+    }
+}
+impl std::fmt::Display for Distribution {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
+        match self {
+            Distribution::Uniform => write!(f, "uniform"),
+            Distribution::Sorted => write!(f, "sorted"),
+            Distribution::Reversed => write!(f, "reversed"),
+            Distribution::NearlySorted(swaps) => write!(f, "nearlysorted:{}", swaps),
+            Distribution::FewUnique(count) => write!(f, "fewunique:{}", count),
+            Distribution::Sawtooth(teeth) => write!(f, "sawtooth:{}", teeth),
+            Distribution::OrganPipe => write!(f, "organpipe"),
+            Distribution::PushedFront => write!(f, "pushedfront"),
+            Distribution::PushedBack => write!(f, "pushedback"),
+            Distribution::Gaussian => write!(f, "gaussian"),
+            Distribution::Permutation => write!(f, "permutation"),
+        }
+    }
+}
+impl std::str::FromStr for Distribution {
+    type Err = String;
+    fn from_str(name: &str) -> Result<Distribution, String> {
+        let (base, argument) = match name.find(':') {
+            Some(position) => (&name[..position], Some(&name[position + 1..])),
+            None => (name, None),
+        };
+        let parameter = |default| match argument {
+            Some(text) => text
+                .parse::<usize>()
+                .map_err(|_| format!("Cannot parse \"{}\" as unsigned integer", text)),
+            None => Ok(default),
+        };
+        match base {
+            "uniform" if argument.is_none() => Ok(Distribution::Uniform),
+            "sorted" if argument.is_none() => Ok(Distribution::Sorted),
+            "reversed" if argument.is_none() => Ok(Distribution::Reversed),
+            "nearlysorted" => parameter(DEFAULT_SWAPS).map(Distribution::NearlySorted),
+            "fewunique" => parameter(DEFAULT_UNIQUE).map(Distribution::FewUnique),
+            "sawtooth" => parameter(DEFAULT_TEETH).map(Distribution::Sawtooth),
+            "organpipe" if argument.is_none() => Ok(Distribution::OrganPipe),
+            "pushedfront" if argument.is_none() => Ok(Distribution::PushedFront),
+            "pushedback" if argument.is_none() => Ok(Distribution::PushedBack),
+            "gaussian" if argument.is_none() => Ok(Distribution::Gaussian),
+            "permutation" if argument.is_none() => Ok(Distribution::Permutation),
+            _ => Err(format!("Distribution \"{}\" not found", name)),
+        }
+    }
+}
+
+/// Parse a value range written as `MIN:MAX`, both inclusive
+pub fn parse_range(text: &str) -> Result<RangeInclusive<u32>, String> {
+    let error = || format!("Cannot parse \"{}\" as a MIN:MAX value range", text);
+    let position = text.find(':').ok_or_else(error)?;
+    let min = text[..position].parse::<u32>().map_err(|_| error())?;
+    let max = text[position + 1..].parse::<u32>().map_err(|_| error())?;
+    if min > max {
+        return Err(format!("Value range \"{}\" is empty", text));
+    }
+    Ok(min..=max)
+}
+
+/// Value `index` of `count` evenly spaced values from `min` to `max`
+fn spread(min: u32, max: u32, index: usize, count: usize) -> u32 {
+    if count < 2 {
+        return min;
+    }
+    min + (u64::from(max - min) * index as u64 / (count - 1) as u64) as u32
+}
+
+/// Standard normal sample by the Box-Muller transform
+fn standard_normal(rng: &mut dyn RngCore) -> f64 {
+    // Uniform samples in (0, 1], so that the logarithm is finite
+    let mut uniform = || 1.0 - (rng.next_u64() >> 11) as f64 / (1u64 << 53) as f64;
+    let (u1, u2) = (uniform(), uniform());
+    (-2.0 * u1.ln()).sqrt() * (2.0 * std::f64::consts::PI * u2).cos()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+    fn is_sorted(data: &[u32]) -> bool {
+        data.windows(2).all(|pair| pair[0] <= pair[1])
+    }
+    #[test]
+    fn test_distributions() {
+        let mut rng = StdRng::seed_from_u64(42);
+        for name in Distribution::NAMES
+            .iter()
+            .filter(|name| !name.contains(':'))
+        {
+            let distribution = name.parse::<Distribution>().unwrap();
+            for &length in [0, 1, 2, 7, 100].iter() {
+                let data = distribution.generate(length, 10..=50, &mut rng);
+                assert_eq!(data.len(), length);
+                assert!(data.iter().all(|value| (10..=50).contains(value)));
+            }
+        }
+    }
+    #[test]
+    fn test_distribution_shapes() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let mut generate =
+            |distribution: Distribution| distribution.generate(100, 1..=100, &mut rng);
+        let sorted: Vec<u32> = (1..=100).collect();
+        assert_eq!(generate(Distribution::Sorted), sorted);
+        assert_eq!(
+            generate(Distribution::Reversed),
+            (1..=100).rev().collect::<Vec<_>>()
+        );
+        let nearly_sorted = generate(Distribution::NearlySorted(3));
+        assert!(
+            nearly_sorted
+                .iter()
+                .zip(sorted.iter())
+                .filter(|(a, b)| a != b)
+                .count()
+                <= 6
+        );
+        let mut few_unique = generate(Distribution::FewUnique(4));
+        few_unique.sort_unstable();
+        few_unique.dedup();
+        assert!(few_unique.len() <= 4);
+        let sawtooth = generate(Distribution::Sawtooth(4));
+        assert!(sawtooth.chunks(25).all(is_sorted));
+        assert!(!is_sorted(&sawtooth));
+        let organ_pipe = generate(Distribution::OrganPipe);
+        assert!(is_sorted(&organ_pipe[..50]));
+        assert!(organ_pipe[50..].windows(2).all(|pair| pair[0] >= pair[1]));
+        let pushed_front = generate(Distribution::PushedFront);
+        assert_eq!(pushed_front[0], 100);
+        assert_eq!(pushed_front[1..], sorted[..99]);
+        let pushed_back = generate(Distribution::PushedBack);
+        assert_eq!(pushed_back[99], 1);
+        assert_eq!(pushed_back[..99], sorted[1..]);
+        let mut permutation = generate(Distribution::Permutation);
+        assert_ne!(permutation, sorted);
+        permutation.sort_unstable();
+        assert_eq!(permutation, sorted);
+        let gaussian = Distribution::Gaussian.generate(10000, 0..=600, &mut rng);
+        let mean = gaussian.iter().map(|&value| f64::from(value)).sum::<f64>() / 10000.0;
+        assert!((mean - 300.0).abs() < 5.0);
+        // About 68% of the values are within one standard deviation
+        let within = gaussian
+            .iter()
+            .filter(|&&value| (200..=400).contains(&value))
+            .count();
+        assert!((6500..7100).contains(&within));
+    }
+    #[test]
+    fn test_distribution_names() {
+        for name in [
+            "uniform",
+            "nearlysorted:3",
+            "fewunique:2",
+            "sawtooth:5",
+            "permutation",
+        ]
+        .iter()
+        {
+            assert_eq!(&name.parse::<Distribution>().unwrap().to_string(), name);
+        }
+        assert_eq!(
+            "sawtooth".parse::<Distribution>(),
+            Ok(Distribution::Sawtooth(4))
+        );
+        assert!("sorted:3".parse::<Distribution>().is_err());
+        assert!("fewunique:x".parse::<Distribution>().is_err());
+        assert!("bell".parse::<Distribution>().is_err());
+    }
+    #[test]
+    fn test_parse_range() {
+        assert_eq!(parse_range("1:100"), Ok(1..=100));
+        assert_eq!(parse_range("7:7"), Ok(7..=7));
+        assert!(parse_range("100:1").is_err());
+        assert!(parse_range("100").is_err());
+        assert!(parse_range("a:b").is_err());
+    }
+}
@@ -0,0 +1,108 @@
+use super::{Highlight, List};
+use std::cmp::Ordering;
+
+/// Yaroslavskiy's partition around the elements at `begin` and `end`.
+/// Returns the final positions of both pivots.
+fn partition<T>(list: &mut dyn List<T>, begin: usize, end: usize) -> (usize, usize)
+where
+    T: Copy + Ord + std::fmt::Display,
+{
+    // Invariant: [begin + 1, lower) < p, [lower, index) in [p, q], (upper, end - 1] > q
+    let mut lower = begin + 1;
+    let mut upper = end - 1;
+    let mut index = lower;
+    while index <= upper {
+        if list.compare(index, begin) == Ordering::Less {
+            list.swap(index, lower);
+            lower += 1;
+        } else if list.compare(index, end) == Ordering::Greater {
+            while index < upper && list.compare(upper, end) == Ordering::Greater {
+                upper -= 1;
+            }
+            list.swap(index, upper);
+            upper -= 1;
+            if list.compare(index, begin) == Ordering::Less {
+                list.swap(index, lower);
+                lower += 1;
+            }
+        }
+        index += 1;
+    }
+    // Move pivots to their final positions
+    lower -= 1;
+    upper += 1;
+    list.swap(begin, lower);
+    list.swap(end, upper);
+    (lower, upper)
+}
+
+fn real_dualpivotquicksort<T>(list: &mut dyn List<T>, begin: usize, end: usize)
+where
+    T: Copy + Ord + std::fmt::Display,
+{
+    if begin < end {
+        // Use the tertiles as pivots, which performs well on already sorted input
+        let third = (end - begin + 1) / 3;
+        list.swap(begin, begin + third);
+        list.swap(end, end - third);
+        if list.compare(begin, end) == Ordering::Greater {
+            list.swap(begin, end);
+        }
+        list.highlight(begin, begin + 1, Highlight::Pivot);
+        list.highlight(end, end + 1, Highlight::Pivot);
+        // Partition elements
+        let (lower, upper) = partition(list, begin, end);
+        list.highlight(begin, end + 1, Highlight::None);
+        if list.compare(lower, upper) == Ordering::Equal {
+            // Both pivots are equal, so every element between them is equal too
+            list.highlight(lower, upper + 1, Highlight::Equal);
+        } else {
+            list.highlight(lower, lower + 1, Highlight::Pivot);
+            list.highlight(upper, upper + 1, Highlight::Pivot);
+            real_dualpivotquicksort(list, lower + 1, upper - 1);
+        }
+        if lower > begin {
+            real_dualpivotquicksort(list, begin, lower - 1);
+        }
+        real_dualpivotquicksort(list, upper + 1, end);
+    }
+}
+
+pub fn dualpivotquicksort<T>(list: &mut dyn List<T>)
+where
+    T: Copy + Ord + std::fmt::Display,
+{
+    let length = list.len();
+    if length > 1 {
+        real_dualpivotquicksort(list, 0, length - 1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn test_dualpivotquicksort() {
+        let mut test_slice = vec![1, 4, 123, 7, 8, 4, 8, 8, 2, 4, 57, 8, 324, 213];
+        let mut test_slice2 = test_slice.clone();
+        assert_eq!(test_slice, test_slice2);
+        test_slice.sort_unstable();
+        dualpivotquicksort(&mut test_slice2);
+        assert_eq!(test_slice, test_slice2);
+        println!("{:?}", test_slice2);
+    }
+    #[test]
+    fn test_dualpivotquicksort_duplicates() {
+        let mut test_slice = vec![3, 1, 3, 3, 2, 1, 1, 3, 2, 2, 3, 1, 1, 3, 3, 2, 1, 3, 3, 3];
+        let mut test_slice2 = test_slice.clone();
+        test_slice.sort_unstable();
+        dualpivotquicksort(&mut test_slice2);
+        assert_eq!(test_slice, test_slice2);
+        let mut all_equal = vec![5; 32];
+        dualpivotquicksort(&mut all_equal);
+        assert_eq!(all_equal, vec![5; 32]);
+        let mut sorted: Vec<u32> = (0..100).collect();
+        dualpivotquicksort(&mut sorted);
+        assert_eq!(sorted, (0..100).collect::<Vec<_>>());
+    }
+}
@@ -0,0 +1,79 @@
+use super::countingsort::key_bounds;
+use super::{insertionsort, List, RadixKey};
+
+/// Classify the elements by interpolating their keys between the smallest and the largest,
+/// permute them in place into their classes and finish with insertion sort (Neubert)
+pub fn flashsort<T>(list: &mut dyn List<T>)
+where
+    T: RadixKey + std::fmt::Display,
+{
+    let length = list.len();
+    if length < 2 {
+        return;
+    }
+    let (min, max) = key_bounds(list);
+    if min == max {
+        return;
+    }
+    // About 0.43n classes, with the largest keys alone in the last one
+    let classes = (length * 43 / 100).max(2);
+    let class_of =
+        |key: u64| (u128::from(key - min) * (classes - 1) as u128 / u128::from(max - min)) as usize;
+    let mut heads = list.alloc_histogram("classes", classes);
+    for index in 0..length {
+        let class = class_of(list.key(index));
+        let count = list.histogram_get(&heads, class);
+        list.histogram_set(&mut heads, class, count + 1);
+    }
+    let mut ends = Vec::with_capacity(classes);
+    let mut position = 0;
+    for class in 0..classes {
+        let count = list.histogram_get(&heads, class);
+        list.histogram_set(&mut heads, class, position);
+        position += count;
+        ends.push(position);
+    }
+    // Swap the element at the head of each class into the class it belongs to,
+    // until every element up to the end of the class belongs there
+    for (class, &end) in ends.iter().enumerate() {
+        loop {
+            let head = list.histogram_get(&heads, class);
+            if head == end {
+                break;
+            }
+            let target = class_of(list.key(head));
+            let target_head = list.histogram_get(&heads, target);
+            if target != class {
+                list.swap(head, target_head);
+            }
+            list.histogram_set(&mut heads, target, target_head + 1);
+        }
+    }
+    list.free_histogram(heads);
+    insertionsort(list);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::CountingList;
+    #[test]
+    fn test_flashsort() {
+        let mut test_slice = vec![1, 4, 123, 7, 8, 4, 8, 8, 2, 4, 57, 8, 324, 213];
+        let mut test_slice2 = test_slice.clone();
+        assert_eq!(test_slice, test_slice2);
+        test_slice.sort_unstable();
+        flashsort(&mut test_slice2);
+        assert_eq!(test_slice, test_slice2);
+        println!("{:?}", test_slice2);
+    }
+    #[test]
+    fn test_flashsort_uniform() {
+        // Uniformly distributed keys leave little work for the insertion sort
+        let mut test_slice: Vec<u32> = (0..1000).map(|i| (i * 7919) % 1000 * 4_000_000).collect();
+        let mut list = CountingList::new(&mut test_slice);
+        flashsort(&mut list);
+        assert!(list.statistics().compares < 3000);
+        assert!(test_slice.windows(2).all(|w| w[0] <= w[1]));
+    }
+}
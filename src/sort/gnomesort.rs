@@ -0,0 +1,33 @@
+use super::List;
+use std::cmp::Ordering;
+
+/// Step forwards while the elements are in order, otherwise swap them and step back
+pub fn gnomesort<T>(list: &mut dyn List<T>)
+where
+    T: Copy + Ord + std::fmt::Display,
+{
+    let mut index = 1;
+    while index < list.len() {
+        if index == 0 || list.compare(index - 1, index) != Ordering::Greater {
+            index += 1;
+        } else {
+            list.swap(index - 1, index);
+            index -= 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn test_gnomesort() {
+        let mut test_slice = vec![1, 4, 123, 7, 8, 4, 8, 8, 2, 4, 57, 8, 324, 213];
+        let mut test_slice2 = test_slice.clone();
+        assert_eq!(test_slice, test_slice2);
+        test_slice.sort_unstable();
+        gnomesort(&mut test_slice2);
+        assert_eq!(test_slice, test_slice2);
+        println!("{:?}", test_slice2);
+    }
+}
@@ -1 +1,75 @@
-
+use super::List;
+use std::cmp::Ordering;
+
+fn sift_down<T>(list: &mut dyn List<T>, begin: usize, root: usize, end: usize)
+where
+    T: Copy + Ord + std::fmt::Display,
+{
+    // Heap indices are relative to begin
+    let mut root = root;
+    loop {
+        let left = 2 * root + 1;
+        if begin + left >= end {
+            break;
+        }
+        // Select the largest child
+        let right = left + 1;
+        let child =
+            if begin + right < end && list.compare(begin + left, begin + right) == Ordering::Less {
+                right
+            } else {
+                left
+            };
+        // Stop once the root is not smaller than its largest child
+        if list.compare(begin + root, begin + child) != Ordering::Less {
+            break;
+        }
+        list.swap(begin + root, begin + child);
+        root = child;
+    }
+}
+
+fn heapify<T>(list: &mut dyn List<T>, begin: usize, end: usize)
+where
+    T: Copy + Ord + std::fmt::Display,
+{
+    for root in (0..(end - begin) / 2).rev() {
+        sift_down(list, begin, root, end);
+    }
+}
+
+pub fn heapsort<T>(list: &mut dyn List<T>)
+where
+    T: Copy + Ord + std::fmt::Display,
+{
+    let length = list.len();
+    heapsort_range(list, 0, length);
+}
+
+/// Heapsort the elements in `begin..end`
+pub fn heapsort_range<T>(list: &mut dyn List<T>, begin: usize, end: usize)
+where
+    T: Copy + Ord + std::fmt::Display,
+{
+    heapify(list, begin, end);
+    for last in (begin + 1..end).rev() {
+        // Move the current maximum to the end and restore the heap
+        list.swap(begin, last);
+        sift_down(list, begin, 0, last);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn test_heapsort() {
+        let mut test_slice = vec![1, 4, 123, 7, 8, 4, 8, 8, 2, 4, 57, 8, 324, 213];
+        let mut test_slice2 = test_slice.clone();
+        assert_eq!(test_slice, test_slice2);
+        test_slice.sort_unstable();
+        heapsort(&mut test_slice2);
+        assert_eq!(test_slice, test_slice2);
+        println!("{:?}", test_slice2);
+    }
+}
@@ -5,9 +5,18 @@ pub fn insertionsort<T>(list: &mut dyn List<T>)
 where
     T: Copy + Ord + std::fmt::Display,
 {
-    for insert_index in 1..list.len() {
+    let length = list.len();
+    insertionsort_range(list, 0, length);
+}
+
+/// Insertion sort the elements in `begin..end`
+pub fn insertionsort_range<T>(list: &mut dyn List<T>, begin: usize, end: usize)
+where
+    T: Copy + Ord + std::fmt::Display,
+{
+    for insert_index in begin + 1..end {
         let mut swap_index = insert_index;
-        while swap_index > 0 && list.compare(swap_index - 1, swap_index) == Ordering::Greater {
+        while swap_index > begin && list.compare(swap_index - 1, swap_index) == Ordering::Greater {
             list.swap(swap_index - 1, swap_index);
             swap_index -= 1;
         }
@@ -0,0 +1,147 @@
+use super::{heapsort_range, insertionsort_range, Highlight, List, PivotStrategy};
+use std::cmp::Ordering;
+
+use rand::RngCore;
+
+/// Ranges with fewer elements are insertion sorted
+const INSERTION_SORT_THRESHOLD: usize = 16;
+
+/// Hoare-style partition of `begin..=end` around the element at `pivot`.
+/// Elements equal to the pivot stop both scans, so duplicates end up on both sides.
+pub(crate) fn hoare_partition<T>(
+    list: &mut dyn List<T>,
+    begin: usize,
+    end: usize,
+    pivot: usize,
+) -> usize
+where
+    T: Copy + Ord + std::fmt::Display,
+{
+    list.swap(begin, pivot);
+    let mut left = begin + 1;
+    let mut right = end;
+    loop {
+        while left <= right && list.compare(left, begin) == Ordering::Less {
+            left += 1;
+        }
+        while left <= right && list.compare(right, begin) == Ordering::Greater {
+            right -= 1;
+        }
+        if left >= right {
+            break;
+        }
+        list.swap(left, right);
+        left += 1;
+        right -= 1;
+    }
+    list.swap(begin, right);
+    right
+}
+
+fn real_introsort<T>(
+    list: &mut dyn List<T>,
+    begin: usize,
+    end: usize,
+    depth_limit: usize,
+    pivot_strategy: PivotStrategy,
+    rng: &mut dyn RngCore,
+) where
+    T: Copy + Ord + std::fmt::Display,
+{
+    let mut begin = begin;
+    let mut end = end;
+    let mut depth_limit = depth_limit;
+    while end - begin > INSERTION_SORT_THRESHOLD {
+        // Quicksort is going quadratic, switch to heapsort
+        if depth_limit == 0 {
+            list.highlight(begin, end, Highlight::Fallback);
+            heapsort_range(list, begin, end);
+            list.highlight(begin, end, Highlight::None);
+            return;
+        }
+        depth_limit -= 1;
+        // Partition elements
+        let pivot = pivot_strategy.select(list, begin, end - 1, rng);
+        let pivot = hoare_partition(list, begin, end - 1, pivot);
+        // Recursion on the smallest partition, loop on the largest
+        if pivot - begin < end - pivot - 1 {
+            real_introsort(list, begin, pivot, depth_limit, pivot_strategy, rng);
+            begin = pivot + 1;
+        } else {
+            real_introsort(list, pivot + 1, end, depth_limit, pivot_strategy, rng);
+            end = pivot;
+        }
+    }
+    insertionsort_range(list, begin, end);
+}
+
+pub fn introsort<T>(list: &mut dyn List<T>)
+where
+    T: Copy + Ord + std::fmt::Display,
+{
+    introsort_with_pivot(list, PivotStrategy::MedianOfThree);
+}
+
+pub fn introsort_with_pivot<T>(list: &mut dyn List<T>, pivot_strategy: PivotStrategy)
+where
+    T: Copy + Ord + std::fmt::Display,
+{
+    introsort_with_rng(list, pivot_strategy, &mut pivot_strategy.make_rng());
+}
+
+/// Variant of `introsort_with_pivot` drawing its random pivots from `rng`
+pub fn introsort_with_rng<T>(
+    list: &mut dyn List<T>,
+    pivot_strategy: PivotStrategy,
+    rng: &mut dyn RngCore,
+) where
+    T: Copy + Ord + std::fmt::Display,
+{
+    let length = list.len();
+    if length > 1 {
+        let depth_limit = 2 * log2(length);
+        real_introsort(list, 0, length, depth_limit, pivot_strategy, rng);
+    }
+}
+
+/// Floor of the base 2 logarithm of `n`, which must be positive
+pub(crate) fn log2(n: usize) -> usize {
+    (std::mem::size_of::<usize>() * 8) - 1 - n.leading_zeros() as usize
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{CountingList, Operation, TracingList};
+    #[test]
+    fn test_introsort() {
+        let mut test_slice = vec![1, 4, 123, 7, 8, 4, 8, 8, 2, 4, 57, 8, 324, 213];
+        let mut test_slice2 = test_slice.clone();
+        assert_eq!(test_slice, test_slice2);
+        test_slice.sort_unstable();
+        introsort(&mut test_slice2);
+        assert_eq!(test_slice, test_slice2);
+        println!("{:?}", test_slice2);
+    }
+    #[test]
+    fn test_introsort_fallback() {
+        // Always choosing the first element as pivot is quadratic on sorted input
+        let mut test_slice: Vec<u32> = (0..1000).collect();
+        let mut tracing = TracingList::new(&mut test_slice);
+        let mut list = CountingList::new(&mut tracing);
+        introsort_with_pivot(&mut list, PivotStrategy::First);
+        assert!(list.statistics().compares < 100_000);
+        assert!(tracing.into_steps().iter().any(|step| matches!(
+            step.operation,
+            Operation::Highlight(_, _, Highlight::Fallback)
+        )));
+        assert_eq!(test_slice, (0..1000).collect::<Vec<_>>());
+    }
+    #[test]
+    fn test_log2() {
+        assert_eq!(log2(1), 0);
+        assert_eq!(log2(2), 1);
+        assert_eq!(log2(1000), 9);
+        assert_eq!(log2(1024), 10);
+    }
+}
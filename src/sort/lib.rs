@@ -1,17 +1,88 @@
+pub mod antiqsort;
+pub mod blocksort;
 pub mod bubblesort;
+pub mod bucketsort;
+pub mod cache;
+pub mod cocktailshakersort;
+pub mod combsort;
+pub mod cost;
+pub mod counting;
+pub mod countingsort;
+pub mod cyclesort;
+pub mod distribution;
+pub mod dualpivotquicksort;
+pub mod flashsort;
+pub mod gnomesort;
 pub mod heapsort;
 pub mod insertionsort;
+pub mod introsort;
+pub mod mergesort;
+pub mod network;
+pub mod oddevensort;
+pub mod pancakesort;
+pub mod pdqsort;
+pub mod pigeonholesort;
+pub mod pivot;
 pub mod quicksort;
 pub mod quicksort2;
+pub mod quicksort3way;
+pub mod radixsort;
+pub mod registry;
+pub mod rotationmergesort;
+pub mod search;
+pub mod select;
 pub mod selectionsort;
 pub mod shellsort;
+pub mod shuffle;
+pub mod smoothsort;
+pub mod sublist;
+#[cfg(test)]
+mod tagged;
+pub mod timsort;
+pub mod trace;
+pub mod weakheapsort;
 
+pub use antiqsort::*;
+pub use blocksort::*;
 pub use bubblesort::*;
+pub use bucketsort::*;
+pub use cache::*;
+pub use cocktailshakersort::*;
+pub use combsort::*;
+pub use cost::*;
+pub use counting::*;
+pub use countingsort::*;
+pub use cyclesort::*;
+pub use distribution::*;
+pub use dualpivotquicksort::*;
+pub use flashsort::*;
+pub use gnomesort::*;
+pub use heapsort::*;
 pub use insertionsort::*;
+pub use introsort::*;
+pub use mergesort::*;
+pub use network::*;
+pub use oddevensort::*;
+pub use pancakesort::*;
+pub use pdqsort::*;
+pub use pigeonholesort::*;
+pub use pivot::*;
 pub use quicksort::*;
 pub use quicksort2::*;
+pub use quicksort3way::*;
+pub use radixsort::*;
+pub use registry::*;
+pub use rotationmergesort::*;
+pub use search::*;
+pub use select::*;
 pub use selectionsort::*;
 pub use shellsort::*;
+pub use shuffle::*;
+pub use smoothsort::*;
+pub use sublist::*;
+pub use timsort::*;
+pub use trace::*;
+pub use weakheapsort::*;
 
 #[derive(Copy, Clone, PartialEq)]
 pub enum Operation {
@@ -19,6 +90,27 @@ pub enum Operation {
     Set(usize),
     Compare(usize, usize),
     Swap(usize, usize),
+    Alloc(&'static str, usize),
+    Free(&'static str),
+    BufferGet(&'static str, usize),
+    BufferSet(&'static str, usize),
+    CopyToBuffer(usize, &'static str, usize),
+    CopyFromBuffer(&'static str, usize, usize),
+    Highlight(usize, usize, Highlight),
+    Key(usize),
+    AllocHistogram(&'static str, usize),
+    FreeHistogram(&'static str),
+    HistogramGet(&'static str, usize),
+    /// Histogram, index and the count written
+    HistogramSet(&'static str, usize, usize),
+    /// Range reversed
+    Reverse(usize, usize),
+    /// Range rotated and the number of positions it was rotated left by
+    Rotate(usize, usize, usize),
+    /// Start of both blocks and their length
+    BlockSwap(usize, usize, usize),
+    /// The next operations, as many as given, happen at the same time
+    Parallel(usize),
 }
 impl std::fmt::Display for Operation {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
@@ -27,6 +119,36 @@ impl std::fmt::Display for Operation {
             Operation::Set(index) => write!(f, "Set[{}]", index),
             Operation::Compare(index1, index2) => write!(f, "Compare[{}][{}]", index1, index2),
             Operation::Swap(index1, index2) => write!(f, "Swap[{}][{}]", index1, index2),
+            Operation::Alloc(buffer, len) => write!(f, "Alloc[{}][{}]", buffer, len),
+            Operation::Free(buffer) => write!(f, "Free[{}]", buffer),
+            Operation::BufferGet(buffer, index) => write!(f, "Get[{}][{}]", buffer, index),
+            Operation::BufferSet(buffer, index) => write!(f, "Set[{}][{}]", buffer, index),
+            Operation::CopyToBuffer(index, buffer, buffer_index) => {
+                write!(f, "Copy[{}]->[{}][{}]", index, buffer, buffer_index)
+            }
+            Operation::CopyFromBuffer(buffer, buffer_index, index) => {
+                write!(f, "Copy[{}][{}]->[{}]", buffer, buffer_index, index)
+            }
+            Operation::Highlight(begin, end, highlight) => {
+                write!(f, "Highlight[{}..{}][{}]", begin, end, highlight)
+            }
+            Operation::Key(index) => write!(f, "Key[{}]", index),
+            Operation::AllocHistogram(histogram, len) => {
+                write!(f, "AllocHistogram[{}][{}]", histogram, len)
+            }
+            Operation::FreeHistogram(histogram) => write!(f, "FreeHistogram[{}]", histogram),
+            Operation::HistogramGet(histogram, index) => {
+                write!(f, "Get[{}][{}]", histogram, index)
+            }
+            Operation::HistogramSet(histogram, index, count) => {
+                write!(f, "Set[{}][{}]={}", histogram, index, count)
+            }
+            Operation::Reverse(begin, end) => write!(f, "Reverse[{}..{}]", begin, end),
+            Operation::Rotate(begin, end, k) => write!(f, "Rotate[{}..{}][{}]", begin, end, k),
+            Operation::BlockSwap(a, b, len) => {
+                write!(f, "BlockSwap[{}..{}][{}..{}]", a, a + len, b, b + len)
+            }
+            Operation::Parallel(count) => write!(f, "Parallel[{}]", count),
         }
     }
 }
@@ -36,6 +158,156 @@ impl std::fmt::Debug for Operation {
     }
 }
 
+/// Role of a range of elements, which visualizations can highlight.
+/// It stays until the range is highlighted again, `Highlight::None` removes it.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum Highlight {
+    None,
+    Pivot,
+    Equal,
+    Fallback,
+    Block,
+    Run,
+    Merge,
+}
+impl Highlight {
+    pub const ALL: &'static [Highlight] = &[
+        Highlight::None,
+        Highlight::Pivot,
+        Highlight::Equal,
+        Highlight::Fallback,
+        Highlight::Block,
+        Highlight::Run,
+        Highlight::Merge,
+    ];
+}
+impl std::fmt::Display for Highlight {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
+        match self {
+            Highlight::None => write!(f, "none"),
+            Highlight::Pivot => write!(f, "pivot"),
+            Highlight::Equal => write!(f, "equal"),
+            Highlight::Fallback => write!(f, "fallback"),
+            Highlight::Block => write!(f, "block"),
+            Highlight::Run => write!(f, "run"),
+            Highlight::Merge => write!(f, "merge"),
+        }
+    }
+}
+impl std::str::FromStr for Highlight {
+    type Err = String;
+    fn from_str(name: &str) -> Result<Highlight, String> {
+        Highlight::ALL
+            .iter()
+            .find(|highlight| highlight.to_string() == name)
+            .copied()
+            .ok_or_else(|| format!("Highlight \"{}\" not found", name))
+    }
+}
+
+/// Auxiliary storage allocated through `List::alloc_buffer`.
+///
+/// Slots start out empty and must be written before they are read.
+/// The name identifies the buffer in `Operation`s, so it must be unique
+/// among the buffers that are alive at the same time.
+pub struct Buffer<T>
+where
+    T: Copy + Ord,
+{
+    pub name: &'static str,
+    pub data: Vec<Option<T>>,
+}
+impl<T> Buffer<T>
+where
+    T: Copy + Ord,
+{
+    pub fn new(name: &'static str, len: usize) -> Buffer<T> {
+        Buffer {
+            name,
+            data: vec![None; len],
+        }
+    }
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+    pub fn get(&self, index: usize) -> T {
+        self.data[index]
+            .unwrap_or_else(|| panic!("Read from empty slot [{}][{}]", self.name, index))
+    }
+    pub fn set(&mut self, index: usize, value: T) {
+        self.data[index] = Some(value);
+    }
+}
+
+/// Counts allocated through `List::alloc_histogram`, like the histogram of a counting sort.
+///
+/// Counts start out as zero. Histograms and buffers share their names, which must be
+/// unique among the buffers and histograms that are alive at the same time.
+pub struct Histogram {
+    pub name: &'static str,
+    pub counts: Vec<usize>,
+}
+impl Histogram {
+    pub fn new(name: &'static str, len: usize) -> Histogram {
+        Histogram {
+            name,
+            counts: vec![0; len],
+        }
+    }
+    pub fn len(&self) -> usize {
+        self.counts.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.counts.is_empty()
+    }
+    pub fn get(&self, index: usize) -> usize {
+        self.counts[index]
+    }
+    pub fn set(&mut self, index: usize, count: usize) {
+        self.counts[index] = count;
+    }
+}
+
+/// Elements which can be ordered by the bits of an unsigned key, like integers.
+/// Comparing keys must give the same order as comparing the elements.
+pub trait RadixKey: Copy + Ord {
+    /// Number of significant bits in keys
+    const BITS: u32;
+    fn radix_key(&self) -> u64;
+}
+macro_rules! impl_radix_key_unsigned {
+    ($($t:ty),*) => {$(
+        impl RadixKey for $t {
+            const BITS: u32 = <$t>::BITS;
+            fn radix_key(&self) -> u64 {
+                *self as u64
+            }
+        }
+    )*};
+}
+macro_rules! impl_radix_key_signed {
+    ($($t:ty => $u:ty),*) => {$(
+        impl RadixKey for $t {
+            const BITS: u32 = <$t>::BITS;
+            fn radix_key(&self) -> u64 {
+                // Flipping the sign bit puts negative numbers first
+                (*self as $u ^ (1 << (<$t>::BITS - 1))) as u64
+            }
+        }
+    )*};
+}
+impl_radix_key_unsigned!(u8, u16, u32, u64, usize);
+impl_radix_key_signed!(i8 => u8, i16 => u16, i32 => u32, i64 => u64, isize => usize);
+impl RadixKey for char {
+    const BITS: u32 = 21;
+    fn radix_key(&self) -> u64 {
+        u64::from(u32::from(*self))
+    }
+}
+
 pub trait List<T>
 where
     T: Copy + Ord,
@@ -60,6 +332,83 @@ where
     fn swap(&mut self, a: usize, b: usize) {
         self.as_mut_slice().swap(a, b);
     }
+    /// Reverse the elements in `begin..end`
+    fn reverse_range(&mut self, begin: usize, end: usize) {
+        let (mut low, mut high) = (begin, end);
+        while low + 1 < high {
+            high -= 1;
+            self.swap(low, high);
+            low += 1;
+        }
+    }
+    /// Rotate the elements in `begin..end` left by `k`, so the one at `begin + k` goes first.
+    /// `k` must not exceed `end - begin`.
+    fn rotate_range(&mut self, begin: usize, end: usize, k: usize) {
+        debug_assert!(begin + k <= end);
+        // Swap the shorter side into its final place and go on with the rest
+        let (mut begin, mut middle, mut end) = (begin, begin + k, end);
+        while begin < middle && middle < end {
+            if middle - begin <= end - middle {
+                self.swap_blocks(begin, middle, middle - begin);
+                let len = middle - begin;
+                begin = middle;
+                middle += len;
+            } else {
+                let len = end - middle;
+                self.swap_blocks(middle - len, middle, len);
+                end = middle;
+                middle -= len;
+            }
+        }
+    }
+    /// Swap the `len` elements starting at `a` with those starting at `b`, which must not overlap
+    fn swap_blocks(&mut self, a: usize, b: usize, len: usize) {
+        for offset in 0..len {
+            self.swap(a + offset, b + offset);
+        }
+    }
+    fn alloc_buffer(&mut self, name: &'static str, len: usize) -> Buffer<T> {
+        Buffer::new(name, len)
+    }
+    fn free_buffer(&mut self, buffer: Buffer<T>) {
+        drop(buffer);
+    }
+    fn buffer_get(&self, buffer: &Buffer<T>, index: usize) -> T {
+        buffer.get(index)
+    }
+    fn buffer_set(&mut self, buffer: &mut Buffer<T>, index: usize, value: T) {
+        buffer.set(index, value);
+    }
+    fn copy_to_buffer(&mut self, index: usize, buffer: &mut Buffer<T>, buffer_index: usize) {
+        buffer.set(buffer_index, self.as_slice()[index]);
+    }
+    fn copy_from_buffer(&mut self, buffer: &Buffer<T>, buffer_index: usize, index: usize) {
+        self.as_mut_slice()[index] = buffer.get(buffer_index);
+    }
+    /// Mark the elements in `begin..end`, which does not modify the list
+    fn highlight(&self, _begin: usize, _end: usize, _highlight: Highlight) {}
+    /// Mark the next `count` operations as independent of each other, so they can be
+    /// shown at the same time. This does not modify the list.
+    fn parallel(&self, _count: usize) {}
+    /// Extract the radix key of the element at `index`
+    fn key(&self, index: usize) -> u64
+    where
+        T: RadixKey,
+    {
+        self.as_slice()[index].radix_key()
+    }
+    fn alloc_histogram(&mut self, name: &'static str, len: usize) -> Histogram {
+        Histogram::new(name, len)
+    }
+    fn free_histogram(&mut self, histogram: Histogram) {
+        drop(histogram);
+    }
+    fn histogram_get(&self, histogram: &Histogram, index: usize) -> usize {
+        histogram.get(index)
+    }
+    fn histogram_set(&mut self, histogram: &mut Histogram, index: usize, count: usize) {
+        histogram.set(index, count);
+    }
 }
 
 impl<T> List<T> for Vec<T>
@@ -74,21 +423,21 @@ where
     }
 }
 
+/// Called with the main slice and, for buffer operations, the buffer involved.
+pub type Callback<'b, T> = Box<dyn 'b + Fn(Operation, &[T], Option<&Buffer<T>>)>;
+
 pub struct CallbackList<'a, 'b, T>
 where
     T: Copy + Ord,
 {
     pub slice: &'a mut [T],
-    pub callback: Box<dyn 'b + Fn(Operation, &[T])>,
+    pub callback: Callback<'b, T>,
 }
 impl<'a, 'b, T> CallbackList<'a, 'b, T>
 where
     T: Copy + Ord,
 {
-    pub fn new(
-        slice: &'a mut [T],
-        callback: Box<dyn 'b + Fn(Operation, &[T])>,
-    ) -> CallbackList<'a, 'b, T> {
+    pub fn new(slice: &'a mut [T], callback: Callback<'b, T>) -> CallbackList<'a, 'b, T> {
         CallbackList { slice, callback }
     }
 }
@@ -104,20 +453,117 @@ where
     }
     fn get(&self, index: usize) -> T {
         let result = self.slice[index];
-        (self.callback)(Operation::Get(index), self.slice);
+        (self.callback)(Operation::Get(index), self.slice, None);
         result
     }
     fn set(&mut self, index: usize, value: T) {
         self.slice[index] = value;
-        (self.callback)(Operation::Set(index), self.slice);
+        (self.callback)(Operation::Set(index), self.slice, None);
     }
     fn compare(&self, a: usize, b: usize) -> std::cmp::Ordering {
         let result = self.slice[a].cmp(&self.slice[b]);
-        (self.callback)(Operation::Compare(a, b), self.slice);
+        (self.callback)(Operation::Compare(a, b), self.slice, None);
         result
     }
     fn swap(&mut self, a: usize, b: usize) {
         self.slice.swap(a, b);
-        (self.callback)(Operation::Swap(a, b), self.slice);
+        (self.callback)(Operation::Swap(a, b), self.slice, None);
+    }
+    fn reverse_range(&mut self, begin: usize, end: usize) {
+        self.slice[begin..end].reverse();
+        (self.callback)(Operation::Reverse(begin, end), self.slice, None);
+    }
+    fn rotate_range(&mut self, begin: usize, end: usize, k: usize) {
+        self.slice[begin..end].rotate_left(k);
+        (self.callback)(Operation::Rotate(begin, end, k), self.slice, None);
+    }
+    fn swap_blocks(&mut self, a: usize, b: usize, len: usize) {
+        for offset in 0..len {
+            self.slice.swap(a + offset, b + offset);
+        }
+        (self.callback)(Operation::BlockSwap(a, b, len), self.slice, None);
+    }
+    fn alloc_buffer(&mut self, name: &'static str, len: usize) -> Buffer<T> {
+        let buffer = Buffer::new(name, len);
+        (self.callback)(Operation::Alloc(name, len), self.slice, Some(&buffer));
+        buffer
+    }
+    fn free_buffer(&mut self, buffer: Buffer<T>) {
+        (self.callback)(Operation::Free(buffer.name), self.slice, Some(&buffer));
+    }
+    fn buffer_get(&self, buffer: &Buffer<T>, index: usize) -> T {
+        let result = buffer.get(index);
+        (self.callback)(
+            Operation::BufferGet(buffer.name, index),
+            self.slice,
+            Some(buffer),
+        );
+        result
+    }
+    fn buffer_set(&mut self, buffer: &mut Buffer<T>, index: usize, value: T) {
+        buffer.set(index, value);
+        (self.callback)(
+            Operation::BufferSet(buffer.name, index),
+            self.slice,
+            Some(buffer),
+        );
+    }
+    fn copy_to_buffer(&mut self, index: usize, buffer: &mut Buffer<T>, buffer_index: usize) {
+        buffer.set(buffer_index, self.slice[index]);
+        (self.callback)(
+            Operation::CopyToBuffer(index, buffer.name, buffer_index),
+            self.slice,
+            Some(buffer),
+        );
+    }
+    fn copy_from_buffer(&mut self, buffer: &Buffer<T>, buffer_index: usize, index: usize) {
+        self.slice[index] = buffer.get(buffer_index);
+        (self.callback)(
+            Operation::CopyFromBuffer(buffer.name, buffer_index, index),
+            self.slice,
+            Some(buffer),
+        );
+    }
+    fn highlight(&self, begin: usize, end: usize, highlight: Highlight) {
+        (self.callback)(
+            Operation::Highlight(begin, end, highlight),
+            self.slice,
+            None,
+        );
+    }
+    fn parallel(&self, count: usize) {
+        (self.callback)(Operation::Parallel(count), self.slice, None);
+    }
+    fn key(&self, index: usize) -> u64
+    where
+        T: RadixKey,
+    {
+        let result = self.slice[index].radix_key();
+        (self.callback)(Operation::Key(index), self.slice, None);
+        result
+    }
+    fn alloc_histogram(&mut self, name: &'static str, len: usize) -> Histogram {
+        (self.callback)(Operation::AllocHistogram(name, len), self.slice, None);
+        Histogram::new(name, len)
+    }
+    fn free_histogram(&mut self, histogram: Histogram) {
+        (self.callback)(Operation::FreeHistogram(histogram.name), self.slice, None);
+    }
+    fn histogram_get(&self, histogram: &Histogram, index: usize) -> usize {
+        let result = histogram.get(index);
+        (self.callback)(
+            Operation::HistogramGet(histogram.name, index),
+            self.slice,
+            None,
+        );
+        result
+    }
+    fn histogram_set(&mut self, histogram: &mut Histogram, index: usize, count: usize) {
+        histogram.set(index, count);
+        (self.callback)(
+            Operation::HistogramSet(histogram.name, index, count),
+            self.slice,
+            None,
+        );
     }
 }
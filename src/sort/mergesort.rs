@@ -0,0 +1,103 @@
+use super::{Buffer, List};
+
+fn merge<T>(list: &mut dyn List<T>, buffer: &mut Buffer<T>, begin: usize, middle: usize, end: usize)
+where
+    T: Copy + Ord + std::fmt::Display,
+{
+    // Move the left run out of the way
+    for index in begin..middle {
+        list.copy_to_buffer(index, buffer, index);
+    }
+    // Merge the buffered left run with the right run, taking from the left on ties
+    let mut left = begin;
+    let mut right = middle;
+    let mut output = begin;
+    while left < middle && right < end {
+        let value = list.get(right);
+        if value < list.buffer_get(buffer, left) {
+            list.set(output, value);
+            right += 1;
+        } else {
+            list.copy_from_buffer(buffer, left, output);
+            left += 1;
+        }
+        output += 1;
+    }
+    // Remaining elements of the right run are already in place
+    while left < middle {
+        list.copy_from_buffer(buffer, left, output);
+        left += 1;
+        output += 1;
+    }
+}
+
+fn real_mergesort<T>(list: &mut dyn List<T>, buffer: &mut Buffer<T>, begin: usize, end: usize)
+where
+    T: Copy + Ord + std::fmt::Display,
+{
+    if end - begin > 1 {
+        let middle = begin + (end - begin) / 2;
+        real_mergesort(list, buffer, begin, middle);
+        real_mergesort(list, buffer, middle, end);
+        merge(list, buffer, begin, middle, end);
+    }
+}
+
+pub fn mergesort<T>(list: &mut dyn List<T>)
+where
+    T: Copy + Ord + std::fmt::Display,
+{
+    let length = list.len();
+    if length > 1 {
+        let mut buffer = list.alloc_buffer("merge", length);
+        real_mergesort(list, &mut buffer, 0, length);
+        list.free_buffer(buffer);
+    }
+}
+
+pub fn mergesort_bottomup<T>(list: &mut dyn List<T>)
+where
+    T: Copy + Ord + std::fmt::Display,
+{
+    let length = list.len();
+    if length > 1 {
+        let mut buffer = list.alloc_buffer("merge", length);
+        let mut width = 1;
+        while width < length {
+            let mut begin = 0;
+            while begin + width < length {
+                let middle = begin + width;
+                let end = (middle + width).min(length);
+                merge(list, &mut buffer, begin, middle, end);
+                begin = end;
+            }
+            width *= 2;
+        }
+        list.free_buffer(buffer);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn test_mergesort() {
+        let mut test_slice = vec![1, 4, 123, 7, 8, 4, 8, 8, 2, 4, 57, 8, 324, 213];
+        let mut test_slice2 = test_slice.clone();
+        assert_eq!(test_slice, test_slice2);
+        test_slice.sort_unstable();
+        mergesort(&mut test_slice2);
+        assert_eq!(test_slice, test_slice2);
+        println!("{:?}", test_slice2);
+    }
+    #[test]
+    fn test_mergesort_bottomup() {
+        let mut test_slice = vec![1, 4, 123, 7, 8, 4, 8, 8, 2, 4, 57, 8, 324, 213];
+        let mut test_slice2 = test_slice.clone();
+        assert_eq!(test_slice, test_slice2);
+        test_slice.sort_unstable();
+        mergesort_bottomup(&mut test_slice2);
+        assert_eq!(test_slice, test_slice2);
+        println!("{:?}", test_slice2);
+    }
+}
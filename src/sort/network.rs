@@ -0,0 +1,398 @@
+//! Sorting networks: fixed sequences of comparators, grouped in layers whose comparators
+//! touch different elements and can therefore run at the same time.
+//!
+//! # Text format
+//!
+//! ```text
+//! # Optimal network for 4 elements
+//! size 4
+//! 0:1 2:3
+//! 0:2 1:3
+//! 1:2
+//! ```
+//!
+//! One layer per line, with comparators written as `a:b`, which puts the smaller element
+//! at `a` and the larger one at `b`. The `size` line is optional, by default the network
+//! is as large as its largest index. Empty lines and lines starting with `#` are ignored.
+
+use super::List;
+use std::cmp::Ordering;
+
+/// Largest network checked by `Network::verify`, which tries every 0-1 input
+pub const NETWORK_MAX_VERIFY_SIZE: usize = 24;
+
+/// Puts the smaller of the elements at both indices at the first one
+pub type Comparator = (usize, usize);
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct Network {
+    size: usize,
+    layers: Vec<Vec<Comparator>>,
+}
+impl Network {
+    pub fn new(size: usize, layers: Vec<Vec<Comparator>>) -> Result<Network, String> {
+        for (number, layer) in layers.iter().enumerate() {
+            let mut used = vec![false; size];
+            for &(a, b) in layer.iter() {
+                if a >= size || b >= size || a == b {
+                    return Err(format!(
+                        "Invalid comparator {}:{} for a network of size {}",
+                        a, b, size
+                    ));
+                }
+                if used[a] || used[b] {
+                    return Err(format!(
+                        "Layer {} uses an element in more than one comparator",
+                        number + 1
+                    ));
+                }
+                used[a] = true;
+                used[b] = true;
+            }
+        }
+        Ok(Network { size, layers })
+    }
+    /// Network applying `comparators` in order, each one in the first layer after the
+    /// previous comparators on its elements
+    pub fn from_comparators(size: usize, comparators: &[Comparator]) -> Network {
+        let mut layers: Vec<Vec<Comparator>> = Vec::new();
+        // Number of layers before the next free one for each element
+        let mut depths = vec![0; size];
+        for &(a, b) in comparators.iter() {
+            let depth = depths[a].max(depths[b]);
+            if depth == layers.len() {
+                layers.push(Vec::new());
+            }
+            layers[depth].push((a, b));
+            depths[a] = depth + 1;
+            depths[b] = depth + 1;
+        }
+        Network { size, layers }
+    }
+    /// Bitonic sorter: sort both halves in opposite directions and merge the resulting
+    /// bitonic sequence. Generalized to any size by splitting at powers of two.
+    pub fn bitonic(size: usize) -> Network {
+        fn sort(comparators: &mut Vec<Comparator>, begin: usize, len: usize, ascending: bool) {
+            if len > 1 {
+                let half = len / 2;
+                sort(comparators, begin, half, !ascending);
+                sort(comparators, begin + half, len - half, ascending);
+                merge(comparators, begin, len, ascending);
+            }
+        }
+        fn merge(comparators: &mut Vec<Comparator>, begin: usize, len: usize, ascending: bool) {
+            if len > 1 {
+                // Largest power of two smaller than len
+                let mut distance = 1;
+                while distance * 2 < len {
+                    distance *= 2;
+                }
+                for index in begin..begin + len - distance {
+                    comparators.push(if ascending {
+                        (index, index + distance)
+                    } else {
+                        (index + distance, index)
+                    });
+                }
+                merge(comparators, begin, distance, ascending);
+                merge(comparators, begin + distance, len - distance, ascending);
+            }
+        }
+        let mut comparators = Vec::new();
+        sort(&mut comparators, 0, size, true);
+        Network::from_comparators(size, &comparators)
+    }
+    /// Batcher's odd-even mergesort, built for the next power of two. Comparators past
+    /// the end are dropped, which is like padding the input with larger elements.
+    pub fn odd_even_merge(size: usize) -> Network {
+        let padded = size.next_power_of_two();
+        let mut comparators = Vec::new();
+        let mut p = 1;
+        while p < padded {
+            let mut k = p;
+            while k >= 1 {
+                for j in (k % p..padded - k).step_by(2 * k) {
+                    for i in 0..k.min(padded - j - k) {
+                        let (a, b) = (i + j, i + j + k);
+                        if a / (2 * p) == b / (2 * p) && b < size {
+                            comparators.push((a, b));
+                        }
+                    }
+                }
+                k /= 2;
+            }
+            p *= 2;
+        }
+        Network::from_comparators(size, &comparators)
+    }
+    /// Batcher's merge exchange sort (Knuth's algorithm 5.2.2M), which works for any size
+    pub fn merge_exchange(size: usize) -> Network {
+        let mut comparators = Vec::new();
+        if size > 1 {
+            let t = usize::BITS - (size - 1).leading_zeros();
+            let mut p = 1 << (t - 1);
+            while p > 0 {
+                let (mut q, mut r, mut d) = (1 << (t - 1), 0, p);
+                loop {
+                    for i in 0..size - d {
+                        if i & p == r {
+                            comparators.push((i, i + d));
+                        }
+                    }
+                    if q == p {
+                        break;
+                    }
+                    d = q - p;
+                    q /= 2;
+                    r = p;
+                }
+                p /= 2;
+            }
+        }
+        Network::from_comparators(size, &comparators)
+    }
+    /// Number of elements sorted by the network
+    pub fn size(&self) -> usize {
+        self.size
+    }
+    pub fn layers(&self) -> &[Vec<Comparator>] {
+        &self.layers
+    }
+    /// Number of comparators in all layers
+    pub fn comparators(&self) -> usize {
+        self.layers.iter().map(|layer| layer.len()).sum()
+    }
+    /// Check that the network sorts every input of its size. By the 0-1 principle it is
+    /// enough to check the inputs made of zeros and ones, 64 at a time as bits.
+    pub fn verify(&self) -> Result<(), String> {
+        if self.size > NETWORK_MAX_VERIFY_SIZE {
+            return Err(format!(
+                "Cannot verify networks larger than {} elements",
+                NETWORK_MAX_VERIFY_SIZE
+            ));
+        }
+        let inputs = 1u64 << self.size;
+        let mut wires = vec![0u64; self.size];
+        for first in (0..inputs).step_by(64) {
+            // Bit i of wire w is element w of input first + i
+            for (w, wire) in wires.iter_mut().enumerate() {
+                *wire =
+                    (0..64.min(inputs)).fold(0, |bits, i| bits | ((((first + i) >> w) & 1) << i));
+            }
+            for layer in self.layers.iter() {
+                for &(a, b) in layer.iter() {
+                    let (low, high) = (wires[a] & wires[b], wires[a] | wires[b]);
+                    wires[a] = low;
+                    wires[b] = high;
+                }
+            }
+            // Unsorted inputs have a one followed by a zero
+            let unsorted = wires
+                .windows(2)
+                .fold(0, |bits, pair| bits | (pair[0] & !pair[1]));
+            if unsorted != 0 {
+                let input = first + u64::from(unsorted.trailing_zeros());
+                let input: String = (0..self.size)
+                    .map(|w| if (input >> w) & 1 == 1 { '1' } else { '0' })
+                    .collect();
+                return Err(format!("Network does not sort the input {}", input));
+            }
+        }
+        Ok(())
+    }
+}
+impl std::fmt::Display for Network {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
+        writeln!(f, "size {}", self.size)?;
+        for layer in self.layers.iter() {
+            let comparators: Vec<String> =
+                layer.iter().map(|(a, b)| format!("{}:{}", a, b)).collect();
+            writeln!(f, "{}", comparators.join(" "))?;
+        }
+        Ok(())
+    }
+}
+impl std::str::FromStr for Network {
+    type Err = String;
+    fn from_str(text: &str) -> Result<Network, String> {
+        let mut size = None;
+        let mut layers = Vec::new();
+        for (number, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let invalid = || format!("Invalid network line {}", number + 1);
+            if let Some(value) = line.strip_prefix("size") {
+                size = Some(value.trim().parse::<usize>().map_err(|_| invalid())?);
+                continue;
+            }
+            let mut layer = Vec::new();
+            for comparator in line.split_whitespace() {
+                let mut indices = comparator.splitn(2, ':').map(|i| i.parse::<usize>());
+                match (indices.next(), indices.next()) {
+                    (Some(Ok(a)), Some(Ok(b))) => layer.push((a, b)),
+                    _ => return Err(invalid()),
+                }
+            }
+            layers.push(layer);
+        }
+        let size = size.unwrap_or_else(|| {
+            layers
+                .iter()
+                .flatten()
+                .map(|&(a, b)| a.max(b) + 1)
+                .max()
+                .unwrap_or(0)
+        });
+        Network::new(size, layers)
+    }
+}
+
+/// Apply the comparators of `network` to `list`, one layer at a time. The comparisons of
+/// a layer and then its swaps are marked as parallel operations.
+pub fn run_network<T>(list: &mut dyn List<T>, network: &Network)
+where
+    T: Copy + Ord + std::fmt::Display,
+{
+    assert_eq!(
+        list.len(),
+        network.size(),
+        "Network of size {} cannot sort {} elements",
+        network.size(),
+        list.len()
+    );
+    for layer in network.layers() {
+        list.parallel(layer.len());
+        let swaps: Vec<Comparator> = layer
+            .iter()
+            .copied()
+            .filter(|&(a, b)| list.compare(a, b) == Ordering::Greater)
+            .collect();
+        if !swaps.is_empty() {
+            list.parallel(swaps.len());
+            for (a, b) in swaps {
+                list.swap(a, b);
+            }
+        }
+    }
+}
+
+pub fn bitonicsort<T>(list: &mut dyn List<T>)
+where
+    T: Copy + Ord + std::fmt::Display,
+{
+    let network = Network::bitonic(list.len());
+    run_network(list, &network);
+}
+
+pub fn oddevenmergesort<T>(list: &mut dyn List<T>)
+where
+    T: Copy + Ord + std::fmt::Display,
+{
+    let network = Network::odd_even_merge(list.len());
+    run_network(list, &network);
+}
+
+pub fn mergeexchangesort<T>(list: &mut dyn List<T>)
+where
+    T: Copy + Ord + std::fmt::Display,
+{
+    let network = Network::merge_exchange(list.len());
+    run_network(list, &network);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Operation, TracingList};
+    #[test]
+    fn test_bitonicsort() {
+        let mut test_slice = vec![1, 4, 123, 7, 8, 4, 8, 8, 2, 4, 57, 8, 324, 213];
+        let mut test_slice2 = test_slice.clone();
+        assert_eq!(test_slice, test_slice2);
+        test_slice.sort_unstable();
+        bitonicsort(&mut test_slice2);
+        assert_eq!(test_slice, test_slice2);
+        println!("{:?}", test_slice2);
+    }
+    #[test]
+    fn test_oddevenmergesort() {
+        let mut test_slice = vec![1, 4, 123, 7, 8, 4, 8, 8, 2, 4, 57, 8, 324, 213];
+        let mut test_slice2 = test_slice.clone();
+        assert_eq!(test_slice, test_slice2);
+        test_slice.sort_unstable();
+        oddevenmergesort(&mut test_slice2);
+        assert_eq!(test_slice, test_slice2);
+        println!("{:?}", test_slice2);
+    }
+    #[test]
+    fn test_mergeexchangesort() {
+        let mut test_slice = vec![1, 4, 123, 7, 8, 4, 8, 8, 2, 4, 57, 8, 324, 213];
+        let mut test_slice2 = test_slice.clone();
+        assert_eq!(test_slice, test_slice2);
+        test_slice.sort_unstable();
+        mergeexchangesort(&mut test_slice2);
+        assert_eq!(test_slice, test_slice2);
+        println!("{:?}", test_slice2);
+    }
+    #[test]
+    fn test_network_generators() {
+        for size in 0..=20 {
+            for network in [
+                Network::bitonic(size),
+                Network::odd_even_merge(size),
+                Network::merge_exchange(size),
+            ]
+            .iter()
+            {
+                assert_eq!(network.size(), size);
+                assert_eq!(network.verify(), Ok(()), "size {}:\n{}", size, network);
+            }
+        }
+        // Known sizes and depths for 16 elements
+        let network = Network::odd_even_merge(16);
+        assert_eq!((network.comparators(), network.layers().len()), (63, 10));
+        let network = Network::bitonic(16);
+        assert_eq!((network.comparators(), network.layers().len()), (80, 10));
+    }
+    #[test]
+    fn test_network_text() {
+        let text = "# Optimal network for 4 elements\n0:1 2:3\n0:2 1:3\n\n1:2\n";
+        let network = text.parse::<Network>().unwrap();
+        assert_eq!(network.size(), 4);
+        assert_eq!(network.layers().len(), 3);
+        assert_eq!(network.verify(), Ok(()));
+        assert_eq!(network.to_string().parse::<Network>(), Ok(network));
+        assert!("0:1 1:2".parse::<Network>().is_err());
+        assert!("0:0".parse::<Network>().is_err());
+        assert!("size 2\n0:2".parse::<Network>().is_err());
+        assert!("0-1".parse::<Network>().is_err());
+    }
+    #[test]
+    fn test_network_verify() {
+        // Missing the last comparator
+        let network = "0:1 2:3\n0:2 1:3".parse::<Network>().unwrap();
+        assert_eq!(
+            network.verify(),
+            Err(String::from("Network does not sort the input 1010"))
+        );
+        assert!(Network::merge_exchange(NETWORK_MAX_VERIFY_SIZE + 1)
+            .verify()
+            .is_err());
+    }
+    #[test]
+    fn test_network_parallel() {
+        let mut test_slice = vec![4, 3, 2, 1];
+        let network = Network::bitonic(4);
+        let mut list = TracingList::new(&mut test_slice);
+        run_network(&mut list, &network);
+        let steps = list.into_steps();
+        assert_eq!(steps[0].operation, Operation::Parallel(2));
+        assert_eq!(steps[1].operation, Operation::Compare(1, 0));
+        assert_eq!(steps[2].operation, Operation::Compare(2, 3));
+        assert_eq!(steps[3].operation, Operation::Parallel(1));
+        assert_eq!(steps[4].operation, Operation::Swap(2, 3));
+        assert_eq!(test_slice, vec![1, 2, 3, 4]);
+    }
+}
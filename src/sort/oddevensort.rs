@@ -0,0 +1,37 @@
+use super::List;
+use std::cmp::Ordering;
+
+/// Alternately compare the pairs starting at odd and at even indices. The comparisons
+/// within a phase are independent, which makes this a parallel sorting algorithm.
+pub fn oddevensort<T>(list: &mut dyn List<T>)
+where
+    T: Copy + Ord + std::fmt::Display,
+{
+    let mut sorted = false;
+    while !sorted {
+        sorted = true;
+        for start in [1, 0].iter() {
+            for index in (start + 1..list.len()).step_by(2) {
+                if list.compare(index - 1, index) == Ordering::Greater {
+                    list.swap(index - 1, index);
+                    sorted = false;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn test_oddevensort() {
+        let mut test_slice = vec![1, 4, 123, 7, 8, 4, 8, 8, 2, 4, 57, 8, 324, 213];
+        let mut test_slice2 = test_slice.clone();
+        assert_eq!(test_slice, test_slice2);
+        test_slice.sort_unstable();
+        oddevensort(&mut test_slice2);
+        assert_eq!(test_slice, test_slice2);
+        println!("{:?}", test_slice2);
+    }
+}
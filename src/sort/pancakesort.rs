@@ -0,0 +1,51 @@
+use super::List;
+use std::cmp::Ordering;
+
+/// Sort by flipping prefixes only: the largest unsorted element is flipped to the front
+/// and then to the end of the unsorted part
+pub fn pancakesort<T>(list: &mut dyn List<T>)
+where
+    T: Copy + Ord + std::fmt::Display,
+{
+    for size in (2..=list.len()).rev() {
+        let mut largest = 0;
+        for index in 1..size {
+            if list.compare(index, largest) != Ordering::Less {
+                largest = index;
+            }
+        }
+        if largest != size - 1 {
+            if largest != 0 {
+                list.reverse_range(0, largest + 1);
+            }
+            list.reverse_range(0, size);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::CountingList;
+    #[test]
+    fn test_pancakesort() {
+        let mut test_slice = vec![1, 4, 123, 7, 8, 4, 8, 8, 2, 4, 57, 8, 324, 213];
+        let mut test_slice2 = test_slice.clone();
+        assert_eq!(test_slice, test_slice2);
+        test_slice.sort_unstable();
+        pancakesort(&mut test_slice2);
+        assert_eq!(test_slice, test_slice2);
+        println!("{:?}", test_slice2);
+    }
+    #[test]
+    fn test_pancakesort_flips() {
+        let mut test_slice: Vec<u32> = (0..100).map(|i| (i * 7919) % 100).collect();
+        let mut list = CountingList::new(&mut test_slice);
+        pancakesort(&mut list);
+        let statistics = list.statistics();
+        // At most two flips per element, and no other writes
+        assert!(statistics.reverses <= 2 * 100);
+        assert_eq!(statistics.swaps, 0);
+        assert_eq!(test_slice, (0..100).collect::<Vec<u32>>());
+    }
+}
@@ -0,0 +1,358 @@
+use super::introsort::log2;
+use super::{heapsort_range, insertionsort_range, Highlight, List};
+use std::cmp::Ordering;
+
+/// Ranges with fewer elements are insertion sorted
+const INSERTION_SORT_THRESHOLD: usize = 24;
+/// Ranges with more elements use Tukey's ninther for pivot selection
+const NINTHER_THRESHOLD: usize = 128;
+/// Maximum number of elements moved by an optimistic insertion sort
+const PARTIAL_INSERTION_SORT_LIMIT: usize = 8;
+/// Number of elements scanned at once by the block partition
+const BLOCK_SIZE: usize = 64;
+
+fn sort2<T>(list: &mut dyn List<T>, a: usize, b: usize)
+where
+    T: Copy + Ord + std::fmt::Display,
+{
+    if list.compare(b, a) == Ordering::Less {
+        list.swap(a, b);
+    }
+}
+
+fn sort3<T>(list: &mut dyn List<T>, a: usize, b: usize, c: usize)
+where
+    T: Copy + Ord + std::fmt::Display,
+{
+    sort2(list, a, b);
+    sort2(list, b, c);
+    sort2(list, a, b);
+}
+
+/// Insertion sort which gives up after moving too many elements.
+/// Returns whether `begin..end` was sorted.
+fn partial_insertionsort<T>(list: &mut dyn List<T>, begin: usize, end: usize) -> bool
+where
+    T: Copy + Ord + std::fmt::Display,
+{
+    let mut moved = 0;
+    for current in begin + 1..end {
+        let mut sift = current;
+        while sift > begin && list.compare(sift, sift - 1) == Ordering::Less {
+            list.swap(sift - 1, sift);
+            sift -= 1;
+        }
+        moved += current - sift;
+        if moved > PARTIAL_INSERTION_SORT_LIMIT {
+            return false;
+        }
+    }
+    true
+}
+
+/// Partition `begin..end` around the element at `begin`, with elements equal to the
+/// pivot going to the right. Elements on the wrong side are found a block at a time
+/// (BlockQuicksort by Edelkamp and Weiss) and then swapped in pairs.
+/// Returns the final pivot position and whether the range was already partitioned.
+fn partition_right<T>(list: &mut dyn List<T>, begin: usize, end: usize) -> (usize, bool)
+where
+    T: Copy + Ord + std::fmt::Display,
+{
+    // Find the first element not smaller than the pivot
+    let mut first = begin + 1;
+    while first < end && list.compare(first, begin) == Ordering::Less {
+        first += 1;
+    }
+    // Find the last element smaller than the pivot
+    let mut last = end;
+    while first < last {
+        last -= 1;
+        if list.compare(last, begin) == Ordering::Less {
+            break;
+        }
+    }
+    // If there is nothing to swap the range was already partitioned
+    let already_partitioned = first >= last;
+    if !already_partitioned {
+        list.swap(first, last);
+        first += 1;
+        // Offsets of misplaced elements from the left and right bases
+        let mut offsets_left = [0; BLOCK_SIZE];
+        let mut offsets_right = [0; BLOCK_SIZE];
+        let (mut base_left, mut base_right) = (first, last);
+        let (mut count_left, mut count_right) = (0, 0);
+        let (mut start_left, mut start_right) = (0, 0);
+        while first < last {
+            // Split the unknown elements between the blocks that need refilling
+            let unknown = last - first;
+            let left_split = match (count_left, count_right) {
+                (0, 0) => unknown / 2,
+                (0, _) => unknown,
+                _ => 0,
+            };
+            let right_split = if count_right == 0 {
+                unknown - left_split
+            } else {
+                0
+            };
+            // Scan a block from the left for elements not smaller than the pivot
+            let left_block = left_split.min(BLOCK_SIZE);
+            if left_block > 0 {
+                list.highlight(first, first + left_block, Highlight::Block);
+                for i in 0..left_block {
+                    offsets_left[count_left] = i;
+                    if list.compare(first, begin) != Ordering::Less {
+                        count_left += 1;
+                    }
+                    first += 1;
+                }
+            }
+            // Scan a block from the right for elements smaller than the pivot
+            let right_block = right_split.min(BLOCK_SIZE);
+            if right_block > 0 {
+                list.highlight(last - right_block, last, Highlight::Block);
+                for i in 0..right_block {
+                    offsets_right[count_right] = i + 1;
+                    last -= 1;
+                    if list.compare(last, begin) == Ordering::Less {
+                        count_right += 1;
+                    }
+                }
+            }
+            // Swap misplaced pairs
+            let count = count_left.min(count_right);
+            for i in 0..count {
+                list.swap(
+                    base_left + offsets_left[start_left + i],
+                    base_right - offsets_right[start_right + i],
+                );
+            }
+            count_left -= count;
+            count_right -= count;
+            start_left += count;
+            start_right += count;
+            // Blocks without misplaced elements are done
+            if count_left == 0 {
+                list.highlight(base_left, first, Highlight::None);
+                start_left = 0;
+                base_left = first;
+            }
+            if count_right == 0 {
+                list.highlight(last, base_right, Highlight::None);
+                start_right = 0;
+                base_right = last;
+            }
+        }
+        // Only one block has misplaced elements left, move them to the middle
+        if count_left > 0 {
+            list.highlight(base_left, first, Highlight::None);
+            while count_left > 0 {
+                count_left -= 1;
+                last -= 1;
+                list.swap(base_left + offsets_left[start_left + count_left], last);
+            }
+            first = last;
+        }
+        if count_right > 0 {
+            list.highlight(last, base_right, Highlight::None);
+            while count_right > 0 {
+                count_right -= 1;
+                list.swap(base_right - offsets_right[start_right + count_right], first);
+                first += 1;
+            }
+        }
+    }
+    // Put the pivot in place
+    let pivot = first - 1;
+    list.swap(begin, pivot);
+    (pivot, already_partitioned)
+}
+
+/// Partition `begin..end` around the element at `begin`, with elements equal to the
+/// pivot going to the left. Only used when no element in the range is smaller than
+/// the pivot, so the left partition ends up containing only equal elements.
+fn partition_left<T>(list: &mut dyn List<T>, begin: usize, end: usize) -> usize
+where
+    T: Copy + Ord + std::fmt::Display,
+{
+    let mut first = begin;
+    let mut last = end - 1;
+    while list.compare(begin, last) == Ordering::Less {
+        last -= 1;
+    }
+    first += 1;
+    while first < last && list.compare(begin, first) != Ordering::Less {
+        first += 1;
+    }
+    while first < last {
+        list.swap(first, last);
+        last -= 1;
+        while list.compare(begin, last) == Ordering::Less {
+            last -= 1;
+        }
+        first += 1;
+        while first < last && list.compare(begin, first) != Ordering::Less {
+            first += 1;
+        }
+    }
+    list.swap(begin, last);
+    last
+}
+
+/// Swap elements to break patterns which lead to unbalanced partitions
+fn break_patterns<T>(list: &mut dyn List<T>, begin: usize, pivot: usize, end: usize)
+where
+    T: Copy + Ord + std::fmt::Display,
+{
+    let left_size = pivot - begin;
+    let right_size = end - (pivot + 1);
+    if left_size >= INSERTION_SORT_THRESHOLD {
+        list.swap(begin, begin + left_size / 4);
+        list.swap(pivot - 1, pivot - left_size / 4);
+        if left_size > NINTHER_THRESHOLD {
+            list.swap(begin + 1, begin + (left_size / 4 + 1));
+            list.swap(begin + 2, begin + (left_size / 4 + 2));
+            list.swap(pivot - 2, pivot - (left_size / 4 + 1));
+            list.swap(pivot - 3, pivot - (left_size / 4 + 2));
+        }
+    }
+    if right_size >= INSERTION_SORT_THRESHOLD {
+        list.swap(pivot + 1, pivot + (1 + right_size / 4));
+        list.swap(end - 1, end - right_size / 4);
+        if right_size > NINTHER_THRESHOLD {
+            list.swap(pivot + 2, pivot + (2 + right_size / 4));
+            list.swap(pivot + 3, pivot + (3 + right_size / 4));
+            list.swap(end - 2, end - (1 + right_size / 4));
+            list.swap(end - 3, end - (2 + right_size / 4));
+        }
+    }
+}
+
+fn real_pdqsort<T>(
+    list: &mut dyn List<T>,
+    begin: usize,
+    end: usize,
+    bad_allowed: usize,
+    leftmost: bool,
+) where
+    T: Copy + Ord + std::fmt::Display,
+{
+    let mut begin = begin;
+    let mut bad_allowed = bad_allowed;
+    let mut leftmost = leftmost;
+    loop {
+        let size = end - begin;
+        if size < INSERTION_SORT_THRESHOLD {
+            insertionsort_range(list, begin, end);
+            return;
+        }
+        // Move the pivot to begin
+        let middle = begin + size / 2;
+        if size > NINTHER_THRESHOLD {
+            sort3(list, begin, middle, end - 1);
+            sort3(list, begin + 1, middle - 1, end - 2);
+            sort3(list, begin + 2, middle + 1, end - 3);
+            sort3(list, middle - 1, middle, middle + 1);
+            list.swap(begin, middle);
+        } else {
+            sort3(list, middle, begin, end - 1);
+        }
+        list.highlight(begin, begin + 1, Highlight::Pivot);
+        // The element before begin is a previous pivot not greater than anything in the
+        // range. If it equals the new pivot, put equal elements on the left and skip them.
+        if !leftmost && list.compare(begin - 1, begin) != Ordering::Less {
+            let pivot = partition_left(list, begin, end);
+            list.highlight(begin, pivot + 1, Highlight::Equal);
+            begin = pivot + 1;
+            continue;
+        }
+        // Partition elements
+        let (pivot, already_partitioned) = partition_right(list, begin, end);
+        list.highlight(begin, begin + 1, Highlight::None);
+        list.highlight(pivot, pivot + 1, Highlight::Pivot);
+        let left_size = pivot - begin;
+        let right_size = end - (pivot + 1);
+        if left_size < size / 8 || right_size < size / 8 {
+            // Too many unbalanced partitions, switch to heapsort
+            bad_allowed -= 1;
+            if bad_allowed == 0 {
+                list.highlight(begin, end, Highlight::Fallback);
+                heapsort_range(list, begin, end);
+                list.highlight(begin, end, Highlight::None);
+                return;
+            }
+            break_patterns(list, begin, pivot, end);
+        } else if already_partitioned
+            && partial_insertionsort(list, begin, pivot)
+            && partial_insertionsort(list, pivot + 1, end)
+        {
+            // The range was probably sorted already
+            return;
+        }
+        // Recursion on the left partition, loop on the right one
+        real_pdqsort(list, begin, pivot, bad_allowed, leftmost);
+        begin = pivot + 1;
+        leftmost = false;
+    }
+}
+
+pub fn pdqsort<T>(list: &mut dyn List<T>)
+where
+    T: Copy + Ord + std::fmt::Display,
+{
+    let length = list.len();
+    if length > 1 {
+        real_pdqsort(list, 0, length, log2(length), true);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Operation, TracingList};
+    fn highlights(steps: &[crate::Step<u32>], highlight: Highlight) -> bool {
+        steps.iter().any(|step| match step.operation {
+            Operation::Highlight(_, _, h) => h == highlight,
+            _ => false,
+        })
+    }
+    #[test]
+    fn test_pdqsort() {
+        let mut test_slice = vec![1, 4, 123, 7, 8, 4, 8, 8, 2, 4, 57, 8, 324, 213];
+        let mut test_slice2 = test_slice.clone();
+        assert_eq!(test_slice, test_slice2);
+        test_slice.sort_unstable();
+        pdqsort(&mut test_slice2);
+        assert_eq!(test_slice, test_slice2);
+        println!("{:?}", test_slice2);
+    }
+    #[test]
+    fn test_pdqsort_block_partition() {
+        let mut test_slice: Vec<u32> = (0..1000).map(|i| (i * 7919) % 1000).collect();
+        let mut tracing = TracingList::new(&mut test_slice);
+        pdqsort(&mut tracing);
+        assert!(highlights(&tracing.into_steps(), Highlight::Block));
+        assert_eq!(test_slice, (0..1000).collect::<Vec<_>>());
+    }
+    #[test]
+    fn test_pdqsort_duplicates() {
+        let mut test_slice: Vec<u32> = (0..500).map(|i| (i * 7919) % 3).collect();
+        let mut expected = test_slice.clone();
+        expected.sort_unstable();
+        let mut tracing = TracingList::new(&mut test_slice);
+        pdqsort(&mut tracing);
+        assert!(highlights(&tracing.into_steps(), Highlight::Equal));
+        assert_eq!(test_slice, expected);
+    }
+    #[test]
+    fn test_pdqsort_fallback() {
+        // Allow no bad partitions so the first unbalanced one falls back to heapsort
+        let mut test_slice: Vec<u32> = (0..200).map(|i| if i % 2 == 0 { i } else { 0 }).collect();
+        let mut expected = test_slice.clone();
+        expected.sort_unstable();
+        let mut tracing = TracingList::new(&mut test_slice);
+        real_pdqsort(&mut tracing, 0, 200, 1, true);
+        assert!(highlights(&tracing.into_steps(), Highlight::Fallback));
+        assert_eq!(test_slice, expected);
+    }
+}
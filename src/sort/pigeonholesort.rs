@@ -0,0 +1,81 @@
+use super::countingsort::key_bounds;
+use super::{List, RadixKey};
+
+/// Largest span of keys supported by pigeonhole sort, which needs a hole for each of them
+pub const PIGEONHOLESORT_MAX_RANGE: u64 = 1 << 16;
+
+/// Put each element in the hole for its key and read the holes back in order.
+/// Every element gets its own slot in one buffer, and each hole chains its slots
+/// from first to last, so equal elements come back in their original order.
+pub fn pigeonholesort<T>(list: &mut dyn List<T>)
+where
+    T: RadixKey + std::fmt::Display,
+{
+    let length = list.len();
+    if length < 2 {
+        return;
+    }
+    let (min, max) = key_bounds(list);
+    assert!(
+        max - min < PIGEONHOLESORT_MAX_RANGE,
+        "Pigeonhole sort supports keys spanning at most {} values",
+        PIGEONHOLESORT_MAX_RANGE
+    );
+    let range = (max - min) as usize + 1;
+    // Slots are stored plus one, so that zero ends a chain or marks an empty hole
+    let mut first = list.alloc_histogram("first", range);
+    let mut last = list.alloc_histogram("last", range);
+    let mut next = list.alloc_histogram("next", length);
+    let mut slots = list.alloc_buffer("slots", length);
+    for index in 0..length {
+        let hole = (list.key(index) - min) as usize;
+        list.copy_to_buffer(index, &mut slots, index);
+        match list.histogram_get(&last, hole) {
+            0 => list.histogram_set(&mut first, hole, index + 1),
+            tail => list.histogram_set(&mut next, tail - 1, index + 1),
+        }
+        list.histogram_set(&mut last, hole, index + 1);
+    }
+    let mut position = 0;
+    for hole in 0..range {
+        let mut slot = list.histogram_get(&first, hole);
+        while slot > 0 {
+            list.copy_from_buffer(&slots, slot - 1, position);
+            position += 1;
+            slot = list.histogram_get(&next, slot - 1);
+        }
+    }
+    list.free_buffer(slots);
+    list.free_histogram(next);
+    list.free_histogram(last);
+    list.free_histogram(first);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tagged::{is_sorted_stably, tagged};
+    #[test]
+    fn test_pigeonholesort() {
+        let mut test_slice = vec![1, 4, 123, 7, 8, 4, 8, 8, 2, 4, 57, 8, 324, 213];
+        let mut test_slice2 = test_slice.clone();
+        assert_eq!(test_slice, test_slice2);
+        test_slice.sort_unstable();
+        pigeonholesort(&mut test_slice2);
+        assert_eq!(test_slice, test_slice2);
+        println!("{:?}", test_slice2);
+    }
+    #[test]
+    fn test_pigeonholesort_tagged() {
+        let original = tagged(&[2, 1, 2, 1, 3, 2]);
+        let mut test_slice = original.clone();
+        pigeonholesort(&mut test_slice);
+        assert!(is_sorted_stably(&original, &test_slice));
+    }
+    #[test]
+    fn test_pigeonholesort_negative() {
+        let mut test_slice: Vec<i64> = vec![-5, 3, -5, 0, -1000, 7];
+        pigeonholesort(&mut test_slice);
+        assert_eq!(test_slice, vec![-1000, -5, -5, 0, 3, 7]);
+    }
+}
@@ -0,0 +1,179 @@
+use super::List;
+use std::cmp::Ordering;
+
+use rand::prelude::*;
+use rand::rngs::StdRng;
+
+/// How quicksort chooses the pivot of each partition
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum PivotStrategy {
+    First,
+    Last,
+    Middle,
+    MedianOfThree,
+    Ninther,
+    Random,
+    SeededRandom(u64),
+}
+impl PivotStrategy {
+    pub const NAMES: &'static [&'static str] = &[
+        "first",
+        "last",
+        "middle",
+        "median3",
+        "ninther",
+        "random",
+        "random:SEED",
+    ];
+    /// Random number generator to be passed to `PivotStrategy::select` during a sort
+    pub fn make_rng(&self) -> Box<dyn RngCore> {
+        match *self {
+            PivotStrategy::SeededRandom(seed) => Box::new(StdRng::seed_from_u64(seed)),
+            _ => Box::new(thread_rng()),
+        }
+    }
+    /// Choose a pivot index in `begin..=end`
+    pub fn select<T>(
+        &self,
+        list: &dyn List<T>,
+        begin: usize,
+        end: usize,
+        rng: &mut dyn RngCore,
+    ) -> usize
+    where
+        T: Copy + Ord + std::fmt::Display,
+    {
+        let middle = begin + (end - begin) / 2;
+        match *self {
+            PivotStrategy::First => begin,
+            PivotStrategy::Last => end,
+            PivotStrategy::Middle => middle,
+            PivotStrategy::MedianOfThree => median_of_three(list, begin, middle, end),
+            PivotStrategy::Ninther => {
+                let step = (end - begin + 1) / 8;
+                if step == 0 {
+                    median_of_three(list, begin, middle, end)
+                } else {
+                    let first = median_of_three(list, begin, begin + step, begin + 2 * step);
+                    let second = median_of_three(list, middle - step, middle, middle + step);
+                    let third = median_of_three(list, end - 2 * step, end - step, end);
+                    median_of_three(list, first, second, third)
+                }
+            }
+            PivotStrategy::Random | PivotStrategy::SeededRandom(_) => {
+                (rng.next_u64() as usize % (end + 1 - begin)) + begin
+            }
+        }
+    }
+}
+impl std::fmt::Display for PivotStrategy {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
+        match self {
+            PivotStrategy::First => write!(f, "first"),
+            PivotStrategy::Last => write!(f, "last"),
+            PivotStrategy::Middle => write!(f, "middle"),
+            PivotStrategy::MedianOfThree => write!(f, "median3"),
+            PivotStrategy::Ninther => write!(f, "ninther"),
+            PivotStrategy::Random => write!(f, "random"),
+            PivotStrategy::SeededRandom(seed) => write!(f, "random:{}", seed),
+        }
+    }
+}
+impl std::str::FromStr for PivotStrategy {
+    type Err = String;
+    fn from_str(name: &str) -> Result<PivotStrategy, String> {
+        match name {
+            "first" => Ok(PivotStrategy::First),
+            "last" => Ok(PivotStrategy::Last),
+            "middle" => Ok(PivotStrategy::Middle),
+            "median3" => Ok(PivotStrategy::MedianOfThree),
+            "ninther" => Ok(PivotStrategy::Ninther),
+            "random" => Ok(PivotStrategy::Random),
+            _ if name.starts_with("random:") => name["random:".len()..]
+                .parse::<u64>()
+                .map(PivotStrategy::SeededRandom)
+                .map_err(|_| format!("Cannot parse \"{}\" as a random seed", name)),
+            _ => Err(format!("Pivot strategy \"{}\" not found", name)),
+        }
+    }
+}
+
+/// Index of the median element among `a`, `b` and `c`
+fn median_of_three<T>(list: &dyn List<T>, a: usize, b: usize, c: usize) -> usize
+where
+    T: Copy + Ord + std::fmt::Display,
+{
+    if list.compare(a, b) == Ordering::Less {
+        if list.compare(b, c) == Ordering::Less {
+            b
+        } else if list.compare(a, c) == Ordering::Less {
+            c
+        } else {
+            a
+        }
+    } else if list.compare(a, c) == Ordering::Less {
+        a
+    } else if list.compare(b, c) == Ordering::Less {
+        c
+    } else {
+        b
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn test_median_of_three() {
+        for permutation in [
+            [1, 2, 3],
+            [1, 3, 2],
+            [2, 1, 3],
+            [2, 3, 1],
+            [3, 1, 2],
+            [3, 2, 1],
+        ]
+        .iter()
+        {
+            let list = permutation.to_vec();
+            let median = median_of_three(&list, 0, 1, 2);
+            assert_eq!(list[median], 2);
+        }
+    }
+    #[test]
+    fn test_pivot_strategies() {
+        let list: Vec<u32> = (0..27).collect();
+        let mut rng = PivotStrategy::Random.make_rng();
+        assert_eq!(PivotStrategy::First.select(&list, 3, 20, &mut rng), 3);
+        assert_eq!(PivotStrategy::Last.select(&list, 3, 20, &mut rng), 20);
+        assert_eq!(PivotStrategy::Middle.select(&list, 3, 20, &mut rng), 11);
+        assert_eq!(
+            PivotStrategy::MedianOfThree.select(&list, 3, 20, &mut rng),
+            11
+        );
+        assert_eq!(PivotStrategy::Ninther.select(&list, 0, 26, &mut rng), 13);
+        let pivot = PivotStrategy::Random.select(&list, 3, 20, &mut rng);
+        assert!((3..=20).contains(&pivot));
+        // The same seed always produces the same pivots
+        let seeded = PivotStrategy::SeededRandom(42);
+        let (mut rng1, mut rng2) = (seeded.make_rng(), seeded.make_rng());
+        for _ in 0..10 {
+            assert_eq!(
+                seeded.select(&list, 0, 26, &mut rng1),
+                seeded.select(&list, 0, 26, &mut rng2)
+            );
+        }
+    }
+    #[test]
+    fn test_pivot_strategy_names() {
+        for &name in PivotStrategy::NAMES.iter().filter(|n| !n.contains("SEED")) {
+            assert_eq!(name.parse::<PivotStrategy>().unwrap().to_string(), name);
+        }
+        assert_eq!(
+            "random:7".parse::<PivotStrategy>(),
+            Ok(PivotStrategy::SeededRandom(7))
+        );
+        assert!("random:x".parse::<PivotStrategy>().is_err());
+        assert!("median5".parse::<PivotStrategy>().is_err());
+    }
+}
@@ -1,7 +1,7 @@
-use super::List;
+use super::{List, PivotStrategy};
 use std::cmp::Ordering;
 
-use rand::prelude::*;
+use rand::RngCore;
 
 fn relocate_pivot_right<T>(list: &mut dyn List<T>, pivot: usize, right: usize) -> usize
 where
@@ -72,11 +72,18 @@ where
     }
 }
 
-fn partition<T>(list: &mut dyn List<T>, begin: usize, end: usize) -> usize
+/// Partition `begin..=end` around a pivot chosen by `pivot_strategy` and return its final index
+pub(crate) fn partition<T>(
+    list: &mut dyn List<T>,
+    begin: usize,
+    end: usize,
+    pivot_strategy: PivotStrategy,
+    rng: &mut dyn RngCore,
+) -> usize
 where
     T: Copy + Ord + std::fmt::Display,
 {
-    let pivot = (random::<usize>() % (end + 1 - begin)) + begin;
+    let pivot = pivot_strategy.select(list, begin, end, rng);
     // Swap large elements to the left with small ones to the right
     let remaining = swap_from_sides(list, begin, pivot, end);
     // Move remaining elements left or right and reposition pivot element
@@ -87,27 +94,32 @@ where
     }
 }
 
-fn real_quicksort<T>(list: &mut dyn List<T>, begin: usize, end: usize)
-where
+fn real_quicksort<T>(
+    list: &mut dyn List<T>,
+    begin: usize,
+    end: usize,
+    pivot_strategy: PivotStrategy,
+    rng: &mut dyn RngCore,
+) where
     T: Copy + Ord + std::fmt::Display,
 {
     if begin < end {
         // Partition elements
-        let pivot = partition(list, begin, end);
+        let pivot = partition(list, begin, end, pivot_strategy, rng);
         // Calculate element count on each side of the pivot
         let left_length = pivot - begin;
         let right_length = end - pivot;
         // Recursion (tail call to the largest partition)
         if left_length <= right_length {
             if left_length > 0 {
-                real_quicksort(list, begin, pivot - 1);
+                real_quicksort(list, begin, pivot - 1, pivot_strategy, rng);
             }
-            real_quicksort(list, pivot + 1, end);
+            real_quicksort(list, pivot + 1, end, pivot_strategy, rng);
         } else {
             if right_length > 0 {
-                real_quicksort(list, pivot + 1, end);
+                real_quicksort(list, pivot + 1, end, pivot_strategy, rng);
             }
-            real_quicksort(list, begin, pivot - 1);
+            real_quicksort(list, begin, pivot - 1, pivot_strategy, rng);
         }
     }
 }
@@ -115,10 +127,28 @@ where
 pub fn quicksort<T>(list: &mut dyn List<T>)
 where
     T: Copy + Ord + std::fmt::Display,
+{
+    quicksort_with_pivot(list, PivotStrategy::Random);
+}
+
+pub fn quicksort_with_pivot<T>(list: &mut dyn List<T>, pivot_strategy: PivotStrategy)
+where
+    T: Copy + Ord + std::fmt::Display,
+{
+    quicksort_with_rng(list, pivot_strategy, &mut pivot_strategy.make_rng());
+}
+
+/// Variant of `quicksort_with_pivot` drawing its random pivots from `rng`
+pub fn quicksort_with_rng<T>(
+    list: &mut dyn List<T>,
+    pivot_strategy: PivotStrategy,
+    rng: &mut dyn RngCore,
+) where
+    T: Copy + Ord + std::fmt::Display,
 {
     let length = list.len();
     if length > 1 {
-        real_quicksort(list, 0, length - 1);
+        real_quicksort(list, 0, length - 1, pivot_strategy, rng);
     }
 }
 
@@ -135,4 +165,25 @@ mod tests {
         assert_eq!(test_slice, test_slice2);
         println!("{:?}", test_slice2);
     }
+    #[test]
+    fn test_quicksort_with_pivot() {
+        let strategies = [
+            PivotStrategy::First,
+            PivotStrategy::Last,
+            PivotStrategy::Middle,
+            PivotStrategy::MedianOfThree,
+            PivotStrategy::Ninther,
+            PivotStrategy::SeededRandom(42),
+        ];
+        for &pivot_strategy in strategies.iter() {
+            let mut test_slice = vec![1, 4, 123, 7, 8, 4, 8, 8, 2, 4, 57, 8, 324, 213];
+            let mut test_slice2 = test_slice.clone();
+            test_slice.sort_unstable();
+            quicksort_with_pivot(&mut test_slice2, pivot_strategy);
+            assert_eq!(test_slice, test_slice2);
+            // Already sorted input
+            quicksort_with_pivot(&mut test_slice2, pivot_strategy);
+            assert_eq!(test_slice, test_slice2);
+        }
+    }
 }
@@ -0,0 +1,116 @@
+use super::{Highlight, List, PivotStrategy};
+use std::cmp::Ordering;
+
+use rand::RngCore;
+
+/// Dijkstra's "Dutch national flag" partition.
+/// Returns the bounds of the range of elements equal to the pivot.
+fn partition<T>(
+    list: &mut dyn List<T>,
+    begin: usize,
+    end: usize,
+    pivot_strategy: PivotStrategy,
+    rng: &mut dyn RngCore,
+) -> (usize, usize)
+where
+    T: Copy + Ord + std::fmt::Display,
+{
+    let pivot = pivot_strategy.select(list, begin, end, rng);
+    list.swap(begin, pivot);
+    list.highlight(begin, begin + 1, Highlight::Pivot);
+    // Invariant: [begin, lower) < pivot, [lower, index) == pivot, (upper, end] > pivot
+    let mut lower = begin;
+    let mut index = begin + 1;
+    let mut upper = end;
+    while index <= upper {
+        match list.compare(index, lower) {
+            Ordering::Less => {
+                list.swap(lower, index);
+                lower += 1;
+                index += 1;
+            }
+            Ordering::Greater => {
+                list.swap(index, upper);
+                upper -= 1;
+            }
+            Ordering::Equal => index += 1,
+        }
+    }
+    list.highlight(begin, lower, Highlight::None);
+    list.highlight(lower, upper + 1, Highlight::Equal);
+    (lower, upper)
+}
+
+fn real_quicksort3way<T>(
+    list: &mut dyn List<T>,
+    begin: usize,
+    end: usize,
+    pivot_strategy: PivotStrategy,
+    rng: &mut dyn RngCore,
+) where
+    T: Copy + Ord + std::fmt::Display,
+{
+    if begin < end {
+        // Partition elements
+        let (lower, upper) = partition(list, begin, end, pivot_strategy, rng);
+        // Elements equal to the pivot are already in their final position
+        if lower > begin {
+            real_quicksort3way(list, begin, lower - 1, pivot_strategy, rng);
+        }
+        real_quicksort3way(list, upper + 1, end, pivot_strategy, rng);
+    }
+}
+
+pub fn quicksort3way<T>(list: &mut dyn List<T>)
+where
+    T: Copy + Ord + std::fmt::Display,
+{
+    quicksort3way_with_pivot(list, PivotStrategy::Random);
+}
+
+pub fn quicksort3way_with_pivot<T>(list: &mut dyn List<T>, pivot_strategy: PivotStrategy)
+where
+    T: Copy + Ord + std::fmt::Display,
+{
+    quicksort3way_with_rng(list, pivot_strategy, &mut pivot_strategy.make_rng());
+}
+
+/// Variant of `quicksort3way_with_pivot` drawing its random pivots from `rng`
+pub fn quicksort3way_with_rng<T>(
+    list: &mut dyn List<T>,
+    pivot_strategy: PivotStrategy,
+    rng: &mut dyn RngCore,
+) where
+    T: Copy + Ord + std::fmt::Display,
+{
+    let length = list.len();
+    if length > 1 {
+        real_quicksort3way(list, 0, length - 1, pivot_strategy, rng);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn test_quicksort3way() {
+        let mut test_slice = vec![1, 4, 123, 7, 8, 4, 8, 8, 2, 4, 57, 8, 324, 213];
+        let mut test_slice2 = test_slice.clone();
+        assert_eq!(test_slice, test_slice2);
+        test_slice.sort_unstable();
+        quicksort3way(&mut test_slice2);
+        assert_eq!(test_slice, test_slice2);
+        println!("{:?}", test_slice2);
+    }
+    #[test]
+    fn test_quicksort3way_duplicates() {
+        let mut test_slice = vec![3, 1, 3, 3, 2, 1, 1, 3, 2, 2, 3, 1, 1, 3, 3, 2, 1, 3, 3, 3];
+        let mut test_slice2 = test_slice.clone();
+        test_slice.sort_unstable();
+        quicksort3way_with_pivot(&mut test_slice2, PivotStrategy::First);
+        assert_eq!(test_slice, test_slice2);
+        let mut all_equal = vec![5; 32];
+        quicksort3way(&mut all_equal);
+        assert_eq!(all_equal, vec![5; 32]);
+    }
+}
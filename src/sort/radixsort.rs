@@ -0,0 +1,273 @@
+use super::{insertionsort_range, List, RadixKey};
+
+/// Buckets smaller than this are insertion sorted by the MSD radix sort
+const INSERTION_SORT_THRESHOLD: usize = 16;
+
+/// Number of buckets per digit used by radix sorts, which is a power of two
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct Radix {
+    bits: u32,
+}
+impl Radix {
+    pub const MIN: usize = 2;
+    pub const MAX: usize = 1 << 16;
+    pub fn new(radix: usize) -> Result<Radix, String> {
+        if radix.is_power_of_two() && (Radix::MIN..=Radix::MAX).contains(&radix) {
+            Ok(Radix {
+                bits: radix.trailing_zeros(),
+            })
+        } else {
+            Err(format!(
+                "Radix {} is not a power of two from {} to {}",
+                radix,
+                Radix::MIN,
+                Radix::MAX
+            ))
+        }
+    }
+    /// Number of buckets
+    pub fn size(&self) -> usize {
+        1 << self.bits
+    }
+    /// Number of key bits in each digit
+    pub fn bits(&self) -> u32 {
+        self.bits
+    }
+    /// Number of digits needed to cover the keys of `T`
+    fn digits<T>(&self) -> u32
+    where
+        T: RadixKey,
+    {
+        T::BITS.div_ceil(self.bits)
+    }
+    /// Digit `digit` of `key`, counting from the least significant one
+    fn digit(&self, key: u64, digit: u32) -> usize {
+        ((key >> (digit * self.bits)) as usize) & (self.size() - 1)
+    }
+}
+impl Default for Radix {
+    fn default() -> Radix {
+        Radix { bits: 8 }
+    }
+}
+impl std::fmt::Display for Radix {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
+        write!(f, "{}", self.size())
+    }
+}
+impl std::str::FromStr for Radix {
+    type Err = String;
+    fn from_str(text: &str) -> Result<Radix, String> {
+        match text.parse::<usize>() {
+            Ok(radix) => Radix::new(radix),
+            Err(_) => Err(format!("Cannot parse \"{}\" as unsigned integer", text)),
+        }
+    }
+}
+
+pub fn lsd_radixsort<T>(list: &mut dyn List<T>)
+where
+    T: RadixKey + std::fmt::Display,
+{
+    lsd_radixsort_with_radix(list, Radix::default());
+}
+
+/// Stable distribution by each digit in turn, starting from the least significant one.
+/// Every pass counts the elements per bucket, scatters them into a buffer and copies them back.
+pub fn lsd_radixsort_with_radix<T>(list: &mut dyn List<T>, radix: Radix)
+where
+    T: RadixKey + std::fmt::Display,
+{
+    let length = list.len();
+    if length < 2 {
+        return;
+    }
+    let mut buffer = list.alloc_buffer("buckets", length);
+    for digit in 0..radix.digits::<T>() {
+        let mut counts = list.alloc_histogram("counts", radix.size());
+        for index in 0..length {
+            let bucket = radix.digit(list.key(index), digit);
+            let count = list.histogram_get(&counts, bucket);
+            list.histogram_set(&mut counts, bucket, count + 1);
+        }
+        // Each count becomes the offset of its bucket, nothing moves if every element
+        // is in the same bucket
+        let mut offset = 0;
+        let mut moves = true;
+        for bucket in 0..radix.size() {
+            let count = list.histogram_get(&counts, bucket);
+            moves &= count < length;
+            list.histogram_set(&mut counts, bucket, offset);
+            offset += count;
+        }
+        if moves {
+            for index in 0..length {
+                let bucket = radix.digit(list.key(index), digit);
+                let offset = list.histogram_get(&counts, bucket);
+                list.copy_to_buffer(index, &mut buffer, offset);
+                list.histogram_set(&mut counts, bucket, offset + 1);
+            }
+            for index in 0..length {
+                list.copy_from_buffer(&buffer, index, index);
+            }
+        }
+        list.free_histogram(counts);
+    }
+    list.free_buffer(buffer);
+}
+
+fn real_msd_radixsort<T>(list: &mut dyn List<T>, begin: usize, end: usize, digit: u32, radix: Radix)
+where
+    T: RadixKey + std::fmt::Display,
+{
+    if end - begin < INSERTION_SORT_THRESHOLD {
+        insertionsort_range(list, begin, end);
+        return;
+    }
+    // Bucket boundaries, heads advance as elements are placed in their buckets
+    let mut heads = list.alloc_histogram("heads", radix.size());
+    let mut tails = list.alloc_histogram("tails", radix.size());
+    for index in begin..end {
+        let bucket = radix.digit(list.key(index), digit);
+        let count = list.histogram_get(&heads, bucket);
+        list.histogram_set(&mut heads, bucket, count + 1);
+    }
+    let mut offset = begin;
+    let mut moves = true;
+    for bucket in 0..radix.size() {
+        let count = list.histogram_get(&heads, bucket);
+        moves &= count < end - begin;
+        list.histogram_set(&mut heads, bucket, offset);
+        offset += count;
+        list.histogram_set(&mut tails, bucket, offset);
+    }
+    // Unless every element is in the same bucket, swap each element into its bucket
+    if moves {
+        for bucket in 0..radix.size() {
+            let tail = list.histogram_get(&tails, bucket);
+            loop {
+                let head = list.histogram_get(&heads, bucket);
+                if head == tail {
+                    break;
+                }
+                let target = radix.digit(list.key(head), digit);
+                if target == bucket {
+                    list.histogram_set(&mut heads, bucket, head + 1);
+                } else {
+                    let target_head = list.histogram_get(&heads, target);
+                    list.swap(head, target_head);
+                    list.histogram_set(&mut heads, target, target_head + 1);
+                }
+            }
+        }
+    }
+    // Histogram names must be unique, so free them before going deeper
+    list.free_histogram(tails);
+    list.free_histogram(heads);
+    // Sort each bucket by the next digit, finding where it ends by binary search
+    if digit > 0 {
+        let mut bucket_begin = begin;
+        while bucket_begin < end {
+            let bucket = radix.digit(list.key(bucket_begin), digit);
+            let (mut low, mut high) = (bucket_begin + 1, end);
+            while low < high {
+                let middle = low + (high - low) / 2;
+                if radix.digit(list.key(middle), digit) == bucket {
+                    low = middle + 1;
+                } else {
+                    high = middle;
+                }
+            }
+            if low - bucket_begin > 1 {
+                real_msd_radixsort(list, bucket_begin, low, digit - 1, radix);
+            }
+            bucket_begin = low;
+        }
+    }
+}
+
+pub fn msd_radixsort<T>(list: &mut dyn List<T>)
+where
+    T: RadixKey + std::fmt::Display,
+{
+    msd_radixsort_with_radix(list, Radix::default());
+}
+
+/// In-place distribution by each digit, starting from the most significant one
+/// (American flag sort). Small buckets are finished with insertion sort.
+pub fn msd_radixsort_with_radix<T>(list: &mut dyn List<T>, radix: Radix)
+where
+    T: RadixKey + std::fmt::Display,
+{
+    let length = list.len();
+    if length > 1 {
+        real_msd_radixsort(list, 0, length, radix.digits::<T>() - 1, radix);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::CountingList;
+    #[test]
+    fn test_lsd_radixsort() {
+        let mut test_slice = vec![1, 4, 123, 7, 8, 4, 8, 8, 2, 4, 57, 8, 324, 213];
+        let mut test_slice2 = test_slice.clone();
+        assert_eq!(test_slice, test_slice2);
+        test_slice.sort_unstable();
+        lsd_radixsort(&mut test_slice2);
+        assert_eq!(test_slice, test_slice2);
+        println!("{:?}", test_slice2);
+    }
+    #[test]
+    fn test_msd_radixsort() {
+        let mut test_slice = vec![1, 4, 123, 7, 8, 4, 8, 8, 2, 4, 57, 8, 324, 213];
+        let mut test_slice2 = test_slice.clone();
+        assert_eq!(test_slice, test_slice2);
+        test_slice.sort_unstable();
+        msd_radixsort(&mut test_slice2);
+        assert_eq!(test_slice, test_slice2);
+        println!("{:?}", test_slice2);
+    }
+    #[test]
+    fn test_radixsort_with_radix() {
+        let test_slice: Vec<i32> = (0..1000).map(|i| (i * 7919) % 2001 - 1000).collect();
+        let mut expected = test_slice.clone();
+        expected.sort_unstable();
+        for &radix in [2, 16, 256, 65536].iter() {
+            let radix = Radix::new(radix).unwrap();
+            let mut test_slice2 = test_slice.clone();
+            lsd_radixsort_with_radix(&mut test_slice2, radix);
+            assert_eq!(test_slice2, expected);
+            let mut test_slice2 = test_slice.clone();
+            msd_radixsort_with_radix(&mut test_slice2, radix);
+            assert_eq!(test_slice2, expected);
+        }
+    }
+    #[test]
+    fn test_lsd_radixsort_no_compares() {
+        let mut test_slice: Vec<u32> = (0..100).map(|i| (i * 7919) % 1000).collect();
+        let mut list = CountingList::new(&mut test_slice);
+        lsd_radixsort(&mut list);
+        let statistics = list.statistics();
+        assert_eq!(statistics.compares, 0);
+        // Only the lowest two bytes differ, so the upper passes are skipped after counting
+        assert_eq!(statistics.keys, 100 * 4 + 100 * 2);
+        assert_eq!(statistics.copies_to_buffer, 100 * 2);
+        // The buffer and a histogram of counts for each of the four passes
+        assert_eq!(statistics.allocs, 1 + 4);
+        assert_eq!(statistics.histogram_sets, 4 * (100 + 256) + 2 * 100);
+    }
+    #[test]
+    fn test_radix() {
+        assert_eq!("256".parse::<Radix>(), Ok(Radix::default()));
+        assert_eq!(Radix::new(16).unwrap().bits(), 4);
+        assert_eq!(Radix::new(16).unwrap().digits::<u32>(), 8);
+        assert_eq!(Radix::new(1 << 16).unwrap().digit(0x1234_5678, 1), 0x1234);
+        assert!("0".parse::<Radix>().is_err());
+        assert!("1".parse::<Radix>().is_err());
+        assert!("100".parse::<Radix>().is_err());
+        assert!("131072".parse::<Radix>().is_err());
+        assert!("ten".parse::<Radix>().is_err());
+    }
+}
@@ -0,0 +1,786 @@
+use super::*;
+
+use rand::RngCore;
+
+/// Sorting function over a whole list
+pub type SortFunction<T> = fn(&mut dyn List<T>);
+/// Sorting function taking a pivot strategy and the generator for random pivots
+pub type PivotSortFunction<T> = fn(&mut dyn List<T>, PivotStrategy, &mut dyn RngCore);
+/// Sorting function which uses another one on parts of the list
+pub type InnerSortFunction<T> = fn(&mut dyn List<T>, &mut dyn FnMut(&mut dyn List<T>));
+
+/// Description of a sorting algorithm and its properties
+#[derive(Copy, Clone)]
+pub struct Algorithm<T>
+where
+    T: Copy + Ord + std::fmt::Display,
+{
+    pub name: &'static str,
+    pub aliases: &'static [&'static str],
+    pub stable: bool,
+    /// Whether the elements never leave the list, so it needs no buffers of elements.
+    /// Histograms of counts may still be used.
+    pub in_place: bool,
+    /// Whether it allocates auxiliary buffers or histograms, shown below the list
+    pub uses_buffers: bool,
+    pub randomized: bool,
+    pub best: &'static str,
+    pub average: &'static str,
+    pub worst: &'static str,
+    pub sort_fn: fn(&mut dyn List<T>),
+    /// Variant of `sort_fn` taking a pivot strategy and the generator for random pivots,
+    /// for quicksort-like algorithms
+    pub pivot_fn: Option<PivotSortFunction<T>>,
+    /// Variant of `sort_fn` taking a gap sequence, for shellsort-like algorithms
+    pub gaps_fn: Option<fn(&mut dyn List<T>, &GapSequence)>,
+    /// Variant of `sort_fn` taking a shrink factor for the gap, for comb sort
+    pub shrink_fn: Option<fn(&mut dyn List<T>, Shrink)>,
+    /// Variant of `sort_fn` taking a radix, for radix sorts
+    pub radix_fn: Option<fn(&mut dyn List<T>, Radix)>,
+    /// Variant of `sort_fn` taking the sort used on parts of the list, for bucket sorts
+    pub inner_fn: Option<InnerSortFunction<T>>,
+    /// Largest span of keys supported (largest minus smallest plus one), for sorts
+    /// needing memory for every key
+    pub max_key_range: Option<u64>,
+}
+impl<T> Algorithm<T>
+where
+    T: Copy + Ord + std::fmt::Display,
+{
+    pub fn matches(&self, name: &str) -> bool {
+        self.name == name || self.aliases.contains(&name)
+    }
+}
+
+impl<T> Algorithm<T>
+where
+    T: RadixKey + std::fmt::Display,
+{
+    /// Check that the algorithm supports the range of values in `data`
+    pub fn check_data(&self, data: &[T]) -> Result<(), String> {
+        if let (Some(max_range), Some(min), Some(max)) =
+            (self.max_key_range, data.iter().min(), data.iter().max())
+        {
+            if max.radix_key() - min.radix_key() >= max_range {
+                return Err(format!(
+                    "Sorting function \"{}\" supports values spanning at most {} keys, but the data goes from {} to {}",
+                    self.name, max_range, min, max
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+pub fn algorithms<T>() -> Vec<Algorithm<T>>
+where
+    T: RadixKey + std::fmt::Display,
+{
+    vec![
+        Algorithm {
+            name: "bitonicsort",
+            aliases: &["bitonic"],
+            stable: false,
+            in_place: true,
+            uses_buffers: false,
+            randomized: false,
+            best: "O(n log^2 n)",
+            average: "O(n log^2 n)",
+            worst: "O(n log^2 n)",
+            sort_fn: bitonicsort,
+            pivot_fn: None,
+            gaps_fn: None,
+            shrink_fn: None,
+            radix_fn: None,
+            inner_fn: None,
+            max_key_range: None,
+        },
+        Algorithm {
+            name: "blocksort",
+            aliases: &["block", "wikisort"],
+            stable: true,
+            in_place: true,
+            uses_buffers: false,
+            randomized: false,
+            best: "O(n)",
+            average: "O(n log n)",
+            worst: "O(n log n)",
+            sort_fn: blocksort,
+            pivot_fn: None,
+            gaps_fn: None,
+            shrink_fn: None,
+            radix_fn: None,
+            inner_fn: None,
+            max_key_range: None,
+        },
+        Algorithm {
+            name: "bubblesort",
+            aliases: &["bubble"],
+            stable: true,
+            in_place: true,
+            uses_buffers: false,
+            randomized: false,
+            best: "O(n)",
+            average: "O(n^2)",
+            worst: "O(n^2)",
+            sort_fn: bubblesort,
+            pivot_fn: None,
+            gaps_fn: None,
+            shrink_fn: None,
+            radix_fn: None,
+            inner_fn: None,
+            max_key_range: None,
+        },
+        Algorithm {
+            name: "bucketsort",
+            aliases: &["bucket"],
+            stable: true,
+            in_place: false,
+            uses_buffers: true,
+            randomized: false,
+            best: "O(n)",
+            average: "O(n)",
+            worst: "O(n^2)",
+            sort_fn: bucketsort,
+            pivot_fn: None,
+            gaps_fn: None,
+            shrink_fn: None,
+            radix_fn: None,
+            inner_fn: Some(bucketsort_with_inner),
+            max_key_range: None,
+        },
+        Algorithm {
+            name: "cocktailshakersort",
+            aliases: &["cocktail", "shaker"],
+            stable: true,
+            in_place: true,
+            uses_buffers: false,
+            randomized: false,
+            best: "O(n)",
+            average: "O(n^2)",
+            worst: "O(n^2)",
+            sort_fn: cocktailshakersort,
+            pivot_fn: None,
+            gaps_fn: None,
+            shrink_fn: None,
+            radix_fn: None,
+            inner_fn: None,
+            max_key_range: None,
+        },
+        Algorithm {
+            name: "combsort",
+            aliases: &["comb"],
+            stable: false,
+            in_place: true,
+            uses_buffers: false,
+            randomized: false,
+            best: "O(n log n)",
+            average: "O(n^2/2^p)",
+            worst: "O(n^2)",
+            sort_fn: combsort,
+            pivot_fn: None,
+            gaps_fn: None,
+            shrink_fn: Some(combsort_with_shrink),
+            radix_fn: None,
+            inner_fn: None,
+            max_key_range: None,
+        },
+        Algorithm {
+            name: "countingsort",
+            aliases: &["counting"],
+            stable: true,
+            in_place: false,
+            uses_buffers: true,
+            randomized: false,
+            best: "O(n + k)",
+            average: "O(n + k)",
+            worst: "O(n + k)",
+            sort_fn: countingsort,
+            pivot_fn: None,
+            gaps_fn: None,
+            shrink_fn: None,
+            radix_fn: None,
+            inner_fn: None,
+            max_key_range: Some(COUNTINGSORT_MAX_RANGE),
+        },
+        Algorithm {
+            name: "cyclesort",
+            aliases: &["cycle"],
+            stable: false,
+            in_place: true,
+            uses_buffers: false,
+            randomized: false,
+            best: "O(n^2)",
+            average: "O(n^2)",
+            worst: "O(n^2)",
+            sort_fn: cyclesort,
+            pivot_fn: None,
+            gaps_fn: None,
+            shrink_fn: None,
+            radix_fn: None,
+            inner_fn: None,
+            max_key_range: None,
+        },
+        Algorithm {
+            name: "dualpivotquicksort",
+            aliases: &["dualpivot", "yaroslavskiy"],
+            stable: false,
+            in_place: true,
+            uses_buffers: false,
+            randomized: false,
+            best: "O(n log n)",
+            average: "O(n log n)",
+            worst: "O(n^2)",
+            sort_fn: dualpivotquicksort,
+            pivot_fn: None,
+            gaps_fn: None,
+            shrink_fn: None,
+            radix_fn: None,
+            inner_fn: None,
+            max_key_range: None,
+        },
+        Algorithm {
+            name: "flashsort",
+            aliases: &["flash"],
+            stable: false,
+            in_place: true,
+            uses_buffers: true,
+            randomized: false,
+            best: "O(n)",
+            average: "O(n)",
+            worst: "O(n^2)",
+            sort_fn: flashsort,
+            pivot_fn: None,
+            gaps_fn: None,
+            shrink_fn: None,
+            radix_fn: None,
+            inner_fn: None,
+            max_key_range: None,
+        },
+        Algorithm {
+            name: "gnomesort",
+            aliases: &["gnome", "stupidsort"],
+            stable: true,
+            in_place: true,
+            uses_buffers: false,
+            randomized: false,
+            best: "O(n)",
+            average: "O(n^2)",
+            worst: "O(n^2)",
+            sort_fn: gnomesort,
+            pivot_fn: None,
+            gaps_fn: None,
+            shrink_fn: None,
+            radix_fn: None,
+            inner_fn: None,
+            max_key_range: None,
+        },
+        Algorithm {
+            name: "heapsort",
+            aliases: &["heap"],
+            stable: false,
+            in_place: true,
+            uses_buffers: false,
+            randomized: false,
+            best: "O(n log n)",
+            average: "O(n log n)",
+            worst: "O(n log n)",
+            sort_fn: heapsort,
+            pivot_fn: None,
+            gaps_fn: None,
+            shrink_fn: None,
+            radix_fn: None,
+            inner_fn: None,
+            max_key_range: None,
+        },
+        Algorithm {
+            name: "insertionsort",
+            aliases: &["insertion"],
+            stable: true,
+            in_place: true,
+            uses_buffers: false,
+            randomized: false,
+            best: "O(n)",
+            average: "O(n^2)",
+            worst: "O(n^2)",
+            sort_fn: insertionsort,
+            pivot_fn: None,
+            gaps_fn: None,
+            shrink_fn: None,
+            radix_fn: None,
+            inner_fn: None,
+            max_key_range: None,
+        },
+        Algorithm {
+            name: "introsort",
+            aliases: &["intro"],
+            stable: false,
+            in_place: true,
+            uses_buffers: false,
+            randomized: false,
+            best: "O(n log n)",
+            average: "O(n log n)",
+            worst: "O(n log n)",
+            sort_fn: introsort,
+            pivot_fn: Some(introsort_with_rng),
+            gaps_fn: None,
+            shrink_fn: None,
+            radix_fn: None,
+            inner_fn: None,
+            max_key_range: None,
+        },
+        Algorithm {
+            name: "lsd_radixsort",
+            aliases: &["lsd", "radixsort", "radix"],
+            stable: true,
+            in_place: false,
+            uses_buffers: true,
+            randomized: false,
+            best: "O(nk)",
+            average: "O(nk)",
+            worst: "O(nk)",
+            sort_fn: lsd_radixsort,
+            pivot_fn: None,
+            gaps_fn: None,
+            shrink_fn: None,
+            radix_fn: Some(lsd_radixsort_with_radix),
+            inner_fn: None,
+            max_key_range: None,
+        },
+        Algorithm {
+            name: "mergeexchangesort",
+            aliases: &["mergeexchange", "batchersort"],
+            stable: false,
+            in_place: true,
+            uses_buffers: false,
+            randomized: false,
+            best: "O(n log^2 n)",
+            average: "O(n log^2 n)",
+            worst: "O(n log^2 n)",
+            sort_fn: mergeexchangesort,
+            pivot_fn: None,
+            gaps_fn: None,
+            shrink_fn: None,
+            radix_fn: None,
+            inner_fn: None,
+            max_key_range: None,
+        },
+        Algorithm {
+            name: "mergesort",
+            aliases: &["merge", "mergesort_topdown"],
+            stable: true,
+            in_place: false,
+            uses_buffers: true,
+            randomized: false,
+            best: "O(n log n)",
+            average: "O(n log n)",
+            worst: "O(n log n)",
+            sort_fn: mergesort,
+            pivot_fn: None,
+            gaps_fn: None,
+            shrink_fn: None,
+            radix_fn: None,
+            inner_fn: None,
+            max_key_range: None,
+        },
+        Algorithm {
+            name: "mergesort_bottomup",
+            aliases: &["bottomup"],
+            stable: true,
+            in_place: false,
+            uses_buffers: true,
+            randomized: false,
+            best: "O(n log n)",
+            average: "O(n log n)",
+            worst: "O(n log n)",
+            sort_fn: mergesort_bottomup,
+            pivot_fn: None,
+            gaps_fn: None,
+            shrink_fn: None,
+            radix_fn: None,
+            inner_fn: None,
+            max_key_range: None,
+        },
+        Algorithm {
+            name: "msd_radixsort",
+            aliases: &["msd", "americanflagsort", "american_flag"],
+            stable: false,
+            in_place: true,
+            uses_buffers: true,
+            randomized: false,
+            best: "O(nk)",
+            average: "O(nk)",
+            worst: "O(nk)",
+            sort_fn: msd_radixsort,
+            pivot_fn: None,
+            gaps_fn: None,
+            shrink_fn: None,
+            radix_fn: Some(msd_radixsort_with_radix),
+            inner_fn: None,
+            max_key_range: None,
+        },
+        Algorithm {
+            name: "oddevenmergesort",
+            aliases: &["oddevenmerge"],
+            stable: false,
+            in_place: true,
+            uses_buffers: false,
+            randomized: false,
+            best: "O(n log^2 n)",
+            average: "O(n log^2 n)",
+            worst: "O(n log^2 n)",
+            sort_fn: oddevenmergesort,
+            pivot_fn: None,
+            gaps_fn: None,
+            shrink_fn: None,
+            radix_fn: None,
+            inner_fn: None,
+            max_key_range: None,
+        },
+        Algorithm {
+            name: "oddevensort",
+            aliases: &["oddeven", "bricksort"],
+            stable: true,
+            in_place: true,
+            uses_buffers: false,
+            randomized: false,
+            best: "O(n)",
+            average: "O(n^2)",
+            worst: "O(n^2)",
+            sort_fn: oddevensort,
+            pivot_fn: None,
+            gaps_fn: None,
+            shrink_fn: None,
+            radix_fn: None,
+            inner_fn: None,
+            max_key_range: None,
+        },
+        Algorithm {
+            name: "pancakesort",
+            aliases: &["pancake"],
+            stable: false,
+            in_place: true,
+            uses_buffers: false,
+            randomized: false,
+            best: "O(n^2)",
+            average: "O(n^2)",
+            worst: "O(n^2)",
+            sort_fn: pancakesort,
+            pivot_fn: None,
+            gaps_fn: None,
+            shrink_fn: None,
+            radix_fn: None,
+            inner_fn: None,
+            max_key_range: None,
+        },
+        Algorithm {
+            name: "pdqsort",
+            aliases: &["pdq", "pattern_defeating_quicksort"],
+            stable: false,
+            in_place: true,
+            uses_buffers: false,
+            randomized: false,
+            best: "O(n)",
+            average: "O(n log n)",
+            worst: "O(n log n)",
+            sort_fn: pdqsort,
+            pivot_fn: None,
+            gaps_fn: None,
+            shrink_fn: None,
+            radix_fn: None,
+            inner_fn: None,
+            max_key_range: None,
+        },
+        Algorithm {
+            name: "pigeonholesort",
+            aliases: &["pigeonhole"],
+            stable: true,
+            in_place: false,
+            uses_buffers: true,
+            randomized: false,
+            best: "O(n + k)",
+            average: "O(n + k)",
+            worst: "O(n + k)",
+            sort_fn: pigeonholesort,
+            pivot_fn: None,
+            gaps_fn: None,
+            shrink_fn: None,
+            radix_fn: None,
+            inner_fn: None,
+            max_key_range: Some(PIGEONHOLESORT_MAX_RANGE),
+        },
+        Algorithm {
+            name: "quicksort",
+            aliases: &["quick"],
+            stable: false,
+            in_place: true,
+            uses_buffers: false,
+            randomized: true,
+            best: "O(n log n)",
+            average: "O(n log n)",
+            worst: "O(n^2)",
+            sort_fn: quicksort,
+            pivot_fn: Some(quicksort_with_rng),
+            gaps_fn: None,
+            shrink_fn: None,
+            radix_fn: None,
+            inner_fn: None,
+            max_key_range: None,
+        },
+        Algorithm {
+            name: "quicksort2",
+            aliases: &["quick2"],
+            stable: false,
+            in_place: true,
+            uses_buffers: false,
+            randomized: true,
+            best: "O(n log n)",
+            average: "O(n log n)",
+            worst: "O(n^2)",
+            sort_fn: quicksort2,
+            pivot_fn: Some(quicksort2_with_rng),
+            gaps_fn: None,
+            shrink_fn: None,
+            radix_fn: None,
+            inner_fn: None,
+            max_key_range: None,
+        },
+        Algorithm {
+            name: "quicksort3way",
+            aliases: &["3way", "dijkstra"],
+            stable: false,
+            in_place: true,
+            uses_buffers: false,
+            randomized: true,
+            best: "O(n)",
+            average: "O(n log n)",
+            worst: "O(n^2)",
+            sort_fn: quicksort3way,
+            pivot_fn: Some(quicksort3way_with_rng),
+            gaps_fn: None,
+            shrink_fn: None,
+            radix_fn: None,
+            inner_fn: None,
+            max_key_range: None,
+        },
+        Algorithm {
+            name: "rotationmergesort",
+            aliases: &["rotationmerge", "inplacemergesort"],
+            stable: true,
+            in_place: true,
+            uses_buffers: false,
+            randomized: false,
+            best: "O(n)",
+            average: "O(n log^2 n)",
+            worst: "O(n log^2 n)",
+            sort_fn: rotationmergesort,
+            pivot_fn: None,
+            gaps_fn: None,
+            shrink_fn: None,
+            radix_fn: None,
+            inner_fn: None,
+            max_key_range: None,
+        },
+        Algorithm {
+            name: "selectionsort",
+            aliases: &["selection"],
+            stable: false,
+            in_place: true,
+            uses_buffers: false,
+            randomized: false,
+            best: "O(n^2)",
+            average: "O(n^2)",
+            worst: "O(n^2)",
+            sort_fn: selectionsort,
+            pivot_fn: None,
+            gaps_fn: None,
+            shrink_fn: None,
+            radix_fn: None,
+            inner_fn: None,
+            max_key_range: None,
+        },
+        Algorithm {
+            name: "shellsort",
+            aliases: &["shell"],
+            stable: false,
+            in_place: true,
+            uses_buffers: false,
+            randomized: false,
+            best: "O(n log n)",
+            average: "unknown",
+            worst: "unknown",
+            sort_fn: shellsort,
+            pivot_fn: None,
+            gaps_fn: Some(shellsort_with_gaps),
+            shrink_fn: None,
+            radix_fn: None,
+            inner_fn: None,
+            max_key_range: None,
+        },
+        Algorithm {
+            name: "smoothsort",
+            aliases: &["smooth"],
+            stable: false,
+            in_place: true,
+            uses_buffers: false,
+            randomized: false,
+            best: "O(n)",
+            average: "O(n log n)",
+            worst: "O(n log n)",
+            sort_fn: smoothsort,
+            pivot_fn: None,
+            gaps_fn: None,
+            shrink_fn: None,
+            radix_fn: None,
+            inner_fn: None,
+            max_key_range: None,
+        },
+        Algorithm {
+            name: "timsort",
+            aliases: &["tim"],
+            stable: true,
+            in_place: false,
+            uses_buffers: true,
+            randomized: false,
+            best: "O(n)",
+            average: "O(n log n)",
+            worst: "O(n log n)",
+            sort_fn: timsort,
+            pivot_fn: None,
+            gaps_fn: None,
+            shrink_fn: None,
+            radix_fn: None,
+            inner_fn: None,
+            max_key_range: None,
+        },
+        Algorithm {
+            name: "weakheapsort",
+            aliases: &["weakheap"],
+            stable: false,
+            in_place: true,
+            uses_buffers: false,
+            randomized: false,
+            best: "O(n log n)",
+            average: "O(n log n)",
+            worst: "O(n log n)",
+            sort_fn: weakheapsort,
+            pivot_fn: None,
+            gaps_fn: None,
+            shrink_fn: None,
+            radix_fn: None,
+            inner_fn: None,
+            max_key_range: None,
+        },
+    ]
+}
+
+/// Find an algorithm by its name or one of its aliases
+pub fn find_algorithm<T>(name: &str) -> Option<Algorithm<T>>
+where
+    T: RadixKey + std::fmt::Display,
+{
+    algorithms()
+        .into_iter()
+        .find(|algorithm| algorithm.matches(name))
+}
+
+/// Suggest the closest algorithm name or alias for a misspelled name
+pub fn suggest_algorithm(name: &str) -> Option<&'static str> {
+    algorithms::<u32>()
+        .iter()
+        .flat_map(|algorithm| std::iter::once(&algorithm.name).chain(algorithm.aliases))
+        .map(|&candidate| (edit_distance(name, candidate), candidate))
+        .filter(|&(distance, candidate)| distance <= candidate.len() / 3 + 1)
+        .min_by_key(|&(distance, _)| distance)
+        .map(|(_, candidate)| candidate)
+}
+
+/// Levenshtein distance between two strings
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, &cb) in b.iter().enumerate() {
+            let substitution = diagonal + if ca == cb { 0 } else { 1 };
+            diagonal = row[j + 1];
+            row[j + 1] = substitution.min(row[j] + 1).min(diagonal + 1);
+        }
+    }
+    row[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    #[test]
+    fn test_registry_sorts() {
+        for algorithm in algorithms::<u32>() {
+            let mut test_slice = vec![1, 4, 123, 7, 8, 4, 8, 8, 2, 4, 57, 8, 324, 213];
+            let mut test_slice2 = test_slice.clone();
+            test_slice.sort_unstable();
+            (algorithm.sort_fn)(&mut test_slice2);
+            assert_eq!(test_slice, test_slice2, "{}", algorithm.name);
+        }
+    }
+    #[test]
+    fn test_registry_uses_buffers() {
+        // Long enough for timsort to merge runs
+        let mut rng = rand::rngs::StdRng::seed_from_u64(7);
+        let data = Distribution::Uniform.generate(200, 1..=1000, &mut rng);
+        for algorithm in algorithms::<u32>() {
+            let mut test_slice = data.clone();
+            let mut list = TracingList::new(&mut test_slice);
+            (algorithm.sort_fn)(&mut list);
+            let steps = list.into_steps();
+            let allocates = |histograms| {
+                steps.iter().any(|step| match step.operation {
+                    Operation::Alloc(_, _) => true,
+                    Operation::AllocHistogram(_, _) => histograms,
+                    _ => false,
+                })
+            };
+            assert_eq!(
+                algorithm.uses_buffers,
+                allocates(true),
+                "{}",
+                algorithm.name
+            );
+            assert_eq!(algorithm.in_place, !allocates(false), "{}", algorithm.name);
+        }
+    }
+    #[test]
+    fn test_seeded_runs() {
+        // Generate, shuffle and sort with a single generator, like sortgui --seed does
+        let run = |algorithm: &Algorithm<u32>, seed| {
+            let mut rng = rand::rngs::StdRng::seed_from_u64(seed);
+            let mut test_slice = Distribution::Uniform.generate(200, 1..=1000, &mut rng);
+            let mut list = TracingList::new(&mut test_slice);
+            Shuffle::FisherYates.shuffle(&mut list, &mut rng);
+            (algorithm.pivot_fn.unwrap())(&mut list, PivotStrategy::Random, &mut rng);
+            list.into_steps()
+        };
+        for algorithm in algorithms::<u32>().iter().filter(|a| a.randomized) {
+            assert_eq!(run(algorithm, 7), run(algorithm, 7), "{}", algorithm.name);
+            assert_ne!(run(algorithm, 7), run(algorithm, 8), "{}", algorithm.name);
+        }
+    }
+    #[test]
+    fn test_find_algorithm() {
+        assert_eq!(find_algorithm::<u32>("heap").unwrap().name, "heapsort");
+        assert_eq!(
+            find_algorithm::<u32>("quicksort2").unwrap().name,
+            "quicksort2"
+        );
+        assert!(find_algorithm::<u32>("bogosort").is_none());
+    }
+    #[test]
+    fn test_suggest_algorithm() {
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+        assert_eq!(suggest_algorithm("quiksort"), Some("quicksort"));
+        assert_eq!(suggest_algorithm("heapsrot"), Some("heapsort"));
+        assert_eq!(suggest_algorithm("xyz"), None);
+    }
+}
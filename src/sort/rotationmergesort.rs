@@ -0,0 +1,118 @@
+use super::{insertionsort_range, Highlight, List};
+use std::cmp::Ordering;
+
+/// Ranges smaller than this are insertion sorted
+const INSERTION_SORT_THRESHOLD: usize = 16;
+
+/// First index in `begin..end` whose element is not smaller than the one at `index`
+pub(crate) fn lower_bound<T>(list: &dyn List<T>, begin: usize, end: usize, index: usize) -> usize
+where
+    T: Copy + Ord + std::fmt::Display,
+{
+    let (mut low, mut high) = (begin, end);
+    while low < high {
+        let middle = low + (high - low) / 2;
+        if list.compare(middle, index) == Ordering::Less {
+            low = middle + 1;
+        } else {
+            high = middle;
+        }
+    }
+    low
+}
+
+/// First index in `begin..end` whose element is greater than the one at `index`
+pub(crate) fn upper_bound<T>(list: &dyn List<T>, begin: usize, end: usize, index: usize) -> usize
+where
+    T: Copy + Ord + std::fmt::Display,
+{
+    let (mut low, mut high) = (begin, end);
+    while low < high {
+        let middle = low + (high - low) / 2;
+        if list.compare(middle, index) == Ordering::Greater {
+            high = middle;
+        } else {
+            low = middle + 1;
+        }
+    }
+    low
+}
+
+/// Merge the sorted ranges `begin..middle` and `middle..end` without a buffer. The larger
+/// range is cut in half, the matching cut of the other range is found by binary search,
+/// and rotating the elements between both cuts leaves two smaller merges.
+fn merge<T>(list: &mut dyn List<T>, begin: usize, middle: usize, end: usize)
+where
+    T: Copy + Ord + std::fmt::Display,
+{
+    if begin == middle || middle == end || list.compare(middle - 1, middle) != Ordering::Greater {
+        return;
+    }
+    if end - begin == 2 {
+        list.swap(begin, middle);
+        return;
+    }
+    let (first_cut, second_cut) = if middle - begin > end - middle {
+        let first_cut = begin + (middle - begin) / 2;
+        (first_cut, lower_bound(list, middle, end, first_cut))
+    } else {
+        let second_cut = middle + (end - middle) / 2;
+        (upper_bound(list, begin, middle, second_cut), second_cut)
+    };
+    list.rotate_range(first_cut, second_cut, middle - first_cut);
+    let new_middle = first_cut + (second_cut - middle);
+    merge(list, begin, first_cut, new_middle);
+    merge(list, new_middle, second_cut, end);
+}
+
+fn real_rotationmergesort<T>(list: &mut dyn List<T>, begin: usize, end: usize)
+where
+    T: Copy + Ord + std::fmt::Display,
+{
+    if end - begin < INSERTION_SORT_THRESHOLD {
+        insertionsort_range(list, begin, end);
+        return;
+    }
+    let middle = begin + (end - begin) / 2;
+    real_rotationmergesort(list, begin, middle);
+    real_rotationmergesort(list, middle, end);
+    list.highlight(begin, end, Highlight::Merge);
+    merge(list, begin, middle, end);
+    list.highlight(begin, end, Highlight::None);
+}
+
+/// Stable mergesort without buffers, merging by rotations
+pub fn rotationmergesort<T>(list: &mut dyn List<T>)
+where
+    T: Copy + Ord + std::fmt::Display,
+{
+    let length = list.len();
+    real_rotationmergesort(list, 0, length);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tagged::{is_sorted_stably, tagged};
+    use crate::CountingList;
+    #[test]
+    fn test_rotationmergesort() {
+        let mut test_slice = vec![1, 4, 123, 7, 8, 4, 8, 8, 2, 4, 57, 8, 324, 213];
+        let mut test_slice2 = test_slice.clone();
+        assert_eq!(test_slice, test_slice2);
+        test_slice.sort_unstable();
+        rotationmergesort(&mut test_slice2);
+        assert_eq!(test_slice, test_slice2);
+        println!("{:?}", test_slice2);
+    }
+    #[test]
+    fn test_rotationmergesort_stable() {
+        let keys: Vec<u32> = (0..1000).map(|i| (i * 7919) % 13).collect();
+        let test_slice = tagged(&keys);
+        let mut test_slice2 = test_slice.clone();
+        let mut list = CountingList::new(&mut test_slice2);
+        rotationmergesort(&mut list);
+        assert_eq!(list.statistics().allocs, 0);
+        assert!(is_sorted_stably(&test_slice, &test_slice2));
+    }
+}
@@ -0,0 +1,297 @@
+use super::{List, RadixKey};
+use std::cmp::Ordering;
+
+/// Outcome of searching a sorted list for a value
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct SearchResult {
+    /// Index of an element equal to the value, or the position found by bound queries
+    pub index: Option<usize>,
+    /// Number of elements read from the list
+    pub probes: usize,
+}
+
+/// A search algorithm over sorted lists, as listed by `searches`
+pub struct Search<T> {
+    pub name: &'static str,
+    pub search_fn: fn(&dyn List<T>, T) -> SearchResult,
+}
+
+pub fn searches<T>() -> Vec<Search<T>>
+where
+    T: RadixKey + std::fmt::Display,
+{
+    vec![
+        Search {
+            name: "binary",
+            search_fn: binary_search,
+        },
+        Search {
+            name: "interpolation",
+            search_fn: interpolation_search,
+        },
+        Search {
+            name: "exponential",
+            search_fn: exponential_search,
+        },
+        Search {
+            name: "fibonacci",
+            search_fn: fibonacci_search,
+        },
+        Search {
+            name: "lower_bound",
+            search_fn: lower_bound_search,
+        },
+        Search {
+            name: "upper_bound",
+            search_fn: upper_bound_search,
+        },
+    ]
+}
+
+/// Reads elements from a list, counting every probe
+struct Prober<'a, T> {
+    list: &'a dyn List<T>,
+    probes: usize,
+}
+impl<'a, T> Prober<'a, T>
+where
+    T: Copy + Ord,
+{
+    fn new(list: &'a dyn List<T>) -> Prober<'a, T> {
+        Prober { list, probes: 0 }
+    }
+    fn probe(&mut self, index: usize) -> T {
+        self.probes += 1;
+        self.list.get(index)
+    }
+    fn result(&self, index: Option<usize>) -> SearchResult {
+        SearchResult {
+            index,
+            probes: self.probes,
+        }
+    }
+}
+
+fn binary_search_range<T>(
+    prober: &mut Prober<T>,
+    begin: usize,
+    end: usize,
+    value: T,
+) -> Option<usize>
+where
+    T: Copy + Ord,
+{
+    let (mut low, mut high) = (begin, end);
+    while low < high {
+        let middle = low + (high - low) / 2;
+        match prober.probe(middle).cmp(&value) {
+            Ordering::Less => low = middle + 1,
+            Ordering::Equal => return Some(middle),
+            Ordering::Greater => high = middle,
+        }
+    }
+    None
+}
+
+/// Halve the range around the middle element until the value is found
+pub fn binary_search<T>(list: &dyn List<T>, value: T) -> SearchResult
+where
+    T: Copy + Ord + std::fmt::Display,
+{
+    let mut prober = Prober::new(list);
+    let index = binary_search_range(&mut prober, 0, list.len(), value);
+    prober.result(index)
+}
+
+/// Guess the position of the value from the keys at both ends of the range, assuming
+/// evenly distributed keys. Takes O(log log n) probes on uniform data but O(n) at worst.
+pub fn interpolation_search<T>(list: &dyn List<T>, value: T) -> SearchResult
+where
+    T: RadixKey + std::fmt::Display,
+{
+    let mut prober = Prober::new(list);
+    if list.is_empty() {
+        return prober.result(None);
+    }
+    let (mut low, mut high) = (0, list.len() - 1);
+    while low <= high {
+        let low_value = prober.probe(low);
+        let high_value = if high == low {
+            low_value
+        } else {
+            prober.probe(high)
+        };
+        if value < low_value || value > high_value {
+            break;
+        }
+        if low_value == high_value {
+            return prober.result(Some(low));
+        }
+        let (key, low_key, high_key) = (
+            value.radix_key() as u128,
+            low_value.radix_key() as u128,
+            high_value.radix_key() as u128,
+        );
+        let position =
+            low + ((key - low_key) * (high - low) as u128 / (high_key - low_key)) as usize;
+        // A guess that is too high is past low, as the element at low is not greater
+        match prober.probe(position).cmp(&value) {
+            Ordering::Less => low = position + 1,
+            Ordering::Equal => return prober.result(Some(position)),
+            Ordering::Greater => high = position - 1,
+        }
+    }
+    prober.result(None)
+}
+
+/// Double a bound until it passes the value (galloping), then binary search the last step
+pub fn exponential_search<T>(list: &dyn List<T>, value: T) -> SearchResult
+where
+    T: Copy + Ord + std::fmt::Display,
+{
+    let mut prober = Prober::new(list);
+    let length = list.len();
+    let mut bound = 1;
+    while bound <= length {
+        match prober.probe(bound - 1).cmp(&value) {
+            Ordering::Less => bound *= 2,
+            Ordering::Equal => return prober.result(Some(bound - 1)),
+            Ordering::Greater => break,
+        }
+    }
+    let begin = bound / 2;
+    let end = std::cmp::min(bound - 1, length);
+    let index = binary_search_range(&mut prober, begin, end, value);
+    prober.result(index)
+}
+
+/// Split the range at Fibonacci numbers instead of halves, which only needs additions
+pub fn fibonacci_search<T>(list: &dyn List<T>, value: T) -> SearchResult
+where
+    T: Copy + Ord + std::fmt::Display,
+{
+    let mut prober = Prober::new(list);
+    let length = list.len();
+    // Smallest Fibonacci number not less than the length, and the two before it
+    let (mut fib2, mut fib1, mut fib) = (0, 1, 1);
+    while fib < length {
+        fib2 = fib1;
+        fib1 = fib;
+        fib = fib1 + fib2;
+    }
+    // Elements before offset are known to be smaller than the value
+    let mut offset = 0;
+    while fib > 1 {
+        let index = std::cmp::min(offset + fib2, length) - 1;
+        match prober.probe(index).cmp(&value) {
+            Ordering::Less => {
+                fib = fib1;
+                fib1 = fib2;
+                fib2 = fib - fib1;
+                offset = index + 1;
+            }
+            Ordering::Equal => return prober.result(Some(index)),
+            Ordering::Greater => {
+                fib = fib2;
+                fib1 -= fib2;
+                fib2 = fib - fib1;
+            }
+        }
+    }
+    if fib1 == 1 && offset < length && prober.probe(offset) == value {
+        return prober.result(Some(offset));
+    }
+    prober.result(None)
+}
+
+/// First index whose element is not less than the value
+pub fn lower_bound_search<T>(list: &dyn List<T>, value: T) -> SearchResult
+where
+    T: Copy + Ord + std::fmt::Display,
+{
+    let mut prober = Prober::new(list);
+    let (mut low, mut high) = (0, list.len());
+    while low < high {
+        let middle = low + (high - low) / 2;
+        if prober.probe(middle) < value {
+            low = middle + 1;
+        } else {
+            high = middle;
+        }
+    }
+    prober.result(Some(low))
+}
+
+/// First index whose element is greater than the value
+pub fn upper_bound_search<T>(list: &dyn List<T>, value: T) -> SearchResult
+where
+    T: Copy + Ord + std::fmt::Display,
+{
+    let mut prober = Prober::new(list);
+    let (mut low, mut high) = (0, list.len());
+    while low < high {
+        let middle = low + (high - low) / 2;
+        if prober.probe(middle) > value {
+            high = middle;
+        } else {
+            low = middle + 1;
+        }
+    }
+    prober.result(Some(low))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::CountingList;
+    #[test]
+    fn test_searches() {
+        let mut test_slice = vec![1, 4, 123, 7, 8, 4, 8, 8, 2, 4, 57, 8, 324, 213];
+        test_slice.sort_unstable();
+        for search in searches::<u32>().iter() {
+            for value in 0..330 {
+                let result = (search.search_fn)(&test_slice, value);
+                let lower = test_slice.iter().filter(|&&e| e < value).count();
+                let upper = test_slice.iter().filter(|&&e| e <= value).count();
+                match search.name {
+                    "lower_bound" => assert_eq!(result.index, Some(lower)),
+                    "upper_bound" => assert_eq!(result.index, Some(upper)),
+                    _ => match result.index {
+                        Some(index) => assert_eq!(test_slice[index], value),
+                        None => assert_eq!(lower, upper, "{} missed {}", search.name, value),
+                    },
+                }
+            }
+        }
+    }
+    #[test]
+    fn test_searches_empty() {
+        let test_slice: Vec<u32> = Vec::new();
+        for search in searches::<u32>().iter() {
+            let result = (search.search_fn)(&test_slice, 1);
+            assert_eq!(result.probes, 0);
+        }
+    }
+    #[test]
+    fn test_search_probes() {
+        let mut test_slice: Vec<u32> = (0..1000).map(|i| i * 3).collect();
+        for search in searches::<u32>().iter() {
+            for &value in [0, 1, 1500, 2997, 3000].iter() {
+                let list = CountingList::new(&mut test_slice);
+                let result = (search.search_fn)(&list, value);
+                // Probes are reads through the list
+                assert_eq!(result.probes, list.statistics().gets);
+                assert!(result.probes <= 2 * 11, "{} {}", search.name, result.probes);
+            }
+        }
+        // Evenly spaced keys are found with a single guess
+        let result = interpolation_search(&test_slice, 1500);
+        assert_eq!(
+            result,
+            SearchResult {
+                index: Some(500),
+                probes: 3
+            }
+        );
+    }
+}
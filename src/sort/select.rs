@@ -0,0 +1,308 @@
+use super::introsort::hoare_partition;
+use super::quicksort::partition;
+use super::{heapsort_range, insertionsort_range, log2, Highlight, List, PivotStrategy};
+use std::cmp::Ordering;
+
+use rand::RngCore;
+
+/// Ranges with fewer elements are insertion sorted by introselect
+const INSERTION_SORT_THRESHOLD: usize = 16;
+
+/// Size of the groups whose medians are used to choose a pivot by median of medians
+const GROUP_SIZE: usize = 5;
+
+/// Sort each group of five in `begin..end` and move the group medians to the front of the range.
+/// Returns the index of the median of those medians, which has at least 30% of the range on each side.
+fn median_of_medians<T>(list: &mut dyn List<T>, begin: usize, end: usize) -> usize
+where
+    T: Copy + Ord + std::fmt::Display,
+{
+    let mut medians = begin;
+    let mut group = begin;
+    while group < end {
+        let group_end = std::cmp::min(group + GROUP_SIZE, end);
+        insertionsort_range(list, group, group_end);
+        let median = group + (group_end - group) / 2;
+        if medians != median {
+            list.swap(medians, median);
+        }
+        medians += 1;
+        group = group_end;
+    }
+    let middle = begin + (medians - begin) / 2;
+    linear_select(list, begin, medians, middle);
+    middle
+}
+
+/// Worst case linear selection of the `k`-th element of `begin..end` (BFPRT)
+fn linear_select<T>(list: &mut dyn List<T>, begin: usize, end: usize, k: usize)
+where
+    T: Copy + Ord + std::fmt::Display,
+{
+    let mut begin = begin;
+    let mut end = end;
+    while end - begin > GROUP_SIZE {
+        let pivot = median_of_medians(list, begin, end);
+        let pivot = hoare_partition(list, begin, end - 1, pivot);
+        match k.cmp(&pivot) {
+            Ordering::Less => end = pivot,
+            Ordering::Equal => return,
+            Ordering::Greater => begin = pivot + 1,
+        }
+    }
+    insertionsort_range(list, begin, end);
+}
+
+fn real_quickselect<T>(
+    list: &mut dyn List<T>,
+    begin: usize,
+    end: usize,
+    k: usize,
+    pivot_strategy: PivotStrategy,
+    rng: &mut dyn RngCore,
+) where
+    T: Copy + Ord + std::fmt::Display,
+{
+    let mut begin = begin;
+    let mut end = end;
+    while begin < end {
+        // Partition elements and keep only the side containing k
+        let pivot = partition(list, begin, end, pivot_strategy, rng);
+        match k.cmp(&pivot) {
+            Ordering::Less => end = pivot - 1,
+            Ordering::Equal => return,
+            Ordering::Greater => begin = pivot + 1,
+        }
+    }
+}
+
+fn real_introselect<T>(
+    list: &mut dyn List<T>,
+    begin: usize,
+    end: usize,
+    k: usize,
+    depth_limit: usize,
+    pivot_strategy: PivotStrategy,
+    rng: &mut dyn RngCore,
+) where
+    T: Copy + Ord + std::fmt::Display,
+{
+    let mut begin = begin;
+    let mut end = end;
+    let mut depth_limit = depth_limit;
+    while end - begin > INSERTION_SORT_THRESHOLD {
+        // Quickselect is going quadratic, switch to median of medians
+        if depth_limit == 0 {
+            list.highlight(begin, end, Highlight::Fallback);
+            linear_select(list, begin, end, k);
+            list.highlight(begin, end, Highlight::None);
+            return;
+        }
+        depth_limit -= 1;
+        let pivot = pivot_strategy.select(list, begin, end - 1, rng);
+        let pivot = hoare_partition(list, begin, end - 1, pivot);
+        match k.cmp(&pivot) {
+            Ordering::Less => end = pivot,
+            Ordering::Equal => return,
+            Ordering::Greater => begin = pivot + 1,
+        }
+    }
+    insertionsort_range(list, begin, end);
+}
+
+pub fn quickselect<T>(list: &mut dyn List<T>, k: usize)
+where
+    T: Copy + Ord + std::fmt::Display,
+{
+    quickselect_with_pivot(list, k, PivotStrategy::Random);
+}
+
+/// Move the `k`-th smallest element to index `k`, partitioning like quicksort
+/// but only continuing into the side that contains `k`
+pub fn quickselect_with_pivot<T>(list: &mut dyn List<T>, k: usize, pivot_strategy: PivotStrategy)
+where
+    T: Copy + Ord + std::fmt::Display,
+{
+    quickselect_with_rng(list, k, pivot_strategy, &mut pivot_strategy.make_rng());
+}
+
+/// Variant of `quickselect_with_pivot` drawing its random pivots from `rng`
+pub fn quickselect_with_rng<T>(
+    list: &mut dyn List<T>,
+    k: usize,
+    pivot_strategy: PivotStrategy,
+    rng: &mut dyn RngCore,
+) where
+    T: Copy + Ord + std::fmt::Display,
+{
+    let length = list.len();
+    assert!(k < length, "Index {} out of range for length {}", k, length);
+    real_quickselect(list, 0, length - 1, k, pivot_strategy, rng);
+}
+
+pub fn introselect<T>(list: &mut dyn List<T>, k: usize)
+where
+    T: Copy + Ord + std::fmt::Display,
+{
+    introselect_with_pivot(list, k, PivotStrategy::MedianOfThree);
+}
+
+/// Quickselect that falls back to median of medians pivots when it partitions too many times
+pub fn introselect_with_pivot<T>(list: &mut dyn List<T>, k: usize, pivot_strategy: PivotStrategy)
+where
+    T: Copy + Ord + std::fmt::Display,
+{
+    introselect_with_rng(list, k, pivot_strategy, &mut pivot_strategy.make_rng());
+}
+
+/// Variant of `introselect_with_pivot` drawing its random pivots from `rng`
+pub fn introselect_with_rng<T>(
+    list: &mut dyn List<T>,
+    k: usize,
+    pivot_strategy: PivotStrategy,
+    rng: &mut dyn RngCore,
+) where
+    T: Copy + Ord + std::fmt::Display,
+{
+    let length = list.len();
+    assert!(k < length, "Index {} out of range for length {}", k, length);
+    let depth_limit = 2 * log2(length);
+    real_introselect(list, 0, length, k, depth_limit, pivot_strategy, rng);
+}
+
+/// Move the `k`-th smallest element to index `k`, with no greater elements before it
+/// and no smaller elements after it
+pub fn nth_element<T>(list: &mut dyn List<T>, k: usize)
+where
+    T: Copy + Ord + std::fmt::Display,
+{
+    introselect(list, k);
+}
+
+/// Sort the `k` smallest elements into `0..k`, leaving the rest in unspecified order
+pub fn partial_sort<T>(list: &mut dyn List<T>, k: usize)
+where
+    T: Copy + Ord + std::fmt::Display,
+{
+    let length = list.len();
+    assert!(
+        k <= length,
+        "Count {} out of range for length {}",
+        k,
+        length
+    );
+    if k == 0 {
+        return;
+    }
+    if k < length {
+        nth_element(list, k);
+    }
+    heapsort_range(list, 0, k);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{CountingList, Operation, TracingList};
+    fn check_selected(list: &[u32], k: usize, expected: &[u32]) {
+        assert_eq!(list[k], expected[k]);
+        assert!(list[..k].iter().all(|&e| e <= list[k]));
+        assert!(list[k + 1..].iter().all(|&e| e >= list[k]));
+    }
+    #[test]
+    fn test_quickselect() {
+        let test_slice = vec![1, 4, 123, 7, 8, 4, 8, 8, 2, 4, 57, 8, 324, 213];
+        let mut expected = test_slice.clone();
+        expected.sort_unstable();
+        for k in 0..test_slice.len() {
+            let mut test_slice2 = test_slice.clone();
+            quickselect(&mut test_slice2, k);
+            check_selected(&test_slice2, k, &expected);
+            println!("{:?}", test_slice2);
+        }
+    }
+    #[test]
+    fn test_quickselect_with_pivot() {
+        let strategies = [
+            PivotStrategy::First,
+            PivotStrategy::Last,
+            PivotStrategy::Middle,
+            PivotStrategy::MedianOfThree,
+            PivotStrategy::Ninther,
+            PivotStrategy::SeededRandom(42),
+        ];
+        let test_slice: Vec<u32> = (0..100).map(|i| (i * 7919) % 37).collect();
+        let mut expected = test_slice.clone();
+        expected.sort_unstable();
+        for &pivot_strategy in strategies.iter() {
+            for k in 0..test_slice.len() {
+                let mut test_slice2 = test_slice.clone();
+                quickselect_with_pivot(&mut test_slice2, k, pivot_strategy);
+                check_selected(&test_slice2, k, &expected);
+                let mut test_slice2 = test_slice.clone();
+                introselect_with_pivot(&mut test_slice2, k, pivot_strategy);
+                check_selected(&test_slice2, k, &expected);
+            }
+        }
+    }
+    #[test]
+    fn test_nth_element() {
+        let test_slice = vec![1, 4, 123, 7, 8, 4, 8, 8, 2, 4, 57, 8, 324, 213];
+        let mut expected = test_slice.clone();
+        expected.sort_unstable();
+        for k in 0..test_slice.len() {
+            let mut test_slice2 = test_slice.clone();
+            nth_element(&mut test_slice2, k);
+            check_selected(&test_slice2, k, &expected);
+            println!("{:?}", test_slice2);
+        }
+    }
+    #[test]
+    fn test_introselect_fallback() {
+        // Always choosing the first element as pivot is quadratic on sorted input
+        let mut test_slice: Vec<u32> = (0..1000).collect();
+        let mut tracing = TracingList::new(&mut test_slice);
+        let mut list = CountingList::new(&mut tracing);
+        introselect_with_pivot(&mut list, 900, PivotStrategy::First);
+        assert!(list.statistics().compares < 50_000);
+        assert!(tracing.into_steps().iter().any(|step| matches!(
+            step.operation,
+            Operation::Highlight(_, _, Highlight::Fallback)
+        )));
+        assert_eq!(test_slice[900], 900);
+    }
+    #[test]
+    fn test_linear_select() {
+        let inputs: Vec<Vec<u32>> = vec![
+            (0..1000).collect(),
+            (0..1000).rev().collect(),
+            (0..1000).map(|i| (i * 7919) % 1000).collect(),
+            vec![7; 1000],
+        ];
+        for input in inputs.iter() {
+            let mut expected = input.clone();
+            expected.sort_unstable();
+            for &k in [0, 1, 499, 500, 998, 999].iter() {
+                let mut test_slice = input.clone();
+                let mut list = CountingList::new(&mut test_slice);
+                linear_select(&mut list, 0, 1000, k);
+                assert!(list.statistics().compares < 10 * 1000);
+                check_selected(&test_slice, k, &expected);
+            }
+        }
+    }
+    #[test]
+    fn test_partial_sort() {
+        let test_slice = vec![1, 4, 123, 7, 8, 4, 8, 8, 2, 4, 57, 8, 324, 213];
+        let mut expected = test_slice.clone();
+        expected.sort_unstable();
+        for k in 0..=test_slice.len() {
+            let mut test_slice2 = test_slice.clone();
+            partial_sort(&mut test_slice2, k);
+            assert_eq!(test_slice2[..k], expected[..k]);
+            let mut rest = test_slice2[k..].to_vec();
+            rest.sort_unstable();
+            assert_eq!(rest, expected[k..]);
+        }
+    }
+}
@@ -18,7 +18,7 @@ impl Iterator for TokudaIterator {
     type Item = usize;
     fn next (&mut self) -> Option<Self::Item> {
         self.pow_value *= 2.25;
-        let value = (0.8f64 * (self.pow_value as f64 - 1.0)).ceil() as usize;
+        let value = (0.8f64 * (self.pow_value - 1.0)).ceil() as usize;
         if value <= self.max_value {
             Some(value)
         } else {
@@ -28,14 +28,161 @@ impl Iterator for TokudaIterator {
 }
 
 pub fn gaps_sequence(len: usize) -> impl Iterator<Item=usize> {
-    TokudaIterator::new(len).collect::<Vec<_>>().into_iter().rev()
+    GapSequence::Tokuda.gaps(len.saturating_add(1)).into_iter()
+}
+
+/// Sequence of gaps used by shellsort, from largest to smallest
+#[derive(Clone, PartialEq, Debug)]
+pub enum GapSequence {
+    /// Shell (1959): N/2, N/4, ..., 1
+    Shell,
+    /// Knuth (1973): 1, 4, 13, 40, ... (3^k - 1) / 2
+    Knuth,
+    /// Hibbard (1963): 1, 3, 7, 15, ... 2^k - 1
+    Hibbard,
+    /// Sedgewick (1986): 1, 5, 19, 41, 109, ...
+    Sedgewick,
+    /// Pratt (1971): 1, 2, 3, 4, 6, 8, 9, 12, ... 2^p 3^q
+    Pratt,
+    /// Ciura (2001): 1, 4, 10, 23, 57, 132, 301, 701, extended by a factor of 2.25
+    Ciura,
+    /// Tokuda (1992): 1, 4, 9, 20, 46, 103, ...
+    Tokuda,
+    /// User supplied gaps
+    Custom(Vec<usize>),
+}
+impl GapSequence {
+    pub const NAMES: &'static [&'static str] = &[
+        "shell",
+        "knuth",
+        "hibbard",
+        "sedgewick",
+        "pratt",
+        "ciura",
+        "tokuda",
+        "GAP,GAP,...",
+    ];
+    /// Gaps smaller than `len` in decreasing order, always ending with 1
+    pub fn gaps(&self, len: usize) -> Vec<usize> {
+        let mut gaps = match self {
+            GapSequence::Shell => {
+                let mut gaps = Vec::new();
+                let mut gap = len / 2;
+                while gap > 0 {
+                    gaps.push(gap);
+                    gap /= 2;
+                }
+                gaps.reverse();
+                gaps
+            }
+            GapSequence::Knuth => increasing(len, (1..).map(|k| (3usize.pow(k) - 1) / 2)),
+            GapSequence::Hibbard => increasing(len, (1..).map(|k| 2usize.pow(k) - 1)),
+            GapSequence::Sedgewick => increasing(
+                len,
+                (0..).flat_map(|i| {
+                    let (power2, power4) = (2usize.pow(i), 4usize.pow(i));
+                    vec![9 * (power4 - power2) + 1, 16 * power4 - 12 * power2 + 1]
+                }),
+            ),
+            GapSequence::Pratt => {
+                let mut gaps = Vec::new();
+                let mut power2 = 1;
+                while power2 < len {
+                    let mut gap = power2;
+                    while gap < len {
+                        gaps.push(gap);
+                        gap *= 3;
+                    }
+                    power2 *= 2;
+                }
+                gaps.sort_unstable();
+                gaps
+            }
+            GapSequence::Ciura => {
+                let mut gaps = vec![1, 4, 10, 23, 57, 132, 301, 701];
+                while *gaps.last().unwrap() < len {
+                    let next = (*gaps.last().unwrap() as f64 * 2.25) as usize;
+                    gaps.push(next);
+                }
+                gaps
+            }
+            GapSequence::Tokuda => TokudaIterator::new(len.saturating_sub(1)).collect(),
+            GapSequence::Custom(gaps) => {
+                let mut gaps = gaps.clone();
+                gaps.sort_unstable();
+                gaps.dedup();
+                gaps
+            }
+        };
+        gaps.retain(|&gap| gap > 0 && gap < len);
+        if gaps.first() != Some(&1) {
+            gaps.insert(0, 1);
+        }
+        gaps.reverse();
+        gaps
+    }
+}
+impl std::fmt::Display for GapSequence {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
+        match self {
+            GapSequence::Shell => write!(f, "shell"),
+            GapSequence::Knuth => write!(f, "knuth"),
+            GapSequence::Hibbard => write!(f, "hibbard"),
+            GapSequence::Sedgewick => write!(f, "sedgewick"),
+            GapSequence::Pratt => write!(f, "pratt"),
+            GapSequence::Ciura => write!(f, "ciura"),
+            GapSequence::Tokuda => write!(f, "tokuda"),
+            GapSequence::Custom(gaps) => {
+                let gaps = gaps.iter().map(|gap| gap.to_string()).collect::<Vec<_>>();
+                write!(f, "{}", gaps.join(","))
+            }
+        }
+    }
+}
+impl std::str::FromStr for GapSequence {
+    type Err = String;
+    fn from_str(name: &str) -> Result<GapSequence, String> {
+        match name {
+            "shell" => Ok(GapSequence::Shell),
+            "knuth" => Ok(GapSequence::Knuth),
+            "hibbard" => Ok(GapSequence::Hibbard),
+            "sedgewick" => Ok(GapSequence::Sedgewick),
+            "pratt" => Ok(GapSequence::Pratt),
+            "ciura" => Ok(GapSequence::Ciura),
+            "tokuda" => Ok(GapSequence::Tokuda),
+            _ if name.starts_with(|c: char| c.is_ascii_digit()) => name
+                .split(',')
+                .map(|gap| match gap.trim().parse::<usize>() {
+                    Ok(gap) if gap > 0 => Ok(gap),
+                    _ => Err(format!("Cannot parse \"{}\" as a positive gap", gap)),
+                })
+                .collect::<Result<Vec<_>, _>>()
+                .map(GapSequence::Custom),
+            _ => Err(format!("Gap sequence \"{}\" not found", name)),
+        }
+    }
+}
+
+/// Collect the values of an increasing sequence up to `len`
+fn increasing<I>(len: usize, sequence: I) -> Vec<usize>
+where
+    I: Iterator<Item = usize>,
+{
+    sequence.take_while(|&gap| gap < len).collect()
 }
 
 pub fn shellsort<T>(list: &mut dyn List<T>)
 where
     T: Copy + Ord + std::fmt::Display,
 {
-    for gap in gaps_sequence(list.len() - 1) {
+    shellsort_with_gaps(list, &GapSequence::Tokuda);
+}
+
+pub fn shellsort_with_gaps<T>(list: &mut dyn List<T>, gap_sequence: &GapSequence)
+where
+    T: Copy + Ord + std::fmt::Display,
+{
+    for gap in gap_sequence.gaps(list.len()) {
         for i in gap..list.len() {
             let temp = list.get(i);
             let mut j = i;
@@ -58,6 +205,47 @@ mod tests {
         assert_eq!(expected, actual);
     }
     #[test]
+    fn test_gap_sequences() {
+        let gaps = |sequence: GapSequence, len| {
+            let mut gaps = sequence.gaps(len);
+            gaps.reverse();
+            gaps
+        };
+        assert_eq!(gaps(GapSequence::Shell, 20), vec![1, 2, 5, 10]);
+        assert_eq!(gaps(GapSequence::Knuth, 130), vec![1, 4, 13, 40, 121]);
+        assert_eq!(gaps(GapSequence::Hibbard, 40), vec![1, 3, 7, 15, 31]);
+        assert_eq!(
+            gaps(GapSequence::Sedgewick, 1000),
+            vec![1, 5, 19, 41, 109, 209, 505, 929]
+        );
+        assert_eq!(
+            gaps(GapSequence::Pratt, 20),
+            vec![1, 2, 3, 4, 6, 8, 9, 12, 16, 18]
+        );
+        assert_eq!(
+            gaps(GapSequence::Ciura, 4000),
+            vec![1, 4, 10, 23, 57, 132, 301, 701, 1577, 3548]
+        );
+        assert_eq!(gaps(GapSequence::Tokuda, 104), vec![1, 4, 9, 20, 46, 103]);
+        assert_eq!(
+            gaps(GapSequence::Custom(vec![10, 3, 3, 50]), 20),
+            vec![1, 3, 10]
+        );
+        assert_eq!(gaps(GapSequence::Shell, 0), vec![1]);
+    }
+    #[test]
+    fn test_gap_sequence_names() {
+        for &name in GapSequence::NAMES.iter().filter(|n| !n.contains("GAP")) {
+            assert_eq!(name.parse::<GapSequence>().unwrap().to_string(), name);
+        }
+        assert_eq!(
+            "1, 4,10".parse::<GapSequence>(),
+            Ok(GapSequence::Custom(vec![1, 4, 10]))
+        );
+        assert!("1,0".parse::<GapSequence>().is_err());
+        assert!("fibonacci".parse::<GapSequence>().is_err());
+    }
+    #[test]
     fn test_shellsort() {
         let mut test_slice = vec![1, 4, 123, 7, 8, 4, 8, 8, 2, 4, 57, 8, 324, 213];
         let mut test_slice2 = test_slice.clone();
@@ -67,4 +255,26 @@ mod tests {
         assert_eq!(test_slice, test_slice2);
         println!("{:?}", test_slice2);
     }
+    #[test]
+    fn test_shellsort_with_gaps() {
+        let sequences = [
+            GapSequence::Shell,
+            GapSequence::Knuth,
+            GapSequence::Hibbard,
+            GapSequence::Sedgewick,
+            GapSequence::Pratt,
+            GapSequence::Ciura,
+            GapSequence::Tokuda,
+            GapSequence::Custom(vec![7, 3]),
+        ];
+        for gap_sequence in sequences.iter() {
+            let mut test_slice = vec![1, 4, 123, 7, 8, 4, 8, 8, 2, 4, 57, 8, 324, 213];
+            let mut test_slice2 = test_slice.clone();
+            test_slice.sort_unstable();
+            shellsort_with_gaps(&mut test_slice2, gap_sequence);
+            assert_eq!(test_slice, test_slice2);
+        }
+        let mut empty: Vec<u32> = vec![];
+        shellsort_with_gaps(&mut empty, &GapSequence::Tokuda);
+    }
 }
@@ -0,0 +1,284 @@
+use super::List;
+
+use rand::RngCore;
+
+/// Riffles performed by the default riffle shuffle, enough to mix a deck of 52 cards
+const DEFAULT_RIFFLES: usize = 7;
+
+/// How to shuffle a list before sorting it
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum Shuffle {
+    FisherYates,
+    Sattolo,
+    Naive,
+    Riffle(usize),
+}
+impl Shuffle {
+    pub const NAMES: &'static [&'static str] =
+        &["fisheryates", "sattolo", "naive", "riffle", "riffle:COUNT"];
+    /// Whether the shuffle copies elements through an auxiliary buffer
+    pub fn uses_buffer(&self) -> bool {
+        matches!(self, Shuffle::Riffle(_))
+    }
+    pub fn shuffle<T>(&self, list: &mut dyn List<T>, rng: &mut dyn RngCore)
+    where
+        T: Copy + Ord + std::fmt::Display,
+    {
+        match *self {
+            Shuffle::FisherYates => fisher_yates_shuffle(list, rng),
+            Shuffle::Sattolo => sattolo_shuffle(list, rng),
+            Shuffle::Naive => naive_shuffle(list, rng),
+            Shuffle::Riffle(riffles) => {
+                for _ in 0..riffles {
+                    riffle(list, rng);
+                }
+            }
+        }
+    }
+}
+impl std::fmt::Display for Shuffle {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
+        match self {
+            Shuffle::FisherYates => write!(f, "fisheryates"),
+            Shuffle::Sattolo => write!(f, "sattolo"),
+            Shuffle::Naive => write!(f, "naive"),
+            Shuffle::Riffle(riffles) => write!(f, "riffle:{}", riffles),
+        }
+    }
+}
+impl std::str::FromStr for Shuffle {
+    type Err = String;
+    fn from_str(name: &str) -> Result<Shuffle, String> {
+        match name {
+            "fisheryates" => Ok(Shuffle::FisherYates),
+            "sattolo" => Ok(Shuffle::Sattolo),
+            "naive" => Ok(Shuffle::Naive),
+            "riffle" => Ok(Shuffle::Riffle(DEFAULT_RIFFLES)),
+            _ if name.starts_with("riffle:") => name["riffle:".len()..]
+                .parse::<usize>()
+                .map(Shuffle::Riffle)
+                .map_err(|_| format!("Cannot parse \"{}\" as a riffle count", name)),
+            _ => Err(format!("Shuffle \"{}\" not found", name)),
+        }
+    }
+}
+
+/// Random index in `0..bound`, which must be positive
+fn random_below(rng: &mut dyn RngCore, bound: usize) -> usize {
+    rng.next_u64() as usize % bound
+}
+
+/// Swap each element with a random one not after it, which makes every permutation equally likely
+pub fn fisher_yates_shuffle<T>(list: &mut dyn List<T>, rng: &mut dyn RngCore)
+where
+    T: Copy + Ord + std::fmt::Display,
+{
+    for i in (1..list.len()).rev() {
+        let j = random_below(rng, i + 1);
+        if i != j {
+            list.swap(i, j);
+        }
+    }
+}
+
+/// Like Fisher-Yates, but swapping each element with one strictly before it,
+/// which produces a random permutation made of a single cycle
+pub fn sattolo_shuffle<T>(list: &mut dyn List<T>, rng: &mut dyn RngCore)
+where
+    T: Copy + Ord + std::fmt::Display,
+{
+    for i in (1..list.len()).rev() {
+        let j = random_below(rng, i);
+        list.swap(i, j);
+    }
+}
+
+/// Swap each element with any random one. There are n^n equally likely choices,
+/// which cannot map evenly onto the n! permutations, so some are more likely than others.
+pub fn naive_shuffle<T>(list: &mut dyn List<T>, rng: &mut dyn RngCore)
+where
+    T: Copy + Ord + std::fmt::Display,
+{
+    let length = list.len();
+    for i in 0..length {
+        let j = random_below(rng, length);
+        if i != j {
+            list.swap(i, j);
+        }
+    }
+}
+
+/// One riffle of the Gilbert-Shannon-Reeds model: cut the list at a binomially distributed
+/// point, then interleave both halves, dropping from each with probability proportional to its size
+pub fn riffle<T>(list: &mut dyn List<T>, rng: &mut dyn RngCore)
+where
+    T: Copy + Ord + std::fmt::Display,
+{
+    let length = list.len();
+    if length < 2 {
+        return;
+    }
+    let mut cut = 0;
+    let mut remaining = length;
+    while remaining > 0 {
+        let bits = std::cmp::min(remaining, 64);
+        cut += (rng.next_u64() >> (64 - bits)).count_ones() as usize;
+        remaining -= bits;
+    }
+    let mut buffer = list.alloc_buffer("riffle", length);
+    for index in 0..length {
+        list.copy_to_buffer(index, &mut buffer, index);
+    }
+    let (mut left, mut right) = (0, cut);
+    for index in 0..length {
+        let left_remaining = cut - left;
+        let right_remaining = length - right;
+        if random_below(rng, left_remaining + right_remaining) < left_remaining {
+            list.copy_from_buffer(&buffer, left, index);
+            left += 1;
+        } else {
+            list.copy_from_buffer(&buffer, right, index);
+            right += 1;
+        }
+    }
+    list.free_buffer(buffer);
+}
+
+/// How often each element of `0..length` ends up at each position over many shuffles
+#[derive(Clone, PartialEq, Debug)]
+pub struct ShuffleBias {
+    pub trials: usize,
+    /// `counts[element][position]`
+    pub counts: Vec<Vec<usize>>,
+}
+impl ShuffleBias {
+    pub fn measure(
+        shuffle: Shuffle,
+        length: usize,
+        trials: usize,
+        rng: &mut dyn RngCore,
+    ) -> ShuffleBias {
+        let mut counts = vec![vec![0; length]; length];
+        for _ in 0..trials {
+            let mut list: Vec<usize> = (0..length).collect();
+            shuffle.shuffle(&mut list, rng);
+            for (position, &element) in list.iter().enumerate() {
+                counts[element][position] += 1;
+            }
+        }
+        ShuffleBias { trials, counts }
+    }
+    /// Count expected in every cell from an unbiased shuffle
+    pub fn expected(&self) -> f64 {
+        self.trials as f64 / self.counts.len() as f64
+    }
+    /// Pearson's chi-squared statistic of the counts against the uniform distribution.
+    /// For an unbiased shuffle it stays close to the (n - 1)^2 degrees of freedom.
+    pub fn chi_squared(&self) -> f64 {
+        let expected = self.expected();
+        self.counts
+            .iter()
+            .flatten()
+            .map(|&count| (count as f64 - expected).powi(2) / expected)
+            .sum()
+    }
+    /// Largest relative difference between a count and the expected one
+    pub fn max_deviation(&self) -> f64 {
+        let expected = self.expected();
+        self.counts
+            .iter()
+            .flatten()
+            .map(|&count| (count as f64 - expected).abs() / expected)
+            .fold(0.0, f64::max)
+    }
+}
+impl std::fmt::Display for ShuffleBias {
+    /// Percentage of trials in which each element (row) landed at each position (column)
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
+        for (element, counts) in self.counts.iter().enumerate() {
+            write!(f, "{:>5}:", element)?;
+            for &count in counts.iter() {
+                write!(f, " {:5.1}", 100.0 * count as f64 / self.trials as f64)?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::CountingList;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+    #[test]
+    fn test_shuffles() {
+        let shuffles = [
+            Shuffle::FisherYates,
+            Shuffle::Sattolo,
+            Shuffle::Naive,
+            Shuffle::Riffle(1),
+            Shuffle::Riffle(DEFAULT_RIFFLES),
+        ];
+        let mut rng = StdRng::seed_from_u64(42);
+        for &shuffle in shuffles.iter() {
+            let mut test_slice: Vec<u32> = (1..=100).collect();
+            shuffle.shuffle(&mut test_slice, &mut rng);
+            assert_ne!(test_slice, (1..=100).collect::<Vec<_>>());
+            test_slice.sort_unstable();
+            assert_eq!(test_slice, (1..=100).collect::<Vec<_>>());
+        }
+    }
+    #[test]
+    fn test_sattolo_single_cycle() {
+        let mut rng = StdRng::seed_from_u64(42);
+        for _ in 0..100 {
+            let mut test_slice: Vec<usize> = (0..20).collect();
+            sattolo_shuffle(&mut test_slice, &mut rng);
+            let mut position = 0;
+            let mut cycle_length = 0;
+            loop {
+                position = test_slice[position];
+                cycle_length += 1;
+                if position == 0 {
+                    break;
+                }
+            }
+            assert_eq!(cycle_length, 20);
+        }
+    }
+    #[test]
+    fn test_riffle_buffer() {
+        let mut test_slice: Vec<u32> = (1..=10).collect();
+        let mut list = CountingList::new(&mut test_slice);
+        Shuffle::Riffle(2).shuffle(&mut list, &mut StdRng::seed_from_u64(42));
+        let statistics = list.statistics();
+        assert_eq!(statistics.allocs, 2);
+        assert_eq!(statistics.copies_to_buffer, 20);
+        assert_eq!(statistics.copies_from_buffer, 20);
+    }
+    #[test]
+    fn test_shuffle_bias() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let fair = ShuffleBias::measure(Shuffle::FisherYates, 4, 20000, &mut rng);
+        assert_eq!(fair.counts.iter().flatten().sum::<usize>(), 4 * 20000);
+        assert!(fair.max_deviation() < 0.1);
+        // Well below the 99.9% quantile for 9 degrees of freedom
+        assert!(fair.chi_squared() < 27.9);
+        let naive = ShuffleBias::measure(Shuffle::Naive, 4, 20000, &mut rng);
+        assert!(naive.chi_squared() > 27.9);
+        // Sattolo never leaves an element in place
+        let sattolo = ShuffleBias::measure(Shuffle::Sattolo, 4, 1000, &mut rng);
+        assert!((0..4).all(|i| sattolo.counts[i][i] == 0));
+    }
+    #[test]
+    fn test_shuffle_names() {
+        for name in ["fisheryates", "sattolo", "naive", "riffle:3"].iter() {
+            assert_eq!(&name.parse::<Shuffle>().unwrap().to_string(), name);
+        }
+        assert_eq!("riffle".parse::<Shuffle>(), Ok(Shuffle::Riffle(7)));
+        assert!("riffle:x".parse::<Shuffle>().is_err());
+        assert!("bogo".parse::<Shuffle>().is_err());
+    }
+}
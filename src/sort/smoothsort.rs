@@ -0,0 +1,165 @@
+use super::List;
+use std::cmp::Ordering;
+
+/// Leonardo numbers L(k) = L(k - 1) + L(k - 2) + 1 up to the first one not smaller than `limit`
+fn leonardo_numbers(limit: usize) -> Vec<usize> {
+    let mut numbers = vec![1, 1];
+    while numbers[numbers.len() - 1] < limit {
+        let count = numbers.len();
+        numbers.push(numbers[count - 1] + numbers[count - 2] + 1);
+    }
+    numbers
+}
+
+/// Leonardo tree of order `order` whose root is the last element, at `root`.
+/// Its subtrees are the trees of order `order - 1` and `order - 2` just before the root.
+#[derive(Copy, Clone)]
+struct Tree {
+    root: usize,
+    order: usize,
+}
+impl Tree {
+    /// Roots of the left (larger) and right subtrees
+    fn children(&self, leonardo: &[usize]) -> Option<(Tree, Tree)> {
+        if self.order < 2 {
+            return None;
+        }
+        let right = Tree {
+            root: self.root - 1,
+            order: self.order - 2,
+        };
+        let left = Tree {
+            root: right.root - leonardo[right.order],
+            order: self.order - 1,
+        };
+        Some((left, right))
+    }
+}
+
+/// Restore the max-heap property of `tree`, whose subtrees are already heaps
+fn sift<T>(list: &mut dyn List<T>, leonardo: &[usize], tree: Tree)
+where
+    T: Copy + Ord + std::fmt::Display,
+{
+    let mut tree = tree;
+    while let Some((left, right)) = tree.children(leonardo) {
+        let child = if list.compare(left.root, right.root) == Ordering::Greater {
+            left
+        } else {
+            right
+        };
+        if list.compare(tree.root, child.root) != Ordering::Less {
+            break;
+        }
+        list.swap(tree.root, child.root);
+        tree = child;
+    }
+}
+
+/// Move the root of `trees[index]` left among the roots until they are in ascending order,
+/// then restore the heap property of the tree it ends up in
+fn rectify<T>(list: &mut dyn List<T>, leonardo: &[usize], trees: &[Tree], index: usize)
+where
+    T: Copy + Ord + std::fmt::Display,
+{
+    let mut index = index;
+    while index > 0 {
+        let tree = trees[index];
+        let previous = trees[index - 1].root;
+        if list.compare(previous, tree.root) != Ordering::Greater {
+            break;
+        }
+        // The previous root must also be larger than the children it would adopt
+        if let Some((left, right)) = tree.children(leonardo) {
+            if list.compare(previous, left.root) != Ordering::Greater
+                || list.compare(previous, right.root) != Ordering::Greater
+            {
+                break;
+            }
+        }
+        list.swap(previous, tree.root);
+        index -= 1;
+    }
+    sift(list, leonardo, trees[index]);
+}
+
+/// Dijkstra's smoothsort. The list is built into a forest of Leonardo heaps whose roots
+/// are in ascending order, which costs O(n) for input that is already nearly sorted.
+pub fn smoothsort<T>(list: &mut dyn List<T>)
+where
+    T: Copy + Ord + std::fmt::Display,
+{
+    let length = list.len();
+    if length < 2 {
+        return;
+    }
+    let leonardo = leonardo_numbers(length);
+    let mut trees: Vec<Tree> = Vec::new();
+    // Add each element to the forest, merging the last two trees if their orders are consecutive
+    for root in 0..length {
+        let count = trees.len();
+        if count >= 2 && trees[count - 2].order == trees[count - 1].order + 1 {
+            let order = trees[count - 2].order + 1;
+            trees.truncate(count - 2);
+            trees.push(Tree { root, order });
+        } else if count >= 1 && trees[count - 1].order == 1 {
+            trees.push(Tree { root, order: 0 });
+        } else {
+            trees.push(Tree { root, order: 1 });
+        }
+        rectify(list, &leonardo, &trees, trees.len() - 1);
+    }
+    // The last root is the maximum. Removing it exposes the roots of its subtrees.
+    while let Some(tree) = trees.pop() {
+        if let Some((left, right)) = tree.children(&leonardo) {
+            trees.push(left);
+            trees.push(right);
+            rectify(list, &leonardo, &trees, trees.len() - 2);
+            rectify(list, &leonardo, &trees, trees.len() - 1);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{heapsort, CountingList};
+    #[test]
+    fn test_smoothsort() {
+        let mut test_slice = vec![1, 4, 123, 7, 8, 4, 8, 8, 2, 4, 57, 8, 324, 213];
+        let mut test_slice2 = test_slice.clone();
+        assert_eq!(test_slice, test_slice2);
+        test_slice.sort_unstable();
+        smoothsort(&mut test_slice2);
+        assert_eq!(test_slice, test_slice2);
+        println!("{:?}", test_slice2);
+    }
+    #[test]
+    fn test_smoothsort_inputs() {
+        let sorted: Vec<u32> = (0..1000).collect();
+        let reversed: Vec<u32> = (0..1000).rev().collect();
+        let random: Vec<u32> = (0..1000).map(|i| (i * 7919) % 1000).collect();
+        for test_slice in [sorted.clone(), reversed, random].iter() {
+            let mut test_slice2 = test_slice.clone();
+            smoothsort(&mut test_slice2);
+            assert_eq!(test_slice2, sorted);
+        }
+    }
+    #[test]
+    fn test_smoothsort_adaptive() {
+        let mut test_slice: Vec<u32> = (0..1000).collect();
+        let mut list = CountingList::new(&mut test_slice);
+        smoothsort(&mut list);
+        let smoothsort_statistics = list.statistics();
+        let mut test_slice: Vec<u32> = (0..1000).collect();
+        let mut list = CountingList::new(&mut test_slice);
+        heapsort(&mut list);
+        let heapsort_statistics = list.statistics();
+        println!("smoothsort: {}", smoothsort_statistics);
+        println!("heapsort: {}", heapsort_statistics);
+        // Sorted input is already a valid forest, so nothing moves
+        assert_eq!(smoothsort_statistics.swaps, 0);
+        assert!(smoothsort_statistics.compares < 5 * 1000);
+        assert!(heapsort_statistics.compares > 10 * 1000);
+    }
+}
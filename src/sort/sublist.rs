@@ -0,0 +1,126 @@
+use super::{Buffer, Highlight, Histogram, List, RadixKey};
+
+/// View of the elements in `begin..end` of another `List`, so that a sort can run on part of
+/// a list. Indices are relative to `begin` and operations are forwarded to the underlying list.
+pub struct SubList<'a, T>
+where
+    T: Copy + Ord,
+{
+    list: &'a mut dyn List<T>,
+    begin: usize,
+    end: usize,
+}
+impl<'a, T> SubList<'a, T>
+where
+    T: Copy + Ord,
+{
+    pub fn new(list: &'a mut dyn List<T>, begin: usize, end: usize) -> SubList<'a, T> {
+        assert!(begin <= end && end <= list.len());
+        SubList { list, begin, end }
+    }
+}
+impl<'a, T> List<T> for SubList<'a, T>
+where
+    T: Copy + Ord,
+{
+    fn as_slice(&self) -> &[T] {
+        &self.list.as_slice()[self.begin..self.end]
+    }
+    fn as_mut_slice(&mut self) -> &mut [T] {
+        &mut self.list.as_mut_slice()[self.begin..self.end]
+    }
+    fn len(&self) -> usize {
+        self.end - self.begin
+    }
+    fn get(&self, index: usize) -> T {
+        self.list.get(self.begin + index)
+    }
+    fn set(&mut self, index: usize, value: T) {
+        self.list.set(self.begin + index, value);
+    }
+    fn compare(&self, a: usize, b: usize) -> std::cmp::Ordering {
+        self.list.compare(self.begin + a, self.begin + b)
+    }
+    fn swap(&mut self, a: usize, b: usize) {
+        self.list.swap(self.begin + a, self.begin + b);
+    }
+    fn reverse_range(&mut self, begin: usize, end: usize) {
+        self.list.reverse_range(self.begin + begin, self.begin + end);
+    }
+    fn rotate_range(&mut self, begin: usize, end: usize, k: usize) {
+        self.list.rotate_range(self.begin + begin, self.begin + end, k);
+    }
+    fn swap_blocks(&mut self, a: usize, b: usize, len: usize) {
+        self.list.swap_blocks(self.begin + a, self.begin + b, len);
+    }
+    fn alloc_buffer(&mut self, name: &'static str, len: usize) -> Buffer<T> {
+        self.list.alloc_buffer(name, len)
+    }
+    fn free_buffer(&mut self, buffer: Buffer<T>) {
+        self.list.free_buffer(buffer);
+    }
+    fn buffer_get(&self, buffer: &Buffer<T>, index: usize) -> T {
+        self.list.buffer_get(buffer, index)
+    }
+    fn buffer_set(&mut self, buffer: &mut Buffer<T>, index: usize, value: T) {
+        self.list.buffer_set(buffer, index, value);
+    }
+    fn copy_to_buffer(&mut self, index: usize, buffer: &mut Buffer<T>, buffer_index: usize) {
+        self.list
+            .copy_to_buffer(self.begin + index, buffer, buffer_index);
+    }
+    fn copy_from_buffer(&mut self, buffer: &Buffer<T>, buffer_index: usize, index: usize) {
+        self.list
+            .copy_from_buffer(buffer, buffer_index, self.begin + index);
+    }
+    fn highlight(&self, begin: usize, end: usize, highlight: Highlight) {
+        self.list
+            .highlight(self.begin + begin, self.begin + end, highlight);
+    }
+    fn parallel(&self, count: usize) {
+        self.list.parallel(count);
+    }
+    fn key(&self, index: usize) -> u64
+    where
+        T: RadixKey,
+    {
+        self.list.key(self.begin + index)
+    }
+    fn alloc_histogram(&mut self, name: &'static str, len: usize) -> Histogram {
+        self.list.alloc_histogram(name, len)
+    }
+    fn free_histogram(&mut self, histogram: Histogram) {
+        self.list.free_histogram(histogram);
+    }
+    fn histogram_get(&self, histogram: &Histogram, index: usize) -> usize {
+        self.list.histogram_get(histogram, index)
+    }
+    fn histogram_set(&mut self, histogram: &mut Histogram, index: usize, count: usize) {
+        self.list.histogram_set(histogram, index, count);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{mergesort, CountingList, Operation, TracingList};
+    #[test]
+    fn test_sublist() {
+        let mut test_slice = vec![9, 8, 7, 6, 5, 4, 3, 2, 1];
+        let mut counting = CountingList::new(&mut test_slice);
+        mergesort(&mut SubList::new(&mut counting, 2, 6));
+        assert_eq!(counting.statistics().allocs, 1);
+        assert_eq!(test_slice, vec![9, 8, 4, 5, 6, 7, 3, 2, 1]);
+    }
+    #[test]
+    fn test_sublist_indices() {
+        let mut test_slice = vec![3, 2, 1];
+        let mut tracing = TracingList::new(&mut test_slice);
+        let mut list = SubList::new(&mut tracing, 1, 3);
+        assert_eq!(list.len(), 2);
+        assert_eq!(list.as_slice(), &[2, 1]);
+        list.swap(0, 1);
+        assert_eq!(tracing.into_steps()[0].operation, Operation::Swap(1, 2));
+        assert_eq!(test_slice, vec![3, 1, 2]);
+    }
+}
@@ -0,0 +1,55 @@
+//! Elements for stability tests, which are ordered by key and remember their original position
+
+use super::RadixKey;
+
+#[derive(Copy, Clone, Debug)]
+pub struct Tagged {
+    pub key: u32,
+    pub tag: usize,
+}
+impl PartialEq for Tagged {
+    fn eq(&self, other: &Tagged) -> bool {
+        self.key == other.key
+    }
+}
+impl Eq for Tagged {}
+impl PartialOrd for Tagged {
+    fn partial_cmp(&self, other: &Tagged) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+impl Ord for Tagged {
+    fn cmp(&self, other: &Tagged) -> std::cmp::Ordering {
+        self.key.cmp(&other.key)
+    }
+}
+impl std::fmt::Display for Tagged {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
+        write!(f, "{}", self.key)
+    }
+}
+impl RadixKey for Tagged {
+    const BITS: u32 = 32;
+    fn radix_key(&self) -> u64 {
+        self.key as u64
+    }
+}
+
+/// Tag each key with its position
+pub fn tagged(keys: &[u32]) -> Vec<Tagged> {
+    keys.iter()
+        .enumerate()
+        .map(|(tag, &key)| Tagged { key, tag })
+        .collect()
+}
+
+/// Whether `sorted` is `original` sorted, with equal keys in their original order
+pub fn is_sorted_stably(original: &[Tagged], sorted: &[Tagged]) -> bool {
+    let mut expected = original.to_vec();
+    expected.sort_by_key(|element| (element.key, element.tag));
+    expected
+        .iter()
+        .zip(sorted.iter())
+        .all(|(a, b)| a.key == b.key && a.tag == b.tag)
+        && expected.len() == sorted.len()
+}
@@ -0,0 +1,596 @@
+use super::{Buffer, Highlight, List};
+use std::cmp::Ordering;
+
+/// Lists shorter than this are sorted with a single binary insertion sort
+const MIN_MERGE: usize = 64;
+/// Consecutive wins needed by one run before a merge enters galloping mode
+const MIN_GALLOP: usize = 7;
+
+/// Sorted run waiting to be merged, stored in `list[base..base + len]`
+struct Run {
+    base: usize,
+    len: usize,
+}
+
+/// State shared by the merges of a single sort
+struct MergeState<T>
+where
+    T: Copy + Ord,
+{
+    buffer: Buffer<T>,
+    min_gallop: usize,
+    runs: Vec<Run>,
+}
+
+/// Minimum run length for a list of `length` elements, chosen so that the number of
+/// runs is a power of two or slightly less, which keeps the merges balanced
+fn min_run_length(length: usize) -> usize {
+    let mut length = length;
+    let mut remainder = 0;
+    while length >= MIN_MERGE {
+        remainder |= length & 1;
+        length >>= 1;
+    }
+    length + remainder
+}
+
+/// Find the length of the run starting at `begin`, reversing it if it is strictly descending
+fn count_run<T>(list: &mut dyn List<T>, begin: usize, end: usize) -> usize
+where
+    T: Copy + Ord + std::fmt::Display,
+{
+    let mut run_end = begin + 1;
+    if run_end == end {
+        return 1;
+    }
+    if list.compare(run_end, begin) == Ordering::Less {
+        // Descending runs must be strict, otherwise reversing them breaks stability
+        run_end += 1;
+        while run_end < end && list.compare(run_end, run_end - 1) == Ordering::Less {
+            run_end += 1;
+        }
+        list.reverse_range(begin, run_end);
+    } else {
+        run_end += 1;
+        while run_end < end && list.compare(run_end, run_end - 1) != Ordering::Less {
+            run_end += 1;
+        }
+    }
+    run_end - begin
+}
+
+/// Binary insertion sort `begin..end`, where `begin..start` is already sorted
+fn binary_insertionsort<T>(list: &mut dyn List<T>, begin: usize, end: usize, start: usize)
+where
+    T: Copy + Ord + std::fmt::Display,
+{
+    for current in start..end {
+        let value = list.get(current);
+        // Insert after any equal elements to keep the sort stable
+        let (mut left, mut right) = (begin, current);
+        while left < right {
+            let middle = left + (right - left) / 2;
+            if value < list.get(middle) {
+                right = middle;
+            } else {
+                left = middle + 1;
+            }
+        }
+        if left < current {
+            for index in (left..current).rev() {
+                let moved = list.get(index);
+                list.set(index + 1, moved);
+            }
+            list.set(left, value);
+        }
+    }
+}
+
+/// Find where `key` goes in the sorted range `base..base + len` read through `get`,
+/// before any equal elements. The search starts from `base + hint` and gallops outwards.
+fn gallop_left<T>(key: T, get: &dyn Fn(usize) -> T, base: usize, len: usize, hint: usize) -> usize
+where
+    T: Copy + Ord,
+{
+    let (mut last_offset, mut offset): (isize, isize) = (0, 1);
+    let hint = hint as isize;
+    let at = |offset: isize| get((base as isize + offset) as usize);
+    if key > at(hint) {
+        // Gallop right until at(hint + last_offset) < key <= at(hint + offset)
+        let max_offset = len as isize - hint;
+        while offset < max_offset && key > at(hint + offset) {
+            last_offset = offset;
+            offset = offset * 2 + 1;
+        }
+        offset = offset.min(max_offset);
+        last_offset += hint;
+        offset += hint;
+    } else {
+        // Gallop left until at(hint - offset) < key <= at(hint - last_offset)
+        let max_offset = hint + 1;
+        while offset < max_offset && key <= at(hint - offset) {
+            last_offset = offset;
+            offset = offset * 2 + 1;
+        }
+        offset = offset.min(max_offset);
+        let previous = last_offset;
+        last_offset = hint - offset;
+        offset = hint - previous;
+    }
+    // Binary search in at(last_offset) < key <= at(offset)
+    last_offset += 1;
+    while last_offset < offset {
+        let middle = last_offset + (offset - last_offset) / 2;
+        if key > at(middle) {
+            last_offset = middle + 1;
+        } else {
+            offset = middle;
+        }
+    }
+    offset as usize
+}
+
+/// Like `gallop_left`, but `key` goes after any equal elements
+fn gallop_right<T>(key: T, get: &dyn Fn(usize) -> T, base: usize, len: usize, hint: usize) -> usize
+where
+    T: Copy + Ord,
+{
+    let (mut last_offset, mut offset): (isize, isize) = (0, 1);
+    let hint = hint as isize;
+    let at = |offset: isize| get((base as isize + offset) as usize);
+    if key < at(hint) {
+        // Gallop left until at(hint - offset) <= key < at(hint - last_offset)
+        let max_offset = hint + 1;
+        while offset < max_offset && key < at(hint - offset) {
+            last_offset = offset;
+            offset = offset * 2 + 1;
+        }
+        offset = offset.min(max_offset);
+        let previous = last_offset;
+        last_offset = hint - offset;
+        offset = hint - previous;
+    } else {
+        // Gallop right until at(hint + last_offset) <= key < at(hint + offset)
+        let max_offset = len as isize - hint;
+        while offset < max_offset && key >= at(hint + offset) {
+            last_offset = offset;
+            offset = offset * 2 + 1;
+        }
+        offset = offset.min(max_offset);
+        last_offset += hint;
+        offset += hint;
+    }
+    // Binary search in at(last_offset) <= key < at(offset)
+    last_offset += 1;
+    while last_offset < offset {
+        let middle = last_offset + (offset - last_offset) / 2;
+        if key < at(middle) {
+            offset = middle;
+        } else {
+            last_offset = middle + 1;
+        }
+    }
+    offset as usize
+}
+
+/// Merge the adjacent runs `base1..base1 + len1` and `base2..base2 + len2` where
+/// `len1 <= len2`, moving the left run to the buffer and merging from the front
+fn merge_low<T>(
+    list: &mut dyn List<T>,
+    state: &mut MergeState<T>,
+    base1: usize,
+    len1: usize,
+    base2: usize,
+    len2: usize,
+) where
+    T: Copy + Ord + std::fmt::Display,
+{
+    let buffer = &mut state.buffer;
+    for index in 0..len1 {
+        list.copy_to_buffer(base1 + index, buffer, index);
+    }
+    let (mut len1, mut len2) = (len1, len2);
+    let (mut cursor1, mut cursor2, mut dest) = (0, base2, base1);
+    // The first element of the right run is known to go first
+    let moved = list.get(cursor2);
+    list.set(dest, moved);
+    dest += 1;
+    cursor2 += 1;
+    len2 -= 1;
+    let mut min_gallop = state.min_gallop;
+    if len2 > 0 && len1 > 1 {
+        'outer: loop {
+            // Merge one element at a time until a run wins consistently
+            let (mut count1, mut count2) = (0, 0);
+            loop {
+                let value = list.get(cursor2);
+                if value < list.buffer_get(buffer, cursor1) {
+                    list.set(dest, value);
+                    dest += 1;
+                    cursor2 += 1;
+                    count2 += 1;
+                    count1 = 0;
+                    len2 -= 1;
+                    if len2 == 0 {
+                        break 'outer;
+                    }
+                } else {
+                    list.copy_from_buffer(buffer, cursor1, dest);
+                    dest += 1;
+                    cursor1 += 1;
+                    count1 += 1;
+                    count2 = 0;
+                    len1 -= 1;
+                    if len1 == 1 {
+                        break 'outer;
+                    }
+                }
+                if count1.max(count2) >= min_gallop {
+                    break;
+                }
+            }
+            // Gallop, moving whole stretches from either run, until it stops paying off
+            loop {
+                let key = list.get(cursor2);
+                count1 = gallop_right(key, &|i| list.buffer_get(buffer, i), cursor1, len1, 0);
+                for _ in 0..count1 {
+                    list.copy_from_buffer(buffer, cursor1, dest);
+                    dest += 1;
+                    cursor1 += 1;
+                }
+                len1 -= count1;
+                if len1 <= 1 {
+                    break 'outer;
+                }
+                let moved = list.get(cursor2);
+                list.set(dest, moved);
+                dest += 1;
+                cursor2 += 1;
+                len2 -= 1;
+                if len2 == 0 {
+                    break 'outer;
+                }
+                let key = list.buffer_get(buffer, cursor1);
+                count2 = gallop_left(key, &|i| list.get(i), cursor2, len2, 0);
+                for _ in 0..count2 {
+                    let moved = list.get(cursor2);
+                    list.set(dest, moved);
+                    dest += 1;
+                    cursor2 += 1;
+                }
+                len2 -= count2;
+                if len2 == 0 {
+                    break 'outer;
+                }
+                list.copy_from_buffer(buffer, cursor1, dest);
+                dest += 1;
+                cursor1 += 1;
+                len1 -= 1;
+                if len1 == 1 {
+                    break 'outer;
+                }
+                min_gallop = min_gallop.saturating_sub(1);
+                if count1 < MIN_GALLOP && count2 < MIN_GALLOP {
+                    break;
+                }
+            }
+            // Penalize leaving galloping mode
+            min_gallop += 2;
+        }
+    }
+    state.min_gallop = min_gallop.max(1);
+    if len1 == 1 {
+        // The last element of the left run goes after the rest of the right run
+        for _ in 0..len2 {
+            let moved = list.get(cursor2);
+            list.set(dest, moved);
+            dest += 1;
+            cursor2 += 1;
+        }
+        list.copy_from_buffer(buffer, cursor1, dest);
+    } else {
+        // The rest of the right run is already in place
+        for _ in 0..len1 {
+            list.copy_from_buffer(buffer, cursor1, dest);
+            dest += 1;
+            cursor1 += 1;
+        }
+    }
+}
+
+/// Merge the adjacent runs `base1..base1 + len1` and `base2..base2 + len2` where
+/// `len1 > len2`, moving the right run to the buffer and merging from the back.
+/// The next free position is always `base1 + len1 + len2 - 1`.
+fn merge_high<T>(
+    list: &mut dyn List<T>,
+    state: &mut MergeState<T>,
+    base1: usize,
+    len1: usize,
+    base2: usize,
+    len2: usize,
+) where
+    T: Copy + Ord + std::fmt::Display,
+{
+    let buffer = &mut state.buffer;
+    for index in 0..len2 {
+        list.copy_to_buffer(base2 + index, buffer, index);
+    }
+    let (mut len1, mut len2) = (len1, len2);
+    // The last element of the left run is known to go last
+    let moved = list.get(base1 + len1 - 1);
+    list.set(base1 + len1 + len2 - 1, moved);
+    len1 -= 1;
+    let mut min_gallop = state.min_gallop;
+    if len1 > 0 && len2 > 1 {
+        'outer: loop {
+            // Merge one element at a time until a run wins consistently
+            let (mut count1, mut count2) = (0, 0);
+            loop {
+                let value = list.get(base1 + len1 - 1);
+                if list.buffer_get(buffer, len2 - 1) < value {
+                    list.set(base1 + len1 + len2 - 1, value);
+                    count1 += 1;
+                    count2 = 0;
+                    len1 -= 1;
+                    if len1 == 0 {
+                        break 'outer;
+                    }
+                } else {
+                    list.copy_from_buffer(buffer, len2 - 1, base1 + len1 + len2 - 1);
+                    count2 += 1;
+                    count1 = 0;
+                    len2 -= 1;
+                    if len2 == 1 {
+                        break 'outer;
+                    }
+                }
+                if count1.max(count2) >= min_gallop {
+                    break;
+                }
+            }
+            // Gallop, moving whole stretches from either run, until it stops paying off
+            loop {
+                let key = list.buffer_get(buffer, len2 - 1);
+                count1 = len1 - gallop_right(key, &|i| list.get(i), base1, len1, len1 - 1);
+                for _ in 0..count1 {
+                    let moved = list.get(base1 + len1 - 1);
+                    list.set(base1 + len1 + len2 - 1, moved);
+                    len1 -= 1;
+                }
+                if len1 == 0 {
+                    break 'outer;
+                }
+                list.copy_from_buffer(buffer, len2 - 1, base1 + len1 + len2 - 1);
+                len2 -= 1;
+                if len2 == 1 {
+                    break 'outer;
+                }
+                let key = list.get(base1 + len1 - 1);
+                count2 =
+                    len2 - gallop_left(key, &|i| list.buffer_get(buffer, i), 0, len2, len2 - 1);
+                for _ in 0..count2 {
+                    list.copy_from_buffer(buffer, len2 - 1, base1 + len1 + len2 - 1);
+                    len2 -= 1;
+                }
+                if len2 <= 1 {
+                    break 'outer;
+                }
+                let moved = list.get(base1 + len1 - 1);
+                list.set(base1 + len1 + len2 - 1, moved);
+                len1 -= 1;
+                if len1 == 0 {
+                    break 'outer;
+                }
+                min_gallop = min_gallop.saturating_sub(1);
+                if count1 < MIN_GALLOP && count2 < MIN_GALLOP {
+                    break;
+                }
+            }
+            // Penalize leaving galloping mode
+            min_gallop += 2;
+        }
+    }
+    state.min_gallop = min_gallop.max(1);
+    if len2 == 1 {
+        // The first element of the right run goes before the rest of the left run
+        for index in (base1..base1 + len1).rev() {
+            let moved = list.get(index);
+            list.set(index + 1, moved);
+        }
+        list.copy_from_buffer(buffer, 0, base1);
+    } else {
+        // The rest of the left run is already in place
+        for index in 0..len2 {
+            list.copy_from_buffer(buffer, index, base1 + len1 + index);
+        }
+    }
+}
+
+/// Merge the runs at positions `index` and `index + 1` of the run stack
+fn merge_at<T>(list: &mut dyn List<T>, state: &mut MergeState<T>, index: usize)
+where
+    T: Copy + Ord + std::fmt::Display,
+{
+    let (mut base1, mut len1) = (state.runs[index].base, state.runs[index].len);
+    let (base2, mut len2) = (state.runs[index + 1].base, state.runs[index + 1].len);
+    state.runs[index].len = len1 + len2;
+    state.runs.remove(index + 1);
+    list.highlight(base1, base2 + len2, Highlight::Merge);
+    // Elements of the left run smaller than the first of the right run are in place
+    let key = list.get(base2);
+    let skipped = gallop_right(key, &|i| list.get(i), base1, len1, 0);
+    base1 += skipped;
+    len1 -= skipped;
+    if len1 > 0 {
+        // So are elements of the right run larger than the last of the left run
+        let key = list.get(base1 + len1 - 1);
+        len2 = gallop_left(key, &|i| list.get(i), base2, len2, len2 - 1);
+        if len2 > 0 {
+            if len1 <= len2 {
+                merge_low(list, state, base1, len1, base2, len2);
+            } else {
+                merge_high(list, state, base1, len1, base2, len2);
+            }
+        }
+    }
+    let run = &state.runs[index];
+    list.highlight(run.base, run.base + run.len, Highlight::Run);
+}
+
+/// Merge runs until the lengths on the stack satisfy, from the top,
+/// `runs[i - 2] > runs[i - 1] + runs[i]` and `runs[i - 1] > runs[i]`
+fn merge_collapse<T>(list: &mut dyn List<T>, state: &mut MergeState<T>)
+where
+    T: Copy + Ord + std::fmt::Display,
+{
+    while state.runs.len() > 1 {
+        let runs = &state.runs;
+        let mut n = runs.len() - 2;
+        if (n > 0 && runs[n - 1].len <= runs[n].len + runs[n + 1].len)
+            || (n > 1 && runs[n - 2].len <= runs[n - 1].len + runs[n].len)
+        {
+            if runs[n - 1].len < runs[n + 1].len {
+                n -= 1;
+            }
+        } else if runs[n].len > runs[n + 1].len {
+            break;
+        }
+        merge_at(list, state, n);
+    }
+}
+
+/// Merge all the remaining runs
+fn merge_force_collapse<T>(list: &mut dyn List<T>, state: &mut MergeState<T>)
+where
+    T: Copy + Ord + std::fmt::Display,
+{
+    while state.runs.len() > 1 {
+        let runs = &state.runs;
+        let mut n = runs.len() - 2;
+        if n > 0 && runs[n - 1].len < runs[n + 1].len {
+            n -= 1;
+        }
+        merge_at(list, state, n);
+    }
+}
+
+pub fn timsort<T>(list: &mut dyn List<T>)
+where
+    T: Copy + Ord + std::fmt::Display,
+{
+    let length = list.len();
+    if length < 2 {
+        return;
+    }
+    if length < MIN_MERGE {
+        // Extend the initial run to the whole list
+        let run_length = count_run(list, 0, length);
+        list.highlight(0, run_length, Highlight::Run);
+        binary_insertionsort(list, 0, length, run_length);
+        list.highlight(0, length, Highlight::None);
+        return;
+    }
+    // No merge ever needs to buffer more than half of the list
+    let mut state = MergeState {
+        buffer: list.alloc_buffer("timsort", length / 2),
+        min_gallop: MIN_GALLOP,
+        runs: Vec::new(),
+    };
+    let min_run = min_run_length(length);
+    let mut begin = 0;
+    while begin < length {
+        let mut run_length = count_run(list, begin, length);
+        list.highlight(begin, begin + run_length, Highlight::Run);
+        if run_length < min_run {
+            // Extend short runs with binary insertion sort
+            let forced = min_run.min(length - begin);
+            binary_insertionsort(list, begin, begin + forced, begin + run_length);
+            run_length = forced;
+            list.highlight(begin, begin + run_length, Highlight::Run);
+        }
+        state.runs.push(Run {
+            base: begin,
+            len: run_length,
+        });
+        merge_collapse(list, &mut state);
+        begin += run_length;
+    }
+    merge_force_collapse(list, &mut state);
+    list.highlight(0, length, Highlight::None);
+    list.free_buffer(state.buffer);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Operation, TracingList};
+    #[test]
+    fn test_timsort() {
+        let mut test_slice = vec![1, 4, 123, 7, 8, 4, 8, 8, 2, 4, 57, 8, 324, 213];
+        let mut test_slice2 = test_slice.clone();
+        assert_eq!(test_slice, test_slice2);
+        test_slice.sort_unstable();
+        timsort(&mut test_slice2);
+        assert_eq!(test_slice, test_slice2);
+        println!("{:?}", test_slice2);
+    }
+    #[test]
+    fn test_timsort_runs() {
+        // Ascending and descending runs of different lengths, with many duplicates
+        let mut test_slice: Vec<u32> = Vec::new();
+        for run in 0..40 {
+            let values = (0..run * 37 % 200).map(|i| (i * 7 + run) % 150);
+            let mut values: Vec<u32> = values.collect();
+            values.sort_unstable();
+            if run % 3 == 0 {
+                values.reverse();
+            }
+            test_slice.extend(values);
+        }
+        let mut expected = test_slice.clone();
+        expected.sort_unstable();
+        let mut tracing = TracingList::new(&mut test_slice);
+        timsort(&mut tracing);
+        let steps = tracing.into_steps();
+        for highlight in [Highlight::Run, Highlight::Merge].iter() {
+            assert!(steps.iter().any(|step| match step.operation {
+                Operation::Highlight(_, _, h) => h == *highlight,
+                _ => false,
+            }));
+        }
+        assert_eq!(test_slice, expected);
+    }
+    #[test]
+    fn test_timsort_sorted() {
+        // A single ascending run is detected without merging
+        let mut test_slice: Vec<u32> = (0..1000).collect();
+        let mut tracing = TracingList::new(&mut test_slice);
+        timsort(&mut tracing);
+        let steps = tracing.into_steps();
+        assert!(!steps.iter().any(|step| match step.operation {
+            Operation::Highlight(_, _, h) => h == Highlight::Merge,
+            _ => false,
+        }));
+        assert_eq!(test_slice, (0..1000).collect::<Vec<_>>());
+    }
+    #[test]
+    fn test_min_run_length() {
+        assert_eq!(min_run_length(63), 63);
+        assert_eq!(min_run_length(64), 32);
+        assert_eq!(min_run_length(65), 33);
+        assert_eq!(min_run_length(1000), 63);
+        assert_eq!(min_run_length(1024), 32);
+    }
+    #[test]
+    fn test_gallop() {
+        let values = [1, 2, 2, 2, 5, 8, 8, 9];
+        let get = |i: usize| values[i];
+        for hint in 0..values.len() {
+            assert_eq!(gallop_left(2, &get, 0, values.len(), hint), 1);
+            assert_eq!(gallop_right(2, &get, 0, values.len(), hint), 4);
+            assert_eq!(gallop_left(0, &get, 0, values.len(), hint), 0);
+            assert_eq!(gallop_right(10, &get, 0, values.len(), hint), 8);
+            assert_eq!(gallop_left(8, &get, 2, 6, hint.min(5)), 3);
+        }
+    }
+}
@@ -0,0 +1,993 @@
+//! Recording and replaying of `Operation` streams.
+//!
+//! A `Trace` holds the algorithm name, the initial data and every operation
+//! performed by a sort run, together with the value written by `Set` and
+//! `BufferSet` (any other value can be recomputed while replaying).
+//!
+//! # Text format
+//!
+//! ```text
+//! trace 1
+//! algorithm mergesort
+//! data 3
+//! 15
+//! 4
+//! 23
+//! operations
+//! Alloc merge 3
+//! CopyToBuffer 0 merge 0
+//! Get 1
+//! BufferGet merge 0
+//! Set 0 4
+//! ...
+//! ```
+//!
+//! One operation per line, named like the `Operation` variants and followed by
+//! their fields in order. `Set` and `BufferSet` have the written value appended.
+//! Buffer names cannot contain whitespace. `Highlight` operations end with the
+//! highlight in lowercase, e.g. `Highlight 0 4 pivot`. `HistogramSet` carries the
+//! written count as a regular field, e.g. `HistogramSet counts 3 7`. `Reverse` and
+//! `Rotate` take the range followed by the rotation, e.g. `Rotate 0 8 3`, and `BlockSwap`
+//! takes the start of both blocks followed by their length. `Parallel` takes the number
+//! of operations after it which happen at the same time.
+//!
+//! # Binary format
+//!
+//! The magic bytes `SRTB` and a version byte (`1`), followed by:
+//! the algorithm name, the data length and values, the number of buffer names
+//! and each name, the number of operations and each operation.
+//! Strings are a length followed by UTF-8 bytes, operations are a tag byte
+//! (the variant in declaration order) followed by their fields, with buffers
+//! and histograms referenced by their position in the name table and highlights by their
+//! position in `Highlight::ALL`.
+//! All integers are unsigned LEB128.
+
+use super::{Buffer, Highlight, Histogram, List, Operation, RadixKey};
+
+use std::cell::RefCell;
+use std::io::{BufRead, Error, ErrorKind, Read, Write};
+
+const TEXT_MAGIC: &str = "trace 1";
+const BINARY_MAGIC: &[u8] = b"SRTB\x01";
+
+/// Most elements reserved up front for a length read from a trace, which may be corrupted
+const MAX_RESERVED: usize = 1 << 16;
+/// Longest algorithm or buffer name accepted in a binary trace
+const MAX_STRING_LENGTH: usize = 1 << 10;
+/// Longest buffer or histogram accepted besides ones as long as the data,
+/// enough for the histograms of the largest radix and key range
+const MAX_AUXILIARY_LENGTH: usize = 1 << 16;
+
+/// An `Operation` along with the value written by it, if not implied by the operation
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct Step<T>
+where
+    T: Copy + Ord,
+{
+    pub operation: Operation,
+    pub value: Option<T>,
+}
+
+/// Wraps another `List` and records every operation performed through it
+pub struct TracingList<'a, T>
+where
+    T: Copy + Ord,
+{
+    list: &'a mut dyn List<T>,
+    steps: RefCell<Vec<Step<T>>>,
+}
+impl<'a, T> TracingList<'a, T>
+where
+    T: Copy + Ord,
+{
+    pub fn new(list: &'a mut dyn List<T>) -> TracingList<'a, T> {
+        TracingList {
+            list,
+            steps: RefCell::new(Vec::new()),
+        }
+    }
+    pub fn into_steps(self) -> Vec<Step<T>> {
+        self.steps.into_inner()
+    }
+    fn record(&self, operation: Operation, value: Option<T>) {
+        self.steps.borrow_mut().push(Step { operation, value });
+    }
+}
+impl<'a, T> List<T> for TracingList<'a, T>
+where
+    T: Copy + Ord,
+{
+    fn as_slice(&self) -> &[T] {
+        self.list.as_slice()
+    }
+    fn as_mut_slice(&mut self) -> &mut [T] {
+        self.list.as_mut_slice()
+    }
+    fn get(&self, index: usize) -> T {
+        self.record(Operation::Get(index), None);
+        self.list.get(index)
+    }
+    fn set(&mut self, index: usize, value: T) {
+        self.record(Operation::Set(index), Some(value));
+        self.list.set(index, value);
+    }
+    fn compare(&self, a: usize, b: usize) -> std::cmp::Ordering {
+        self.record(Operation::Compare(a, b), None);
+        self.list.compare(a, b)
+    }
+    fn swap(&mut self, a: usize, b: usize) {
+        self.record(Operation::Swap(a, b), None);
+        self.list.swap(a, b);
+    }
+    fn reverse_range(&mut self, begin: usize, end: usize) {
+        self.record(Operation::Reverse(begin, end), None);
+        self.list.reverse_range(begin, end);
+    }
+    fn rotate_range(&mut self, begin: usize, end: usize, k: usize) {
+        self.record(Operation::Rotate(begin, end, k), None);
+        self.list.rotate_range(begin, end, k);
+    }
+    fn swap_blocks(&mut self, a: usize, b: usize, len: usize) {
+        self.record(Operation::BlockSwap(a, b, len), None);
+        self.list.swap_blocks(a, b, len);
+    }
+    fn alloc_buffer(&mut self, name: &'static str, len: usize) -> Buffer<T> {
+        self.record(Operation::Alloc(name, len), None);
+        self.list.alloc_buffer(name, len)
+    }
+    fn free_buffer(&mut self, buffer: Buffer<T>) {
+        self.record(Operation::Free(buffer.name), None);
+        self.list.free_buffer(buffer);
+    }
+    fn buffer_get(&self, buffer: &Buffer<T>, index: usize) -> T {
+        self.record(Operation::BufferGet(buffer.name, index), None);
+        self.list.buffer_get(buffer, index)
+    }
+    fn buffer_set(&mut self, buffer: &mut Buffer<T>, index: usize, value: T) {
+        self.record(Operation::BufferSet(buffer.name, index), Some(value));
+        self.list.buffer_set(buffer, index, value);
+    }
+    fn copy_to_buffer(&mut self, index: usize, buffer: &mut Buffer<T>, buffer_index: usize) {
+        self.record(
+            Operation::CopyToBuffer(index, buffer.name, buffer_index),
+            None,
+        );
+        self.list.copy_to_buffer(index, buffer, buffer_index);
+    }
+    fn copy_from_buffer(&mut self, buffer: &Buffer<T>, buffer_index: usize, index: usize) {
+        self.record(
+            Operation::CopyFromBuffer(buffer.name, buffer_index, index),
+            None,
+        );
+        self.list.copy_from_buffer(buffer, buffer_index, index);
+    }
+    fn highlight(&self, begin: usize, end: usize, highlight: Highlight) {
+        self.record(Operation::Highlight(begin, end, highlight), None);
+        self.list.highlight(begin, end, highlight);
+    }
+    fn parallel(&self, count: usize) {
+        self.record(Operation::Parallel(count), None);
+        self.list.parallel(count);
+    }
+    fn key(&self, index: usize) -> u64
+    where
+        T: RadixKey,
+    {
+        self.record(Operation::Key(index), None);
+        self.list.key(index)
+    }
+    fn alloc_histogram(&mut self, name: &'static str, len: usize) -> Histogram {
+        self.record(Operation::AllocHistogram(name, len), None);
+        self.list.alloc_histogram(name, len)
+    }
+    fn free_histogram(&mut self, histogram: Histogram) {
+        self.record(Operation::FreeHistogram(histogram.name), None);
+        self.list.free_histogram(histogram);
+    }
+    fn histogram_get(&self, histogram: &Histogram, index: usize) -> usize {
+        self.record(Operation::HistogramGet(histogram.name, index), None);
+        self.list.histogram_get(histogram, index)
+    }
+    fn histogram_set(&mut self, histogram: &mut Histogram, index: usize, count: usize) {
+        self.record(Operation::HistogramSet(histogram.name, index, count), None);
+        self.list.histogram_set(histogram, index, count);
+    }
+}
+
+/// A recorded sort run
+#[derive(Clone, PartialEq, Debug)]
+pub struct Trace<T>
+where
+    T: Copy + Ord,
+{
+    pub algorithm: String,
+    pub data: Vec<T>,
+    pub steps: Vec<Step<T>>,
+}
+impl<T> Trace<T>
+where
+    T: RadixKey,
+{
+    /// Perform the recorded operations on `list`, which must contain the initial data
+    pub fn replay(&self, list: &mut dyn List<T>) {
+        let mut buffers: Vec<Buffer<T>> = Vec::new();
+        let mut histograms: Vec<Histogram> = Vec::new();
+        let find = |buffers: &[Buffer<T>], name| {
+            buffers
+                .iter()
+                .position(|b| b.name == name)
+                .unwrap_or_else(|| panic!("Buffer \"{}\" used before being allocated", name))
+        };
+        let find_histogram = |histograms: &[Histogram], name| {
+            histograms
+                .iter()
+                .position(|h| h.name == name)
+                .unwrap_or_else(|| panic!("Histogram \"{}\" used before being allocated", name))
+        };
+        for step in self.steps.iter() {
+            match step.operation {
+                Operation::Get(index) => {
+                    list.get(index);
+                }
+                Operation::Set(index) => list.set(index, step.value.unwrap()),
+                Operation::Compare(a, b) => {
+                    list.compare(a, b);
+                }
+                Operation::Swap(a, b) => list.swap(a, b),
+                Operation::Alloc(name, len) => buffers.push(list.alloc_buffer(name, len)),
+                Operation::Free(name) => {
+                    let buffer = buffers.remove(find(&buffers, name));
+                    list.free_buffer(buffer);
+                }
+                Operation::BufferGet(name, index) => {
+                    list.buffer_get(&buffers[find(&buffers, name)], index);
+                }
+                Operation::BufferSet(name, index) => {
+                    let buffer = find(&buffers, name);
+                    list.buffer_set(&mut buffers[buffer], index, step.value.unwrap());
+                }
+                Operation::CopyToBuffer(index, name, buffer_index) => {
+                    let buffer = find(&buffers, name);
+                    list.copy_to_buffer(index, &mut buffers[buffer], buffer_index);
+                }
+                Operation::CopyFromBuffer(name, buffer_index, index) => {
+                    list.copy_from_buffer(&buffers[find(&buffers, name)], buffer_index, index);
+                }
+                Operation::Highlight(begin, end, highlight) => {
+                    list.highlight(begin, end, highlight)
+                }
+                Operation::Key(index) => {
+                    list.key(index);
+                }
+                Operation::AllocHistogram(name, len) => {
+                    histograms.push(list.alloc_histogram(name, len))
+                }
+                Operation::FreeHistogram(name) => {
+                    let histogram = histograms.remove(find_histogram(&histograms, name));
+                    list.free_histogram(histogram);
+                }
+                Operation::HistogramGet(name, index) => {
+                    list.histogram_get(&histograms[find_histogram(&histograms, name)], index);
+                }
+                Operation::HistogramSet(name, index, count) => {
+                    let histogram = find_histogram(&histograms, name);
+                    list.histogram_set(&mut histograms[histogram], index, count);
+                }
+                Operation::Reverse(begin, end) => list.reverse_range(begin, end),
+                Operation::Rotate(begin, end, k) => list.rotate_range(begin, end, k),
+                Operation::BlockSwap(a, b, len) => list.swap_blocks(a, b, len),
+                Operation::Parallel(count) => list.parallel(count),
+            }
+        }
+    }
+}
+impl<T> Trace<T>
+where
+    T: Copy + Ord,
+{
+    /// Whether any auxiliary buffer or histogram is allocated during the run
+    pub fn uses_buffers(&self) -> bool {
+        self.steps.iter().any(|step| {
+            matches!(
+                step.operation,
+                Operation::Alloc(_, _) | Operation::AllocHistogram(_, _)
+            )
+        })
+    }
+}
+impl<T> Trace<T>
+where
+    T: Copy + Ord + std::fmt::Display,
+{
+    pub fn write_text(&self, writer: &mut dyn Write) -> std::io::Result<()> {
+        writeln!(writer, "{}", TEXT_MAGIC)?;
+        writeln!(writer, "algorithm {}", self.algorithm)?;
+        writeln!(writer, "data {}", self.data.len())?;
+        for value in self.data.iter() {
+            writeln!(writer, "{}", value)?;
+        }
+        writeln!(writer, "operations")?;
+        for step in self.steps.iter() {
+            match step.operation {
+                Operation::Get(index) => writeln!(writer, "Get {}", index),
+                Operation::Set(index) => {
+                    writeln!(writer, "Set {} {}", index, step.value.unwrap())
+                }
+                Operation::Compare(a, b) => writeln!(writer, "Compare {} {}", a, b),
+                Operation::Swap(a, b) => writeln!(writer, "Swap {} {}", a, b),
+                Operation::Alloc(name, len) => writeln!(writer, "Alloc {} {}", name, len),
+                Operation::Free(name) => writeln!(writer, "Free {}", name),
+                Operation::BufferGet(name, index) => {
+                    writeln!(writer, "BufferGet {} {}", name, index)
+                }
+                Operation::BufferSet(name, index) => writeln!(
+                    writer,
+                    "BufferSet {} {} {}",
+                    name,
+                    index,
+                    step.value.unwrap()
+                ),
+                Operation::CopyToBuffer(index, name, buffer_index) => {
+                    writeln!(writer, "CopyToBuffer {} {} {}", index, name, buffer_index)
+                }
+                Operation::CopyFromBuffer(name, buffer_index, index) => {
+                    writeln!(writer, "CopyFromBuffer {} {} {}", name, buffer_index, index)
+                }
+                Operation::Highlight(begin, end, highlight) => {
+                    writeln!(writer, "Highlight {} {} {}", begin, end, highlight)
+                }
+                Operation::Key(index) => writeln!(writer, "Key {}", index),
+                Operation::AllocHistogram(name, len) => {
+                    writeln!(writer, "AllocHistogram {} {}", name, len)
+                }
+                Operation::FreeHistogram(name) => writeln!(writer, "FreeHistogram {}", name),
+                Operation::HistogramGet(name, index) => {
+                    writeln!(writer, "HistogramGet {} {}", name, index)
+                }
+                Operation::HistogramSet(name, index, count) => {
+                    writeln!(writer, "HistogramSet {} {} {}", name, index, count)
+                }
+                Operation::Reverse(begin, end) => writeln!(writer, "Reverse {} {}", begin, end),
+                Operation::Rotate(begin, end, k) => {
+                    writeln!(writer, "Rotate {} {} {}", begin, end, k)
+                }
+                Operation::BlockSwap(a, b, len) => {
+                    writeln!(writer, "BlockSwap {} {} {}", a, b, len)
+                }
+                Operation::Parallel(count) => writeln!(writer, "Parallel {}", count),
+            }?;
+        }
+        Ok(())
+    }
+}
+impl<T> Trace<T>
+where
+    T: Copy + Ord + std::str::FromStr,
+{
+    pub fn read_text(reader: &mut dyn BufRead) -> std::io::Result<Trace<T>> {
+        let mut names = Names::default();
+        let mut lines = reader.lines().enumerate();
+        let mut next_line = || match lines.next() {
+            Some((number, line)) => line.map(|line| (number + 1, line)),
+            None => Err(invalid_data("Unexpected end of trace")),
+        };
+        let (_, magic) = next_line()?;
+        if magic.trim() != TEXT_MAGIC {
+            return Err(invalid_data("Not a trace file"));
+        }
+        let (number, line) = next_line()?;
+        let algorithm = match line.trim().splitn(2, ' ').collect::<Vec<_>>()[..] {
+            ["algorithm", name] => name.trim().to_string(),
+            _ => return Err(invalid_line(number)),
+        };
+        let (number, line) = next_line()?;
+        let len: usize = match line.split_whitespace().collect::<Vec<_>>()[..] {
+            ["data", len] => parse(len, number)?,
+            _ => return Err(invalid_line(number)),
+        };
+        let mut data = Vec::with_capacity(len.min(MAX_RESERVED));
+        for _ in 0..len {
+            let (number, line) = next_line()?;
+            data.push(parse(line.trim(), number)?);
+        }
+        let (number, line) = next_line()?;
+        if line.trim() != "operations" {
+            return Err(invalid_line(number));
+        }
+        let mut bounds = Bounds::new(data.len());
+        let mut steps = Vec::new();
+        for (number, line) in lines {
+            let line = line?;
+            let number = number + 1;
+            let fields = line.split_whitespace().collect::<Vec<_>>();
+            let mut step = |operation, value| {
+                if bounds.check(operation) {
+                    Ok(Step { operation, value })
+                } else {
+                    Err(invalid_line(number))
+                }
+            };
+            steps.push(match fields[..] {
+                [] => continue,
+                ["Get", index] => step(Operation::Get(parse(index, number)?), None),
+                ["Set", index, value] => step(
+                    Operation::Set(parse(index, number)?),
+                    Some(parse(value, number)?),
+                ),
+                ["Compare", a, b] => step(
+                    Operation::Compare(parse(a, number)?, parse(b, number)?),
+                    None,
+                ),
+                ["Swap", a, b] => step(Operation::Swap(parse(a, number)?, parse(b, number)?), None),
+                ["Alloc", name, len] => step(
+                    Operation::Alloc(names.intern(name), parse(len, number)?),
+                    None,
+                ),
+                ["Free", name] => step(Operation::Free(names.intern(name)), None),
+                ["BufferGet", name, index] => step(
+                    Operation::BufferGet(names.intern(name), parse(index, number)?),
+                    None,
+                ),
+                ["BufferSet", name, index, value] => step(
+                    Operation::BufferSet(names.intern(name), parse(index, number)?),
+                    Some(parse(value, number)?),
+                ),
+                ["CopyToBuffer", index, name, buffer_index] => step(
+                    Operation::CopyToBuffer(
+                        parse(index, number)?,
+                        names.intern(name),
+                        parse(buffer_index, number)?,
+                    ),
+                    None,
+                ),
+                ["CopyFromBuffer", name, buffer_index, index] => step(
+                    Operation::CopyFromBuffer(
+                        names.intern(name),
+                        parse(buffer_index, number)?,
+                        parse(index, number)?,
+                    ),
+                    None,
+                ),
+                ["Highlight", begin, end, highlight] => step(
+                    Operation::Highlight(
+                        parse(begin, number)?,
+                        parse(end, number)?,
+                        parse(highlight, number)?,
+                    ),
+                    None,
+                ),
+                ["Key", index] => step(Operation::Key(parse(index, number)?), None),
+                ["AllocHistogram", name, len] => step(
+                    Operation::AllocHistogram(names.intern(name), parse(len, number)?),
+                    None,
+                ),
+                ["FreeHistogram", name] => step(Operation::FreeHistogram(names.intern(name)), None),
+                ["HistogramGet", name, index] => step(
+                    Operation::HistogramGet(names.intern(name), parse(index, number)?),
+                    None,
+                ),
+                ["HistogramSet", name, index, count] => step(
+                    Operation::HistogramSet(
+                        names.intern(name),
+                        parse(index, number)?,
+                        parse(count, number)?,
+                    ),
+                    None,
+                ),
+                ["Reverse", begin, end] => step(
+                    Operation::Reverse(parse(begin, number)?, parse(end, number)?),
+                    None,
+                ),
+                ["Rotate", begin, end, k] => step(
+                    Operation::Rotate(
+                        parse(begin, number)?,
+                        parse(end, number)?,
+                        parse(k, number)?,
+                    ),
+                    None,
+                ),
+                ["BlockSwap", a, b, len] => step(
+                    Operation::BlockSwap(parse(a, number)?, parse(b, number)?, parse(len, number)?),
+                    None,
+                ),
+                ["Parallel", count] => step(Operation::Parallel(parse(count, number)?), None),
+                _ => Err(invalid_line(number)),
+            }?);
+        }
+        Ok(Trace {
+            algorithm,
+            data,
+            steps,
+        })
+    }
+}
+impl Trace<u32> {
+    pub fn write_binary(&self, writer: &mut dyn Write) -> std::io::Result<()> {
+        writer.write_all(BINARY_MAGIC)?;
+        write_string(writer, &self.algorithm)?;
+        write_varint(writer, self.data.len() as u64)?;
+        for &value in self.data.iter() {
+            write_varint(writer, u64::from(value))?;
+        }
+        // Buffer and histogram name table
+        let mut names: Vec<&'static str> = Vec::new();
+        for step in self.steps.iter() {
+            if let Operation::Alloc(name, _) | Operation::AllocHistogram(name, _) = step.operation {
+                if !names.contains(&name) {
+                    names.push(name);
+                }
+            }
+        }
+        write_varint(writer, names.len() as u64)?;
+        for name in names.iter() {
+            write_string(writer, name)?;
+        }
+        let name_index = |name| names.iter().position(|&n| n == name).unwrap() as u64;
+        // Operations
+        write_varint(writer, self.steps.len() as u64)?;
+        for step in self.steps.iter() {
+            let (tag, fields): (u8, Vec<u64>) = match step.operation {
+                Operation::Get(index) => (0, vec![index as u64]),
+                Operation::Set(index) => (1, vec![index as u64]),
+                Operation::Compare(a, b) => (2, vec![a as u64, b as u64]),
+                Operation::Swap(a, b) => (3, vec![a as u64, b as u64]),
+                Operation::Alloc(name, len) => (4, vec![name_index(name), len as u64]),
+                Operation::Free(name) => (5, vec![name_index(name)]),
+                Operation::BufferGet(name, index) => (6, vec![name_index(name), index as u64]),
+                Operation::BufferSet(name, index) => (7, vec![name_index(name), index as u64]),
+                Operation::CopyToBuffer(index, name, buffer_index) => {
+                    (8, vec![index as u64, name_index(name), buffer_index as u64])
+                }
+                Operation::CopyFromBuffer(name, buffer_index, index) => {
+                    (9, vec![name_index(name), buffer_index as u64, index as u64])
+                }
+                Operation::Highlight(begin, end, highlight) => {
+                    let kind = Highlight::ALL.iter().position(|&h| h == highlight);
+                    (10, vec![begin as u64, end as u64, kind.unwrap() as u64])
+                }
+                Operation::Key(index) => (11, vec![index as u64]),
+                Operation::AllocHistogram(name, len) => (12, vec![name_index(name), len as u64]),
+                Operation::FreeHistogram(name) => (13, vec![name_index(name)]),
+                Operation::HistogramGet(name, index) => (14, vec![name_index(name), index as u64]),
+                Operation::HistogramSet(name, index, count) => {
+                    (15, vec![name_index(name), index as u64, count as u64])
+                }
+                Operation::Reverse(begin, end) => (16, vec![begin as u64, end as u64]),
+                Operation::Rotate(begin, end, k) => (17, vec![begin as u64, end as u64, k as u64]),
+                Operation::BlockSwap(a, b, len) => (18, vec![a as u64, b as u64, len as u64]),
+                Operation::Parallel(count) => (19, vec![count as u64]),
+            };
+            writer.write_all(&[tag])?;
+            for field in fields.into_iter().chain(step.value.map(u64::from)) {
+                write_varint(writer, field)?;
+            }
+        }
+        Ok(())
+    }
+    pub fn read_binary(reader: &mut dyn Read) -> std::io::Result<Trace<u32>> {
+        let mut magic = [0; 5];
+        reader.read_exact(&mut magic)?;
+        if magic != BINARY_MAGIC {
+            return Err(invalid_data("Not a binary trace file"));
+        }
+        let algorithm = read_string(reader)?;
+        let len = read_varint(reader)? as usize;
+        let mut data = Vec::with_capacity(len.min(MAX_RESERVED));
+        for _ in 0..len {
+            data.push(read_value(reader)?);
+        }
+        let mut names = Names::default();
+        let mut table: Vec<&'static str> = Vec::new();
+        for _ in 0..read_varint(reader)? {
+            table.push(names.intern(&read_string(reader)?));
+        }
+        let name = |reader: &mut dyn Read| {
+            let index = read_varint(reader)? as usize;
+            if index < table.len() {
+                Ok(table[index])
+            } else {
+                Err(invalid_data("Invalid buffer name index"))
+            }
+        };
+        let index = |reader: &mut dyn Read| read_varint(reader).map(|i| i as usize);
+        let highlight = |reader: &mut dyn Read| {
+            let kind = read_varint(reader)? as usize;
+            if kind < Highlight::ALL.len() {
+                Ok(Highlight::ALL[kind])
+            } else {
+                Err(invalid_data("Invalid highlight"))
+            }
+        };
+        let count = read_varint(reader)? as usize;
+        let mut bounds = Bounds::new(data.len());
+        let mut steps = Vec::with_capacity(count.min(MAX_RESERVED));
+        for _ in 0..count {
+            let mut tag = [0];
+            reader.read_exact(&mut tag)?;
+            let (operation, value) = match tag[0] {
+                0 => (Operation::Get(index(reader)?), None),
+                1 => (Operation::Set(index(reader)?), Some(read_value(reader)?)),
+                2 => (Operation::Compare(index(reader)?, index(reader)?), None),
+                3 => (Operation::Swap(index(reader)?, index(reader)?), None),
+                4 => (Operation::Alloc(name(reader)?, index(reader)?), None),
+                5 => (Operation::Free(name(reader)?), None),
+                6 => (Operation::BufferGet(name(reader)?, index(reader)?), None),
+                7 => (
+                    Operation::BufferSet(name(reader)?, index(reader)?),
+                    Some(read_value(reader)?),
+                ),
+                8 => (
+                    Operation::CopyToBuffer(index(reader)?, name(reader)?, index(reader)?),
+                    None,
+                ),
+                9 => (
+                    Operation::CopyFromBuffer(name(reader)?, index(reader)?, index(reader)?),
+                    None,
+                ),
+                10 => (
+                    Operation::Highlight(index(reader)?, index(reader)?, highlight(reader)?),
+                    None,
+                ),
+                11 => (Operation::Key(index(reader)?), None),
+                12 => (
+                    Operation::AllocHistogram(name(reader)?, index(reader)?),
+                    None,
+                ),
+                13 => (Operation::FreeHistogram(name(reader)?), None),
+                14 => (Operation::HistogramGet(name(reader)?, index(reader)?), None),
+                15 => (
+                    Operation::HistogramSet(name(reader)?, index(reader)?, index(reader)?),
+                    None,
+                ),
+                16 => (Operation::Reverse(index(reader)?, index(reader)?), None),
+                17 => (
+                    Operation::Rotate(index(reader)?, index(reader)?, index(reader)?),
+                    None,
+                ),
+                18 => (
+                    Operation::BlockSwap(index(reader)?, index(reader)?, index(reader)?),
+                    None,
+                ),
+                19 => (Operation::Parallel(index(reader)?), None),
+                _ => return Err(invalid_data("Invalid operation tag")),
+            };
+            if !bounds.check(operation) {
+                return Err(invalid_data("Operation out of bounds"));
+            }
+            steps.push(Step { operation, value });
+        }
+        Ok(Trace {
+            algorithm,
+            data,
+            steps,
+        })
+    }
+    /// Read a trace in either format
+    pub fn read(reader: &mut dyn Read) -> std::io::Result<Trace<u32>> {
+        let mut contents = Vec::new();
+        reader.read_to_end(&mut contents)?;
+        if contents.starts_with(BINARY_MAGIC) {
+            Trace::read_binary(&mut &contents[..])
+        } else {
+            Trace::read_text(&mut &contents[..])
+        }
+    }
+}
+
+/// Data length and live buffers and histograms while reading a trace, to reject operations
+/// that would go out of bounds or read empty buffer slots when replayed
+struct Bounds {
+    len: usize,
+    /// Name of each live buffer and which of its slots have been written
+    buffers: Vec<(&'static str, Vec<bool>)>,
+    histograms: Vec<(&'static str, usize)>,
+}
+impl Bounds {
+    fn new(len: usize) -> Bounds {
+        Bounds {
+            len,
+            buffers: Vec::new(),
+            histograms: Vec::new(),
+        }
+    }
+    /// Whether `operation` stays within bounds, keeping track of allocations and writes
+    fn check(&mut self, operation: Operation) -> bool {
+        let len = self.len;
+        let index = |index: usize| index < len;
+        let range = |begin: usize, end: usize| begin <= end && end <= len;
+        let buffer = self
+            .buffers
+            .iter()
+            .position(|&(allocated, _)| Some(allocated) == operation_buffer(operation));
+        let histogram = self
+            .histograms
+            .iter()
+            .position(|&(allocated, _)| Some(allocated) == operation_buffer(operation));
+        let live = buffer.is_some() || histogram.is_some();
+        let auxiliary_length = |length: usize| length <= len.max(MAX_AUXILIARY_LENGTH);
+        let slots = buffer.map(|buffer| &mut self.buffers[buffer].1);
+        let written = |slots: Option<&mut Vec<bool>>, index: usize| {
+            slots.is_some_and(|slots| index < slots.len() && slots[index])
+        };
+        let write = |slots: Option<&mut Vec<bool>>, index: usize| match slots {
+            Some(slots) if index < slots.len() => {
+                slots[index] = true;
+                true
+            }
+            _ => false,
+        };
+        match operation {
+            Operation::Get(i) | Operation::Set(i) | Operation::Key(i) => index(i),
+            Operation::Compare(a, b) | Operation::Swap(a, b) => index(a) && index(b),
+            Operation::BufferGet(_, i) => written(slots, i),
+            Operation::BufferSet(_, i) => write(slots, i),
+            Operation::CopyToBuffer(i, _, bi) => index(i) && write(slots, bi),
+            Operation::CopyFromBuffer(_, bi, i) => index(i) && written(slots, bi),
+            Operation::HistogramGet(_, i) | Operation::HistogramSet(_, i, _) => {
+                histogram.is_some_and(|histogram| i < self.histograms[histogram].1)
+            }
+            Operation::Highlight(begin, end, _) | Operation::Reverse(begin, end) => {
+                range(begin, end)
+            }
+            Operation::Rotate(begin, end, k) => range(begin, end) && k <= end - begin,
+            Operation::BlockSwap(a, b, length) => {
+                a.checked_add(length).is_some_and(|end| end <= len)
+                    && b.checked_add(length).is_some_and(|end| end <= len)
+            }
+            Operation::Parallel(_) => true,
+            Operation::Alloc(name, length) => {
+                let valid = !live && auxiliary_length(length);
+                if valid {
+                    self.buffers.push((name, vec![false; length]));
+                }
+                valid
+            }
+            Operation::AllocHistogram(name, length) => {
+                let valid = !live && auxiliary_length(length);
+                if valid {
+                    self.histograms.push((name, length));
+                }
+                valid
+            }
+            Operation::Free(_) => match buffer {
+                Some(buffer) => {
+                    self.buffers.remove(buffer);
+                    true
+                }
+                None => false,
+            },
+            Operation::FreeHistogram(_) => match histogram {
+                Some(histogram) => {
+                    self.histograms.remove(histogram);
+                    true
+                }
+                None => false,
+            },
+        }
+    }
+}
+
+/// Name of the buffer or histogram `operation` refers to, if any
+fn operation_buffer(operation: Operation) -> Option<&'static str> {
+    match operation {
+        Operation::Alloc(name, _)
+        | Operation::Free(name)
+        | Operation::BufferGet(name, _)
+        | Operation::BufferSet(name, _)
+        | Operation::CopyToBuffer(_, name, _)
+        | Operation::CopyFromBuffer(name, _, _)
+        | Operation::AllocHistogram(name, _)
+        | Operation::FreeHistogram(name)
+        | Operation::HistogramGet(name, _)
+        | Operation::HistogramSet(name, _, _) => Some(name),
+        _ => None,
+    }
+}
+
+/// Buffer names read from a trace, leaked once each so they can live in `Operation`s
+#[derive(Default)]
+struct Names(Vec<&'static str>);
+impl Names {
+    fn intern(&mut self, name: &str) -> &'static str {
+        match self.0.iter().find(|&&n| n == name) {
+            Some(&n) => n,
+            None => {
+                let n: &'static str = Box::leak(name.to_string().into_boxed_str());
+                self.0.push(n);
+                n
+            }
+        }
+    }
+}
+
+fn invalid_data(message: &str) -> Error {
+    Error::new(ErrorKind::InvalidData, message)
+}
+
+fn invalid_line(number: usize) -> Error {
+    Error::new(
+        ErrorKind::InvalidData,
+        format!("Invalid trace line {}", number),
+    )
+}
+
+fn parse<N>(text: &str, number: usize) -> std::io::Result<N>
+where
+    N: std::str::FromStr,
+{
+    text.parse::<N>().map_err(|_| invalid_line(number))
+}
+
+fn write_varint(writer: &mut dyn Write, value: u64) -> std::io::Result<()> {
+    let mut value = value;
+    loop {
+        let byte = (value & 0x7F) as u8;
+        value >>= 7;
+        if value == 0 {
+            return writer.write_all(&[byte]);
+        }
+        writer.write_all(&[byte | 0x80])?;
+    }
+}
+
+fn read_varint(reader: &mut dyn Read) -> std::io::Result<u64> {
+    let mut value = 0u64;
+    let mut shift = 0;
+    loop {
+        let mut byte = [0];
+        reader.read_exact(&mut byte)?;
+        if shift >= 64 {
+            return Err(invalid_data("Integer too large"));
+        }
+        value |= u64::from(byte[0] & 0x7F) << shift;
+        if byte[0] & 0x80 == 0 {
+            return Ok(value);
+        }
+        shift += 7;
+    }
+}
+
+fn read_value(reader: &mut dyn Read) -> std::io::Result<u32> {
+    let value = read_varint(reader)?;
+    if value > u64::from(u32::MAX) {
+        return Err(invalid_data("Value too large"));
+    }
+    Ok(value as u32)
+}
+
+fn write_string(writer: &mut dyn Write, text: &str) -> std::io::Result<()> {
+    write_varint(writer, text.len() as u64)?;
+    writer.write_all(text.as_bytes())
+}
+
+fn read_string(reader: &mut dyn Read) -> std::io::Result<String> {
+    let length = read_varint(reader)?;
+    if length > MAX_STRING_LENGTH as u64 {
+        return Err(invalid_data("String too long"));
+    }
+    let mut bytes = vec![0; length as usize];
+    reader.read_exact(&mut bytes)?;
+    String::from_utf8(bytes).map_err(|_| invalid_data("Invalid UTF-8 string"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::find_algorithm;
+    fn record(name: &str) -> Trace<u32> {
+        let data = vec![1, 4, 123, 7, 8, 4, 8, 8, 2, 4, 57, 8, 324, 213];
+        let mut sorted = data.clone();
+        let mut list = TracingList::new(&mut sorted);
+        (find_algorithm(name).unwrap().sort_fn)(&mut list);
+        Trace {
+            algorithm: name.to_string(),
+            data,
+            steps: list.into_steps(),
+        }
+    }
+    fn check_replay(trace: &Trace<u32>) {
+        let mut expected = trace.data.clone();
+        expected.sort_unstable();
+        let mut replayed = trace.data.clone();
+        trace.replay(&mut replayed);
+        assert_eq!(expected, replayed);
+        // Replaying through a TracingList reproduces the same steps
+        let mut replayed = trace.data.clone();
+        let mut list = TracingList::new(&mut replayed);
+        trace.replay(&mut list);
+        assert_eq!(trace.steps, list.into_steps());
+    }
+    #[test]
+    fn test_trace_text() {
+        for name in [
+            "quicksort",
+            "mergesort",
+            "lsd_radixsort",
+            "countingsort",
+            "blocksort",
+        ]
+        .iter()
+        {
+            let trace = record(name);
+            check_replay(&trace);
+            let mut text = Vec::new();
+            trace.write_text(&mut text).unwrap();
+            let read = Trace::<u32>::read(&mut &text[..]).unwrap();
+            assert_eq!(trace, read);
+            check_replay(&read);
+        }
+    }
+    #[test]
+    fn test_trace_binary() {
+        for name in [
+            "quicksort",
+            "mergesort",
+            "lsd_radixsort",
+            "countingsort",
+            "blocksort",
+        ]
+        .iter()
+        {
+            let trace = record(name);
+            let mut binary = Vec::new();
+            trace.write_binary(&mut binary).unwrap();
+            let read = Trace::<u32>::read(&mut &binary[..]).unwrap();
+            assert_eq!(trace, read);
+            check_replay(&read);
+        }
+    }
+    #[test]
+    fn test_trace_invalid() {
+        let text = "trace 1\nalgorithm x\ndata 1\n5\noperations\nSwap 0\n";
+        assert!(Trace::<u32>::read(&mut text.as_bytes()).is_err());
+        assert!(Trace::<u32>::read(&mut &b"SRTB\x01\x01"[..]).is_err());
+    }
+    #[test]
+    fn test_trace_out_of_bounds() {
+        let read_text = |operations: &str| {
+            let text = format!(
+                "trace 1\nalgorithm x\ndata 2\n5\n3\noperations\n{}\n",
+                operations
+            );
+            Trace::<u32>::read(&mut text.as_bytes())
+        };
+        assert!(read_text(
+            "Swap 0 1\nAlloc a 2\nCopyToBuffer 1 a 1\nBufferGet a 1\nCopyFromBuffer a 1 0\nFree a"
+        )
+        .is_ok());
+        for operations in [
+            "Swap 0 99999",
+            "Get 2",
+            "Reverse 1 3",
+            "Rotate 0 2 3",
+            "BlockSwap 0 1 2",
+            "BufferGet a 0",
+            "Alloc a 2\nBufferSet a 2 7",
+            "Alloc a 2\nFree a\nCopyFromBuffer a 0 0",
+            "AllocHistogram h 4\nHistogramSet h 4 1",
+            "FreeHistogram h",
+            "Alloc a 99999999999",
+            "Alloc a 2\nBufferGet a 0",
+            "Alloc a 2\nCopyFromBuffer a 0 0",
+            "Alloc a 2\nAllocHistogram a 2",
+        ]
+        .iter()
+        {
+            let error = read_text(operations).unwrap_err();
+            assert_eq!(error.kind(), ErrorKind::InvalidData, "{}", operations);
+        }
+        // Truncated or corrupted lengths are errors rather than huge allocations
+        let mut binary = Vec::new();
+        record("mergesort").write_binary(&mut binary).unwrap();
+        let mut huge_data = BINARY_MAGIC.to_vec();
+        write_string(&mut huge_data, "x").unwrap();
+        write_varint(&mut huge_data, u64::MAX >> 1).unwrap();
+        assert!(Trace::<u32>::read(&mut &huge_data[..]).is_err());
+        let mut huge_name = BINARY_MAGIC.to_vec();
+        write_varint(&mut huge_name, u64::MAX >> 1).unwrap();
+        assert!(Trace::<u32>::read(&mut &huge_name[..]).is_err());
+        for length in 0..binary.len() {
+            assert!(Trace::<u32>::read(&mut &binary[..length]).is_err());
+        }
+    }
+}
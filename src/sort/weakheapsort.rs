@@ -0,0 +1,97 @@
+use super::List;
+use std::cmp::Ordering;
+
+/// Weak heap over a list. Every element is not smaller than any element in its right subtree,
+/// and a reverse bit per element tells which of its two children is the right one.
+struct WeakHeap {
+    reverse: Vec<bool>,
+}
+impl WeakHeap {
+    /// Index of the left child of `index`
+    fn left_child(&self, index: usize) -> usize {
+        2 * index + self.reverse[index] as usize
+    }
+    /// Closest ancestor of `index` which has it in its right subtree
+    fn distinguished_ancestor(&self, index: usize) -> usize {
+        let mut index = index;
+        while (index & 1) == self.reverse[index >> 1] as usize {
+            index >>= 1;
+        }
+        index >> 1
+    }
+    /// Make sure the element at `ancestor` is not smaller than that at `index`, by swapping
+    /// them and exchanging the subtrees of `index` if needed
+    fn join<T>(&mut self, list: &mut dyn List<T>, ancestor: usize, index: usize)
+    where
+        T: Copy + Ord + std::fmt::Display,
+    {
+        if list.compare(ancestor, index) == Ordering::Less {
+            list.swap(ancestor, index);
+            self.reverse[index] = !self.reverse[index];
+        }
+    }
+}
+
+/// Heapsort over a weak heap, which needs about n log n comparisons in the worst case
+pub fn weakheapsort<T>(list: &mut dyn List<T>)
+where
+    T: Copy + Ord + std::fmt::Display,
+{
+    let length = list.len();
+    if length < 2 {
+        return;
+    }
+    let mut heap = WeakHeap {
+        reverse: vec![false; length],
+    };
+    for index in (1..length).rev() {
+        let ancestor = heap.distinguished_ancestor(index);
+        heap.join(list, ancestor, index);
+    }
+    for last in (1..length).rev() {
+        // Move the current maximum to the end
+        list.swap(0, last);
+        if last < 2 {
+            break;
+        }
+        // Join the new root with every element on the path of left children from index 1
+        let mut index = 1;
+        while heap.left_child(index) < last {
+            index = heap.left_child(index);
+        }
+        while index > 0 {
+            heap.join(list, 0, index);
+            index >>= 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::CountingList;
+    #[test]
+    fn test_weakheapsort() {
+        let mut test_slice = vec![1, 4, 123, 7, 8, 4, 8, 8, 2, 4, 57, 8, 324, 213];
+        let mut test_slice2 = test_slice.clone();
+        assert_eq!(test_slice, test_slice2);
+        test_slice.sort_unstable();
+        weakheapsort(&mut test_slice2);
+        assert_eq!(test_slice, test_slice2);
+        println!("{:?}", test_slice2);
+    }
+    #[test]
+    fn test_weakheapsort_inputs() {
+        let sorted: Vec<u32> = (0..1000).collect();
+        let reversed: Vec<u32> = (0..1000).rev().collect();
+        let random: Vec<u32> = (0..1000).map(|i| (i * 7919) % 1000).collect();
+        for test_slice in [sorted.clone(), reversed, random].iter() {
+            let mut test_slice2 = test_slice.clone();
+            let mut list = CountingList::new(&mut test_slice2);
+            weakheapsort(&mut list);
+            // Fewer than n k - 2^k + n comparisons, where k is log n rounded up
+            assert!(list.statistics().compares < 1000 * 10 - 1024 + 1000);
+            assert_eq!(test_slice2, sorted);
+        }
+    }
+}